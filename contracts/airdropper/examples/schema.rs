@@ -3,7 +3,7 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, schema_for};
 
-use airdropper::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use airdropper::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use airdropper::state::Config;
 
 use airdropper::msg::{
@@ -20,6 +20,7 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(Config), &out_dir);
 
     export_schema_with_title(&schema_for!(Config), &out_dir, "GetConfigResponse");
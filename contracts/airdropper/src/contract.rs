@@ -1,10 +1,13 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{entry_point, Addr, Deps, DepsMut, Env, MessageInfo, Response};
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw_utils::maybe_addr;
+use semver::Version;
 
 use crate::error::ContractError;
-use crate::msg::{AddressTokenMsg, AddressValMsg, ExecuteMsg, InstantiateMsg, TokenMsg};
+use crate::msg::{
+    AddressTokenMsg, AddressValMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, TokenMsg,
+};
 use crate::state::{
     Config, ADDRESS_CLAIMED_PROMISED_MINTS, ADDRESS_CLAIMED_TOKEN_IDS, ADDRESS_PROMISED_MINTS,
     ADDRESS_PROMISED_TOKEN_IDS, ASSIGNED_TOKEN_IDS, CLAIMED_TOKEN_IDS, CONFIG,
@@ -76,11 +79,59 @@ pub fn execute(
         }
         ExecuteMsg::MarkTokenIDClaimed(msg) => execute_mark_token_id_claimed(deps, env, info, msg),
         ExecuteMsg::IncrementAddressClaimedPromisedMintCount(address) => {
-            execute_increment_address_promised_mint_count(deps, env, info, address)
+            execute_increment_address_promised_mint_count(deps, env, info, address, 1)
+        }
+        ExecuteMsg::IncrementAddressClaimedPromisedMintCountBy(address, count) => {
+            execute_increment_address_promised_mint_count(deps, env, info, address, count)
         }
     }
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+
+    // only allow migrations from a previous version of this contract
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidMigrationContract {
+            stored: stored.contract,
+            expected: CONTRACT_NAME.to_string(),
+        });
+    }
+
+    let stored_version = parse_contract_version(&stored.version)?;
+    let current_version = parse_contract_version(CONTRACT_VERSION)?;
+
+    if stored_version > current_version {
+        return Err(ContractError::CannotMigrateToOlderVersion {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    migrate_state(deps.branch(), &stored_version)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+/// Upgrades airdropper storage written by `stored_version`, one release at a time
+fn migrate_state(_deps: DepsMut, _stored_version: &Version) -> Result<(), ContractError> {
+    // 0.1.x -> 0.2.0 only added `IncrementAddressClaimedPromisedMintCountBy`. the
+    // config and the promise/claim maps are read as 0.1.x wrote them
+    Ok(())
+}
+
+fn parse_contract_version(version: &str) -> Result<Version, ContractError> {
+    Version::parse(version).map_err(|_| ContractError::InvalidContractVersion {
+        version: version.to_string(),
+    })
+}
+
 fn execute_update_config(
     deps: DepsMut,
     env: Env,
@@ -352,6 +403,7 @@ fn execute_increment_address_promised_mint_count(
     env: Env,
     info: MessageInfo,
     address: String,
+    count: u32,
 ) -> Result<Response, ContractError> {
     check_can_update(deps.as_ref(), &env, &info)?;
 
@@ -364,11 +416,11 @@ fn execute_increment_address_promised_mint_count(
         (ADDRESS_CLAIMED_PROMISED_MINTS.may_load(deps.storage, addr.clone())?).unwrap_or(0);
 
     // check promised mint vs address' current mint count
-    if current_mint_count >= promised_mint_count {
+    if current_mint_count + count > promised_mint_count {
         return Err(ContractError::ReachedMaxMints(promised_mint_count));
     }
 
-    ADDRESS_CLAIMED_PROMISED_MINTS.save(deps.storage, addr, &(current_mint_count + 1))?;
+    ADDRESS_CLAIMED_PROMISED_MINTS.save(deps.storage, addr, &(current_mint_count + count))?;
 
     Ok(Response::new()
         .add_attribute("method", "increment_address_promised_mint_count")
@@ -14,7 +14,8 @@ mod tests {
             crate::contract::execute,
             crate::contract::instantiate,
             crate::query::query,
-        );
+        )
+        .with_migrate(crate::contract::migrate);
         Box::new(contract)
     }
 
@@ -59,7 +60,7 @@ mod tests {
                 &msg,
                 &[],
                 "test",
-                None,
+                Some(ADMIN.to_string()),
             )
             .unwrap();
 
@@ -1047,6 +1048,200 @@ mod tests {
             assert_eq!(promised_mints_claimed_response[0].value, 2);
             assert_eq!(promised_mints_claimed_response[1].address, USER3.to_owned());
             assert_eq!(promised_mints_claimed_response[1].value, 1);
+
+            // USER has 2 of 4 left, so claiming 3 at once fails
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::IncrementAddressClaimedPromisedMintCountBy(USER.to_owned(), 3),
+                &[],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::IncrementAddressClaimedPromisedMintCountBy(USER.to_owned(), 2),
+                &[],
+            )
+            .unwrap();
+
+            let promised_mints_claimed_response: Vec<AddressValMsg> = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw_template_contract.addr(),
+                    &QueryMsg::GetClaimedAddressPromisedMints {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(promised_mints_claimed_response[0].value, 4);
+        }
+    }
+
+    mod migrate {
+        use super::*;
+        use crate::contract::{execute, migrate};
+        use crate::msg::{CheckAirdropPromisedMintResponse, MigrateMsg};
+        use crate::query::query;
+        use crate::state::{ADDRESS_CLAIMED_PROMISED_MINTS, ADDRESS_PROMISED_MINTS};
+        use crate::ContractError;
+        use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+        use cosmwasm_std::{from_binary, Storage};
+        use cw2::{get_contract_version, set_contract_version};
+
+        #[test]
+        fn migrate_with_promises() {
+            let (mut app, cw_template_contract) = proper_instantiate();
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::AddPromisedTokenIDs(vec![AddressTokenMsg {
+                    address: USER1.to_string(),
+                    token: TokenMsg {
+                        collection_id: 101,
+                        token_id: 7,
+                    },
+                }]),
+                &[],
+            )
+            .unwrap();
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::AddPromisedMints(vec![AddressValMsg {
+                    address: USER2.to_string(),
+                    value: 3,
+                }]),
+                &[],
+            )
+            .unwrap();
+
+            let new_code_id = app.store_code(contract_template());
+
+            app.migrate_contract(
+                Addr::unchecked(MAINTAINER),
+                cw_template_contract.addr(),
+                &MigrateMsg {},
+                new_code_id,
+            )
+            .unwrap_err();
+
+            app.migrate_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &MigrateMsg {},
+                new_code_id,
+            )
+            .unwrap();
+
+            let config: Config = app
+                .wrap()
+                .query_wasm_smart(&cw_template_contract.addr(), &QueryMsg::GetConfig {})
+                .unwrap();
+            assert_eq!(config.maintainer_addr, Some(Addr::unchecked(MAINTAINER)));
+
+            let promised_token_ids: Vec<AddressPromisedTokensResponse> = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw_template_contract.addr(),
+                    &QueryMsg::GetAddressPromisedTokenIDs {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                promised_token_ids,
+                vec![AddressPromisedTokensResponse {
+                    address: USER1.to_string(),
+                    token_ids: vec![TokenMsg {
+                        collection_id: 101,
+                        token_id: 7,
+                    }],
+                }]
+            );
+
+            let promised_mints: Vec<AddressValMsg> = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw_template_contract.addr(),
+                    &QueryMsg::GetAddressPromisedMints {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                promised_mints,
+                vec![AddressValMsg {
+                    address: USER2.to_string(),
+                    value: 3,
+                }]
+            );
+        }
+
+        #[test]
+        fn migrate_from_0_1_state() {
+            let mut deps = mock_dependencies();
+
+            // storage as 0.1.0 wrote it
+            deps.storage.set(
+                b"config",
+                br#"{"admin":"admin","maintainer_addr":"maintainer","start_time":"1571797419879305533","end_time":null}"#,
+            );
+            ADDRESS_PROMISED_MINTS
+                .save(deps.as_mut().storage, Addr::unchecked(USER2), &3)
+                .unwrap();
+            ADDRESS_CLAIMED_PROMISED_MINTS
+                .save(deps.as_mut().storage, Addr::unchecked(USER2), &1)
+                .unwrap();
+            set_contract_version(deps.as_mut().storage, "crates.io:airdropper", "0.1.0").unwrap();
+
+            migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+            let version = get_contract_version(deps.as_ref().storage).unwrap();
+            assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+            // a minter can count a multi-token claim against the 0.1.0 promises
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(MAINTAINER, &[]),
+                ExecuteMsg::IncrementAddressClaimedPromisedMintCountBy(USER2.to_string(), 2),
+            )
+            .unwrap();
+
+            let res: CheckAirdropPromisedMintResponse = from_binary(
+                &query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::CheckAddressPromisedMints {
+                        minter_address: USER2.to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(res.promised_mint_count, 3);
+            assert_eq!(res.claimed_mint_count, 3);
+        }
+
+        #[test]
+        fn fail_migrate_older_version() {
+            let mut deps = mock_dependencies();
+            set_contract_version(deps.as_mut().storage, "crates.io:airdropper", "1.0.0").unwrap();
+
+            let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+
+            assert!(matches!(
+                err,
+                ContractError::CannotMigrateToOlderVersion { .. }
+            ));
         }
     }
 }
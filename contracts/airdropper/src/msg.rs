@@ -38,8 +38,16 @@ pub enum ExecuteMsg {
     MarkTokenIDClaimed(AddressTokenMsg),
     /// Increments an address' claimed promised mint count
     IncrementAddressClaimedPromisedMintCount(String),
+    /// Increments an address' claimed promised mint count by the number of
+    /// tokens minted. Sent by minters for multi-token mints
+    IncrementAddressClaimedPromisedMintCountBy(String, u32),
 }
 
+/// Migration message. The state transformations that run are selected from the
+/// cw2 version stored in the contract, so no fields are required
+#[cw_serde]
+pub struct MigrateMsg {}
+
 #[cw_serde]
 pub enum QueryMsg {
     /// Gets `state::Config` and returns it
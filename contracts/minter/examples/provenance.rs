@@ -0,0 +1,36 @@
+use std::env::args;
+use std::fs::{read, read_dir};
+use std::path::PathBuf;
+
+use minter::provenance::provenance_hash;
+
+/// computes a collection's provenance hash from a folder of metadata files named
+/// after their token_id, with or without an extension (`1`, `2.json`, ...)
+///
+/// cargo run --example provenance -- ./metadata
+fn main() {
+    let dir = args()
+        .nth(1)
+        .expect("usage: cargo run --example provenance -- <metadata folder>");
+
+    let mut files: Vec<(u32, PathBuf)> = read_dir(dir)
+        .unwrap()
+        .filter_map(|entry| {
+            let path = entry.unwrap().path();
+            let token_id = path.file_stem()?.to_str()?.parse::<u32>().ok()?;
+            Some((token_id, path))
+        })
+        .collect();
+    files.sort();
+
+    for (index, (token_id, path)) in files.iter().enumerate() {
+        if *token_id != index as u32 + 1 {
+            panic!("expected token_id {} but found {:?}", index + 1, path);
+        }
+    }
+
+    let metadata: Vec<Vec<u8>> = files.iter().map(|(_, path)| read(path).unwrap()).collect();
+
+    println!("tokens: {}", metadata.len());
+    println!("provenance_hash: {}", provenance_hash(&metadata));
+}
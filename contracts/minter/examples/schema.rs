@@ -4,7 +4,7 @@ use std::env::current_dir;
 use std::fs::create_dir_all;
 
 use minter::msg::{AddrBal, AddressValMsg, ConfigResponse, TokenDataResponse};
-use minter::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use minter::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use minter::state::{CollectionInfo, Config};
 
 fn main() {
@@ -16,6 +16,7 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(Config), &out_dir);
     export_schema_with_title(&schema_for!(ConfigResponse), &out_dir, "GetConfigResponse");
     export_schema_with_title(
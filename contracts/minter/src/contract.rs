@@ -1,18 +1,32 @@
 use crate::error::ContractError;
 use crate::msg::{
-    AddrBal, AddressValMsg, Admin, BaseInitMsg, CollectionInfoMsg, ExecuteMsg, ExecutionTarget,
-    InstantiateMsg, MintType, ModuleInstantiateInfo, RoyaltyInfoMsg, SharedCollectionInfoMsg,
-    TokenMsg,
+    AddrBal, AddressValMsg, Admin, BaseInitMsg, CollectionInfoMsg, Cw20PaymentMsg, ExecuteMsg,
+    ExecutionTarget, InstantiateMsg, MigrateMsg, MintCodeMsg, MintType, ModuleInstantiateInfo,
+    OraclePriceResponse, OraclePricingMsg, OracleQueryMsg, ProxyExecuteMsg, RandomnessCallback,
+    ReceiveMsg, RoyaltyInfoMsg, SharedCollectionInfoMsg, TokenMsg,
 };
 use crate::state::{
-    CollectionInfo, Config, RoyaltyInfo, SharedCollectionInfo, ADDRESS_MINT_TRACKER,
-    AIRDROPPER_ADDR, BANK_BALANCES, BUNDLE_MINT_TRACKER, COLLECTION_CURRENT_TOKEN_SUPPLY, CONFIG,
-    CURRENT_TOKEN_SUPPLY, CW721_ADDRS, CW721_COLLECTION_INFO, CW721_SHUFFLED_TOKEN_IDS,
-    FEE_COLLECTION_ADDR, TOTAL_TOKEN_SUPPLY, WHITELIST_ADDR,
+    AuctionPurchase, BondingCurve, BulkDiscounts, CollectionInfo, CollectionSelection, Config,
+    Cw20Payment, DiscountTier, DrandBeacon, DrandConfig, DrawKind, DrawRecord, DutchAuction,
+    LegacyCollectionInfo, MinimumRaise, MintCode, MintCodeDiscount, MintReceipt, MintTicket,
+    OraclePricing, PendingMint, RaffleEntry, RaffleProgress, RandomnessCommit, Referral,
+    RoyaltyInfo, SharedCollectionInfo, ShuffleProgress, TokenAuction, TokenAuctionBid,
+    TokenOrderMigration, ADDRESS_MINT_TRACKER, AIRDROPPER_ADDR, AUCTION_CLEARING_PRICE,
+    AUCTION_LAST_PRICE, AUCTION_PURCHASES, BANK_BALANCES, BUNDLE_MINT_TRACKER,
+    COLLECTION_CURRENT_TOKEN_SUPPLY, CONFIG, CURRENT_TOKEN_SUPPLY, CW721_ADDRS,
+    CW721_COLLECTION_INFO, CW721_SHUFFLED_TOKEN_IDS, CW721_TOKEN_ID_POSITIONS,
+    DELAYED_MINT_EXPIRY_BLOCKS, DRAND_BEACONS, DRAND_CONFIG, DRAW_COUNTER, DRAW_HISTORY,
+    ESCROW_LOCKED_UNTIL, FEE_COLLECTION_ADDR, LEGACY_BANK_BALANCES, LEGACY_CONFIG,
+    LEGACY_CW721_COLLECTION_INFO, LEGACY_CW721_SHUFFLED_TOKEN_IDS, MINT_CODES, MINT_JOB_COUNTER,
+    MINT_ORDER_COUNTER, MINT_RECEIPTS, MINT_TICKETS, PENDING_MINTS, PENDING_MINT_COUNT,
+    RAFFLE_ENTRIES, RAFFLE_PROGRESS, RAFFLE_TICKETS, RAISE_CONTRIBUTIONS, RAISE_PROGRESS,
+    RANDOMNESS_COMMIT, RANDOMNESS_PROXY, RANDOMNESS_SECRETS, REFERRALS, SHUFFLE_PROGRESS,
+    TOKEN_AUCTIONS, TOKEN_MINT_ORDER, TOKEN_ORDER_MIGRATION, TOTAL_TOKEN_SUPPLY, WHITELIST_ADDR,
 };
 use airdropper::{
     msg::ExecuteMsg::{
         IncrementAddressClaimedPromisedMintCount as AD_IncrementAddressClaimedPromisedMintCount,
+        IncrementAddressClaimedPromisedMintCountBy as AD_IncrementAddressClaimedPromisedMintCountBy,
         MarkTokenIDClaimed as AD_MarkTokenIDClaimed,
         UpdateMaintainerAddress as AD_UpdateMaintainerAddress,
     },
@@ -23,24 +37,30 @@ use airdropper::{
     },
 };
 use cosmwasm_std::{
-    coin, entry_point, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Empty, Env,
-    MessageInfo, Order, Reply, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    coin, entry_point, from_binary, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal,
+    Deps, DepsMut, Empty, Env, MessageInfo, Order, OverflowError, OverflowOperation, Reply,
+    Response, StdError, StdResult, Storage, SubMsg, Timestamp, Uint128, Uint256, WasmMsg,
 };
 
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+use cw721::Cw721ReceiveMsg;
 use cw721_base::{
     msg::ExecuteMsg as Cw721ExecuteMsg, msg::InstantiateMsg as Cw721InstantiateMsg, MintMsg,
 };
+use cw_storage_plus::Bound;
 use cw_utils::{may_pay, maybe_addr, must_pay, parse_reply_instantiate_data};
+use drand_verify::{derive_randomness, g1_from_variable, verify};
 use rand_core::{RngCore, SeedableRng};
 use rand_xoshiro::Xoshiro128PlusPlus;
+use semver::Version;
 use sha2::{Digest, Sha256};
-use shuffle::{fy::FisherYates, shuffler::Shuffler};
 use std::cmp;
 use whitelist::{
     msg::CheckWhitelistResponse,
     msg::ExecuteMsg::{
         UpdateAddressMintTracker as WL_UpdateAddressMintTracker,
+        UpdateAddressMintTrackerBy as WL_UpdateAddressMintTrackerBy,
         UpdateMaintainerAddress as WL_UpdateMaintainerAddress,
     },
     msg::QueryMsg as WhitelistQueryMsg,
@@ -90,6 +110,8 @@ struct MintParametersResponse {
     /// Used for Airdrop Promised Token IDs to track which ones have not been claimed
     /// by the address that they were promised to
     remaining_token_ids: Vec<TokenMsg>,
+    /// tokens the address already minted in this phase
+    current_mint_count: u32,
 }
 
 /// Default fee collection address if no DAO address is provided
@@ -98,6 +120,28 @@ const DEFAULT_FEE_COLLECTION_ADDRESS: &str = "juno1jv65s3grqf6v6jl3dp4t6c9t9rk99
 /// default fee amount assumes 6 decimal
 const DEFAULT_FEE_AMOUNT: u128 = 1_000_000u128;
 
+/// swaps per `ShuffleTokenOrder` call
+const DEFAULT_SHUFFLE_LIMIT: u32 = 1_000;
+const MAX_SHUFFLE_LIMIT: u32 = 10_000;
+
+/// positions per `MigrateTokenOrder` call
+const DEFAULT_TOKEN_ORDER_MIGRATION_LIMIT: u32 = 1_000;
+const MAX_TOKEN_ORDER_MIGRATION_LIMIT: u32 = 10_000;
+
+/// blocks a ticket paid against a commitment can wait for the reveal, unless
+/// `ConfigureDelayedMint` sets its own expiry
+const DEFAULT_MINT_TICKET_EXPIRY_BLOCKS: u64 = 14_400;
+
+/// winners drawn per `SettleRaffle` call
+const DEFAULT_RAFFLE_SETTLE_LIMIT: u32 = 25;
+const MAX_RAFFLE_SETTLE_LIMIT: u32 = 100;
+
+/// keeps the sum of collection weights well inside u32
+const MAX_COLLECTION_WEIGHT: u32 = 50_000;
+
+/// payments and escrow of a cw20 token use the denom `cw20:<token_addr>`
+const CW20_DENOM_PREFIX: &str = "cw20:";
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -155,10 +199,28 @@ pub fn instantiate(
         validate_shared_collection_info(deps.as_ref(), msg.extension)?;
 
     // validate the denom the user selected is one that is allowed.
-    // cw20 banned
+    // cw20 payments are configured separately in `cw20_payment`
 
     validate_native_denom(msg.base_fields.mint_denom.clone())?;
 
+    if let Some(denom_mint_prices) = &msg.base_fields.denom_mint_prices {
+        validate_denom_mint_prices(&msg.base_fields.mint_denom, denom_mint_prices)?;
+    }
+
+    let cw20_payment = validate_cw20_payment(deps.as_ref(), msg.base_fields.cw20_payment)?;
+    let oracle_pricing = validate_oracle_pricing(deps.as_ref(), msg.base_fields.oracle_pricing)?;
+
+    validate_pricing_modes(
+        &msg.base_fields.dutch_auction,
+        &msg.base_fields.bonding_curve,
+        &msg.base_fields.bulk_discounts,
+        &msg.base_fields.denom_mint_prices,
+        &cw20_payment,
+        &oracle_pricing,
+    )?;
+
+    validate_referral_bps(msg.base_fields.referral_bps)?;
+
     // TODO: add required fee that goes to neta dao's treasury dao OR if the treasury dao
     // is included in rev share then allow this to bypass
     /*
@@ -191,8 +253,23 @@ pub fn instantiate(
         bundle_enabled: msg.base_fields.bundle_enabled,
         bundle_completed: false,
         bonded_denom,
+        dutch_auction: msg.base_fields.dutch_auction,
+        bonding_curve: msg.base_fields.bonding_curve,
+        bulk_discounts: msg.base_fields.bulk_discounts,
+        collection_selection: msg.base_fields.collection_selection,
+        denom_mint_prices: msg.base_fields.denom_mint_prices,
+        cw20_payment,
+        oracle_pricing,
+        referral_bps: msg.base_fields.referral_bps,
+        refund_period: msg.base_fields.refund_period,
+        minimum_raise: msg.base_fields.minimum_raise,
+        raffle: msg.base_fields.raffle,
     };
 
+    validate_refund_policy(&config)?;
+    validate_minimum_raise(deps.storage, &config)?;
+    validate_raffle(&config)?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     CONFIG.save(deps.storage, &config)?;
@@ -275,16 +352,8 @@ pub fn instantiate(
             &coll_info.token_supply,
         )?;
 
-        // shuffle
-
-        let shuffled_token_ids: Vec<u32> = shuffle_token_ids(
-            &env,
-            info.sender.clone(),
-            (1..=coll_info.token_supply).collect::<Vec<u32>>(),
-            coll_info.id,
-        )?;
-
-        CW721_SHUFFLED_TOKEN_IDS.save(deps.storage, coll_info.id, &shuffled_token_ids)?;
+        // nothing to shuffle up front. the swap table starts out empty, meaning every
+        // position still holds its original token_id, and each draw is uniform anyway
     }
 
     Ok(Response::new()
@@ -302,6 +371,15 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // `cw20:` payments can only come from `Receive`
+    if info
+        .funds
+        .iter()
+        .any(|payment| payment.denom.starts_with(CW20_DENOM_PREFIX))
+    {
+        return Err(ContractError::InvalidCw20Token {});
+    }
+
     // mint
     match msg {
         ExecuteMsg::UpdateConfig(msg) => execute_update_config(deps, env, info, msg),
@@ -311,19 +389,133 @@ pub fn execute(
         ExecuteMsg::Mint {
             is_promised_mint,
             minter_address,
-        } => execute_mint(deps, env, info, is_promised_mint, minter_address),
-        ExecuteMsg::MintBundle {} => execute_mint_bundle(deps, env, info),
+            quantity,
+            collection_id,
+            referrer,
+            code,
+        } => execute_mint(
+            deps,
+            env,
+            info,
+            is_promised_mint,
+            minter_address,
+            quantity,
+            collection_id,
+            referrer,
+            code,
+        ),
+        ExecuteMsg::MintBundle { referrer } => execute_mint_bundle(deps, env, info, referrer),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::ReceiveNft(msg) => execute_receive_nft(deps, env, info, msg),
         ExecuteMsg::AirdropClaim { minter_address } => {
             execute_airdrop_token_distribution(deps, env, info, minter_address)
         }
         ExecuteMsg::CleanClaimedTokensFromShuffle {} => {
             execute_clean_claimed_tokens_from_shuffle(deps, env, info)
         }
-        ExecuteMsg::ShuffleTokenOrder {} => execute_shuffle_token_order(deps, env, info),
+        ExecuteMsg::ShuffleTokenOrder { limit } => {
+            execute_shuffle_token_order(deps, env, info, limit)
+        }
+        ExecuteMsg::MigrateTokenOrder { limit } => {
+            execute_migrate_token_order(deps, env, info, limit)
+        }
         ExecuteMsg::SubmoduleHook(target, msg) => {
             execute_submodule_hook(deps, env, info, target, msg)
         }
         ExecuteMsg::DisburseFunds {} => execute_disburse_funds(deps, env, info),
+        ExecuteMsg::ClaimRaiseRefund {} => execute_claim_raise_refund(deps, env, info),
+        ExecuteMsg::EnterRaffle { tickets } => execute_enter_raffle(deps, env, info, tickets),
+        ExecuteMsg::SettleRaffle { limit } => execute_settle_raffle(deps, env, info, limit),
+        ExecuteMsg::ClaimRaffleRefund {} => execute_claim_raffle_refund(deps, env, info),
+        ExecuteMsg::CreateTokenAuction {
+            collection_id,
+            token_id,
+            reserve_price,
+            min_increment,
+            start_time,
+            end_time,
+            extension,
+        } => execute_create_token_auction(
+            deps,
+            env,
+            info,
+            TokenAuction {
+                collection_id,
+                token_id,
+                denom: String::new(),
+                reserve_price,
+                min_increment,
+                start_time,
+                end_time,
+                extension,
+                highest_bid: None,
+                settled: false,
+            },
+        ),
+        ExecuteMsg::BidTokenAuction {
+            collection_id,
+            token_id,
+        } => execute_bid_token_auction(deps, env, info, collection_id, token_id),
+        ExecuteMsg::SettleTokenAuction {
+            collection_id,
+            token_id,
+        } => execute_settle_token_auction(deps, env, info, collection_id, token_id),
+        ExecuteMsg::CommitRandomness { commitment } => {
+            execute_commit_randomness(deps, env, info, commitment)
+        }
+        ExecuteMsg::RevealRandomness { secret } => {
+            execute_reveal_randomness(deps, env, info, secret)
+        }
+        ExecuteMsg::ConfigureDrand {
+            pubkey,
+            genesis_time,
+            period,
+        } => execute_configure_drand(deps, env, info, pubkey, genesis_time, period),
+        ExecuteMsg::SubmitDrandBeacon {
+            round,
+            previous_signature,
+            signature,
+        } => execute_submit_drand_beacon(deps, env, info, round, previous_signature, signature),
+        ExecuteMsg::ConfigureRandomnessProxy { address } => {
+            execute_configure_randomness_proxy(deps, env, info, address)
+        }
+        ExecuteMsg::ReceiveRandomness { callback } => {
+            execute_receive_randomness(deps, env, info, callback)
+        }
+        ExecuteMsg::RefundPendingMint { job_id } => {
+            execute_refund_pending_mint(deps, env, info, job_id)
+        }
+        ExecuteMsg::ConfigureDelayedMint { expiry_blocks } => {
+            execute_configure_delayed_mint(deps, env, info, expiry_blocks)
+        }
+        ExecuteMsg::SettleMintTicket {
+            minter_address,
+            height,
+        } => execute_settle_mint_ticket(deps, env, info, minter_address, height),
+        ExecuteMsg::RefundMintTicket {
+            minter_address,
+            height,
+        } => execute_refund_mint_ticket(deps, env, info, minter_address, height),
+        ExecuteMsg::SettleAuctionRebate { minter_address } => {
+            execute_settle_auction_rebate(deps, env, info, minter_address)
+        }
+        ExecuteMsg::UpdateCollectionMint {
+            collection_id,
+            mint_price,
+            mint_enabled,
+        } => {
+            execute_update_collection_mint(deps, env, info, collection_id, mint_price, mint_enabled)
+        }
+        ExecuteMsg::UpdateProvenanceHash {
+            collection_id,
+            provenance_hash,
+        } => execute_update_provenance_hash(deps, env, info, collection_id, provenance_hash),
+        ExecuteMsg::RegisterMintCodes { codes } => {
+            execute_register_mint_codes(deps, env, info, codes)
+        }
+        ExecuteMsg::RemoveMintCodes { hashes } => {
+            execute_remove_mint_codes(deps, env, info, hashes)
+        }
     }
 }
 
@@ -384,6 +576,21 @@ fn execute_update_config(
         config.start_time = msg.start_time;
     }
 
+    // payments come in on the terms of the raise, so they can't change under them. whitelist
+    // mints and paid mints waiting to be drawn are sales before `start_time`
+    let raise_started = config.start_time <= env.block.time
+        || (RAISE_PROGRESS.may_load(deps.storage)?).map_or(false, |progress| {
+            progress.tokens_sold > 0 || !progress.revenue.is_zero()
+        })
+        || (PENDING_MINT_COUNT.may_load(deps.storage)?).unwrap_or(0) > 0;
+
+    if raise_started
+        && (msg.minimum_raise != config.minimum_raise
+            || (config.minimum_raise.is_some() && msg.end_time != config.end_time))
+    {
+        return Err(ContractError::MinimumRaiseLocked {});
+    }
+
     if msg.end_time != config.end_time {
         if msg
             .end_time
@@ -421,6 +628,14 @@ fn execute_update_config(
         config.mint_denom = msg.mint_denom;
     }
 
+    // checked against the new `mint_denom` even if the list is unchanged
+    if let Some(denom_mint_prices) = &msg.denom_mint_prices {
+        validate_denom_mint_prices(&config.mint_denom, denom_mint_prices)?;
+    }
+
+    let cw20_payment = validate_cw20_payment(deps.as_ref(), msg.cw20_payment)?;
+    let oracle_pricing = validate_oracle_pricing(deps.as_ref(), msg.oracle_pricing)?;
+
     if msg.mint_price != config.mint_price {
         config.mint_price = msg.mint_price;
     }
@@ -429,6 +644,39 @@ fn execute_update_config(
         config.bundle_mint_price = msg.bundle_mint_price;
     }
 
+    if msg.dutch_auction != config.dutch_auction
+        || msg.bonding_curve != config.bonding_curve
+        || msg.bulk_discounts != config.bulk_discounts
+        || msg.denom_mint_prices != config.denom_mint_prices
+        || cw20_payment != config.cw20_payment
+        || oracle_pricing != config.oracle_pricing
+    {
+        validate_pricing_modes(
+            &msg.dutch_auction,
+            &msg.bonding_curve,
+            &msg.bulk_discounts,
+            &msg.denom_mint_prices,
+            &cw20_payment,
+            &oracle_pricing,
+        )?;
+
+        config.dutch_auction = msg.dutch_auction;
+        config.bonding_curve = msg.bonding_curve;
+        config.bulk_discounts = msg.bulk_discounts;
+        config.denom_mint_prices = msg.denom_mint_prices;
+        config.cw20_payment = cw20_payment;
+        config.oracle_pricing = oracle_pricing;
+    }
+
+    if msg.collection_selection != config.collection_selection {
+        config.collection_selection = msg.collection_selection;
+    }
+
+    if msg.referral_bps != config.referral_bps {
+        validate_referral_bps(msg.referral_bps)?;
+        config.referral_bps = msg.referral_bps;
+    }
+
     if msg.max_per_address_mint != config.max_per_address_mint {
         // this may be simplified to just checking against `max_token_supply`
         if msg.max_per_address_mint < 1
@@ -452,6 +700,29 @@ fn execute_update_config(
         config.escrow_funds = msg.escrow_funds;
     }
 
+    if msg.refund_period != config.refund_period {
+        config.refund_period = msg.refund_period;
+    }
+
+    if msg.minimum_raise != config.minimum_raise {
+        config.minimum_raise = msg.minimum_raise;
+    }
+
+    if msg.raffle != config.raffle {
+        // entrants paid in on its terms
+        if (RAFFLE_PROGRESS.may_load(deps.storage)?).map_or(0, |progress| progress.ticket_count) > 0
+        {
+            return Err(ContractError::RaffleLocked {});
+        }
+
+        config.raffle = msg.raffle;
+    }
+
+    // checked on the final config, as escrow and the auction can change with it
+    validate_refund_policy(&config)?;
+    validate_minimum_raise(deps.storage, &config)?;
+    validate_raffle(&config)?;
+
     match maybe_addr(deps.api, msg.airdropper_address)? {
         Some(addr) => AIRDROPPER_ADDR.save(deps.storage, &addr)?,
         None => AIRDROPPER_ADDR.remove(deps.storage),
@@ -491,18 +762,31 @@ fn execute_init_submodule(
 }
 
 /// main public/whitelist minting method
+#[allow(clippy::too_many_arguments)]
 pub fn execute_mint(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
+    mut info: MessageInfo,
     is_airdrop_mint: bool,
     minter_address: Option<String>,
+    quantity: Option<u32>,
+    collection_id: Option<u64>,
+    referrer: Option<String>,
+    code: Option<String>,
 ) -> Result<Response, ContractError> {
-    // check token supply
-    let current_token_supply = CURRENT_TOKEN_SUPPLY.load(deps.storage)?;
+    check_shuffle_not_in_progress(deps.storage)?;
 
-    if current_token_supply == 0 {
-        return Err(ContractError::MintCompleted {});
+    let quantity = quantity.unwrap_or(1);
+
+    if quantity == 0 {
+        return Err(ContractError::InvalidMintQuantity {});
+    }
+
+    // check token supply, minus tokens already reserved by pending mints
+    check_mintable_supply(deps.storage, quantity)?;
+
+    if let Some(collection_id) = collection_id {
+        check_collection_mint(deps.storage, collection_id, quantity)?;
     }
 
     let config = CONFIG.load(deps.storage)?;
@@ -517,11 +801,17 @@ pub fn execute_mint(
         return Err(ContractError::CampaignHasEnded {});
     }
 
-    let mut mint_price: Uint128 = config.mint_price;
+    let mut mint_price: Uint128 = config
+        .mint_price
+        .checked_mul(Uint128::from(quantity))
+        .map_err(StdError::from)?;
+    let mut mint_denom: String = config.mint_denom.clone();
+    let mut oracle_excess = Uint128::zero();
     let mut _mint_type: MintType = MintType::None;
 
     let minter_addr: Addr =
         (maybe_addr(deps.api, minter_address)?).unwrap_or_else(|| info.sender.clone());
+    let referrer = validate_referrer(deps.as_ref(), &info, &minter_addr, referrer)?;
 
     if is_airdrop_mint {
         if minter_addr != info.sender
@@ -537,12 +827,17 @@ pub fn execute_mint(
             &info,
             MintType::PromisedMint,
             minter_addr.clone(),
+            quantity,
         )?;
 
         if check_airdropper_mint_res.can_mint {
             // if mint eligible, execute mint (probably 0 token mint fee)
             _mint_type = MintType::PromisedMint;
-            mint_price = check_airdropper_mint_res.mint_price.unwrap();
+            mint_price = check_airdropper_mint_res
+                .mint_price
+                .unwrap()
+                .checked_mul(Uint128::from(quantity))
+                .map_err(StdError::from)?;
         } else {
             return Err(ContractError::NoPromisedMints {});
         }
@@ -551,75 +846,277 @@ pub fn execute_mint(
     else if env.block.time < config.start_time {
         // if this user is whitelist eligible via `can_mint` then we'll allow them through
         // else we error out as it is before start time of campaign
-        let check_wl = check_whitelist(deps.as_ref(), &info)?;
+        let check_wl = check_whitelist(deps.as_ref(), &info, quantity)?;
         if check_wl.can_mint {
             if check_wl.mint_price.is_none() {
                 return Err(ContractError::InvalidMintPrice {});
             }
 
             _mint_type = MintType::Whitelist;
-            mint_price = check_wl.mint_price.unwrap();
+
+            let unit_price = match cw20_mint_payment(&config, &info) {
+                Some(cw20_payment) => {
+                    mint_denom = cw20_denom(&cw20_payment.token_addr);
+                    cw20_payment
+                        .whitelist_mint_price
+                        .ok_or(ContractError::InvalidMintPrice {})?
+                }
+                None => check_wl.mint_price.unwrap(),
+            };
+
+            (mint_price, _) = apply_bulk_discount(
+                &config,
+                &_mint_type,
+                check_wl.current_mint_count,
+                quantity,
+                unit_price
+                    .checked_mul(Uint128::from(quantity))
+                    .map_err(StdError::from)?,
+            );
         } else {
             return Err(ContractError::BeforeStartTime {});
         }
     } else {
+        // the raffle hands out the public mint until it is settled
+        if !raffle_settled(deps.storage, &config)? {
+            return Err(ContractError::RaffleNotSettled {});
+        }
+
         // if this user has public mints left then we allow them through
-        if check_public_mint(deps.as_ref(), env.clone(), &info)? {
+        if check_public_mint(deps.as_ref(), env.clone(), &info, quantity)? {
             _mint_type = MintType::Public;
+
+            let unit_price = match cw20_mint_payment(&config, &info) {
+                Some(cw20_payment) => Some(coin(
+                    cw20_payment.mint_price.u128(),
+                    cw20_denom(&cw20_payment.token_addr),
+                )),
+                None => denom_mint_price(&config, &info),
+            };
+
+            mint_price = match unit_price {
+                Some(price) => {
+                    mint_denom = price.denom;
+                    price
+                        .amount
+                        .checked_mul(Uint128::from(quantity))
+                        .map_err(StdError::from)?
+                }
+                None => {
+                    quote_public_mint_price(deps.storage, &env, &config, quantity, collection_id)?
+                }
+            };
+
+            if let Some(auction) = &config.dutch_auction {
+                let (price, _) = dutch_auction_price(auction, config.start_time, env.block.time);
+                AUCTION_LAST_PRICE.save(deps.storage, &price)?;
+            }
+
+            let current_mint_count =
+                (ADDRESS_MINT_TRACKER.may_load(deps.storage, minter_addr.clone())?).unwrap_or(0);
+
+            (mint_price, _) = apply_bulk_discount(
+                &config,
+                &_mint_type,
+                current_mint_count,
+                quantity,
+                mint_price,
+            );
+
+            // `mint_denom` prices are in the oracle's reference unit
+            if let (Some(oracle_pricing), true) =
+                (&config.oracle_pricing, mint_denom == config.mint_denom)
+            {
+                let price =
+                    oracle_mint_price(deps.as_ref(), oracle_pricing, &mint_denom, mint_price)?;
+                let payment = may_pay(&info, &mint_denom)?;
+
+                mint_price = charge_oracle_payment(oracle_pricing, price, payment, &mint_denom)?;
+                oracle_excess = payment - mint_price;
+            }
         }
     }
 
     println!("{:?}", 1);
 
     if _mint_type != MintType::None {
-        return _execute_mint(deps, env, info, _mint_type, mint_price, minter_addr);
+        // the mint only sees the charged amount, and the excess is sent back
+        let refund_msg = if oracle_excess.is_zero() {
+            None
+        } else {
+            info.funds = vec![coin(mint_price.u128(), &mint_denom)];
+
+            Some(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![coin(oracle_excess.u128(), &mint_denom)],
+            })
+        };
+
+        let res = _execute_mint(
+            deps,
+            env,
+            info,
+            _mint_type,
+            mint_price,
+            mint_denom,
+            minter_addr,
+            quantity,
+            collection_id,
+            referrer,
+            code,
+        )?;
+
+        return Ok(res.add_messages(refund_msg));
     }
 
     Err(ContractError::UnableToMint {})
 }
 
 /// method that finalizes the mint and generates the submessages
+/// `mint_price` is the total paid in `mint_denom` for all `quantity` tokens
+#[allow(clippy::too_many_arguments)]
 fn _execute_mint(
     mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     mint_type: MintType,
     mint_price: Uint128,
+    mint_denom: String,
     minter_addr: Addr,
+    quantity: u32,
+    collection_id: Option<u64>,
+    referrer: Option<Addr>,
+    code: Option<String>,
 ) -> Result<Response, ContractError> {
     // check supply
-    let current_token_supply = CURRENT_TOKEN_SUPPLY.load(deps.storage)?;
-
-    if current_token_supply == 0 {
-        return Err(ContractError::MintCompleted {});
-    }
+    check_mintable_supply(deps.storage, quantity)?;
 
-    let config = CONFIG.load(deps.storage)?;
+    // the code's discount comes off the price the payment is checked against
+    let mint_price = match code {
+        Some(code) => redeem_mint_code(
+            deps.storage,
+            &info.sender,
+            &mint_type,
+            &mint_denom,
+            &code,
+            quantity,
+            mint_price,
+        )?,
+        None => mint_price,
+    };
 
     // check payment
-    let payment = may_pay(&info, &config.mint_denom)?;
+    let payment = may_pay(&info, &mint_denom)?;
 
     if payment != mint_price {
         return Err(ContractError::IncorrectPaymentAmount {
-            token: config.mint_denom,
+            token: mint_denom,
             amt: mint_price,
         });
     }
 
     let mut res = Response::new();
+    let mut drawn_later = false;
 
-    // TODO: add another element of randomness here?
-    let (collection_id, token_index) =
-        randomize_and_draw_mint(deps.as_ref(), &env, info.sender, None)?;
+    let receipt = mint_receipt(
+        deps.storage,
+        &env,
+        mint_price / Uint128::from(quantity),
+        &mint_denom,
+        referrer.clone(),
+    )?;
 
-    res = res.add_message(process_and_get_mint_msg(
-        deps.branch(),
-        minter_addr.clone(),
-        current_token_supply - 1,
-        collection_id,
-        None,
-        Some(token_index),
-    )?);
+    // with a randomness proxy the tokens are only assigned once the proxy calls back,
+    // and the payment stays in the contract until then
+    if let Some(proxy_addr) = RANDOMNESS_PROXY.may_load(deps.storage)? {
+        let job_id = next_mint_job_id(deps.storage)?;
+
+        PENDING_MINTS.save(
+            deps.storage,
+            job_id.clone(),
+            &PendingMint {
+                job_id: job_id.clone(),
+                minter_addr: minter_addr.clone(),
+                expires_at_height: env.block.height + DEFAULT_MINT_TICKET_EXPIRY_BLOCKS,
+                mint_type: mint_type.clone(),
+                quantity,
+                paid: mint_price,
+                denom: mint_denom.clone(),
+                referrer: referrer.clone(),
+            },
+        )?;
+
+        for _ in 0..quantity {
+            reserve_pending_mint(deps.storage)?;
+        }
+
+        res = res.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: proxy_addr.into_string(),
+            msg: to_binary(&ProxyExecuteMsg::GetNextRandomness { job_id })?,
+            funds: vec![],
+        }));
+
+        drawn_later = true;
+    } else if let Some(expiry_blocks) = mint_ticket_expiry_blocks(deps.storage)? {
+        // payment stays in the contract until the ticket is settled or refunded
+        let ticket_key = (minter_addr.clone(), env.block.height);
+
+        if MINT_TICKETS.has(deps.storage, ticket_key.clone()) {
+            return Err(ContractError::MintTicketExists {});
+        }
+
+        let (commitment, drand_round) = hidden_randomness_sources(deps.storage, &env, None)?;
+
+        MINT_TICKETS.save(
+            deps.storage,
+            ticket_key,
+            &MintTicket {
+                minter_addr: minter_addr.clone(),
+                height: env.block.height,
+                expires_at_height: env.block.height + expiry_blocks,
+                mint_type: mint_type.clone(),
+                quantity,
+                paid: mint_price,
+                denom: Some(mint_denom.clone()),
+                referrer: referrer.clone(),
+                commitment,
+                drand_round,
+            },
+        )?;
+
+        for _ in 0..quantity {
+            reserve_pending_mint(deps.storage)?;
+        }
+
+        drawn_later = true;
+    } else {
+        for nonce in 0..quantity {
+            // TODO: add another element of randomness here?
+            let (collection_id, token_index) = match collection_id {
+                Some(collection_id) => (
+                    collection_id,
+                    randomize_and_draw_collection_mint(
+                        deps.storage,
+                        &env,
+                        info.sender.clone(),
+                        collection_id,
+                        nonce,
+                    )?,
+                ),
+                None => {
+                    randomize_and_draw_mint(deps.storage, &env, info.sender.clone(), None, nonce)?
+                }
+            };
+
+            res = res.add_message(process_and_get_mint_msg(
+                deps.branch(),
+                minter_addr.clone(),
+                collection_id,
+                MintToken::Drawn(token_index),
+                receipt.as_ref(),
+            )?);
+        }
+    }
 
     match mint_type {
         MintType::Public => {
@@ -627,12 +1124,20 @@ fn _execute_mint(
             let current_mint_count =
                 (ADDRESS_MINT_TRACKER.may_load(deps.storage, minter_addr.clone())?).unwrap_or(0);
 
-            ADDRESS_MINT_TRACKER.save(deps.storage, minter_addr, &(current_mint_count + 1))?;
+            ADDRESS_MINT_TRACKER.save(
+                deps.storage,
+                minter_addr,
+                &(current_mint_count + quantity),
+            )?;
         }
         MintType::Whitelist => {
             // fire call to update whitelist
             let whitelist_addr = WHITELIST_ADDR.load(deps.storage)?;
-            let update_msg = WL_UpdateAddressMintTracker(minter_addr.into_string());
+            // single mints keep the message whitelists older than multi-token mints take
+            let update_msg = match quantity {
+                1 => WL_UpdateAddressMintTracker(minter_addr.into_string()),
+                _ => WL_UpdateAddressMintTrackerBy(minter_addr.into_string(), quantity),
+            };
             let msg = CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: whitelist_addr.into_string(),
                 msg: to_binary(&update_msg)?,
@@ -644,7 +1149,13 @@ fn _execute_mint(
         MintType::PromisedMint => {
             // update airdropper mint tracker
             let airdropper_addr = AIRDROPPER_ADDR.load(deps.storage)?;
-            let update_msg = AD_IncrementAddressClaimedPromisedMintCount(minter_addr.into_string());
+            let update_msg = match quantity {
+                1 => AD_IncrementAddressClaimedPromisedMintCount(minter_addr.into_string()),
+                _ => AD_IncrementAddressClaimedPromisedMintCountBy(
+                    minter_addr.into_string(),
+                    quantity,
+                ),
+            };
             let msg = CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: airdropper_addr.into_string(),
                 msg: to_binary(&update_msg)?,
@@ -658,7 +1169,19 @@ fn _execute_mint(
         }
     }
 
-    res = disburse_or_escrow_funds(deps, res, mint_price)?;
+    if !drawn_later {
+        res = settle_mint_payment(
+            deps,
+            &env,
+            res,
+            &info.sender,
+            &mint_type,
+            quantity,
+            mint_price,
+            mint_denom,
+            referrer.as_ref(),
+        )?;
+    }
 
     Ok(res)
 }
@@ -667,7 +1190,17 @@ fn execute_mint_bundle(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    referrer: Option<String>,
 ) -> Result<Response, ContractError> {
+    check_shuffle_not_in_progress(deps.storage)?;
+
+    // a bundle is drawn as it is paid
+    if mints_drawn_later(deps.storage)? {
+        return Err(ContractError::BundleMintUnavailable {});
+    }
+
+    let referrer = validate_referrer(deps.as_ref(), &info, &info.sender, referrer)?;
+
     // check token supply
     let current_token_supply = CURRENT_TOKEN_SUPPLY.load(deps.storage)?;
 
@@ -684,29 +1217,66 @@ fn execute_mint_bundle(
     if config.bundle_completed {
         return Err(ContractError::BundleMintCompleted {});
     } else {
-        let collection_supplies: Vec<u32> = COLLECTION_CURRENT_TOKEN_SUPPLY
-            .range(deps.storage, None, None, Order::Ascending)
-            .map(|item| {
-                let (_, supply) = item?;
-                Ok(supply)
-            })
-            .collect::<StdResult<Vec<u32>>>()
-            .unwrap();
+        let bundle_collections = bundle_collections(deps.storage)?;
+
+        if bundle_collections.is_empty() {
+            return Err(ContractError::BundleMintCompleted {});
+        }
 
-        for supply in collection_supplies {
-            if supply == 0 {
+        for (collection_id, _) in bundle_collections {
+            if COLLECTION_CURRENT_TOKEN_SUPPLY.load(deps.storage, collection_id)? == 0 {
                 return Err(ContractError::BundleMintCompleted {});
             }
         }
     }
 
-    let payment = may_pay(&info, &config.mint_denom)?;
+    let (mut bundle_mint_price, mint_denom) =
+        match (cw20_mint_payment(&config, &info), &config.bonding_curve) {
+            (Some(cw20_payment), _) => (
+                cw20_payment.bundle_mint_price,
+                cw20_denom(&cw20_payment.token_addr),
+            ),
+            // on a curve, a bundle pays the curve price of one token per collection
+            (None, Some(_)) => {
+                let bundle_size = bundle_collections(deps.storage)?.len() as u32;
 
-    if payment != config.bundle_mint_price {
-        return Err(ContractError::IncorrectPaymentAmount {
-            token: config.mint_denom,
-            amt: config.bundle_mint_price,
-        });
+                (
+                    quote_public_mint_price(deps.storage, &env, &config, bundle_size, None)?,
+                    config.mint_denom.clone(),
+                )
+            }
+            (None, None) => (config.bundle_mint_price, config.mint_denom.clone()),
+        };
+
+    let payment = may_pay(&info, &mint_denom)?;
+    let mut refund_msg: Option<BankMsg> = None;
+
+    match &config.oracle_pricing {
+        // `mint_denom` prices are in the oracle's reference unit
+        Some(oracle_pricing) if mint_denom == config.mint_denom => {
+            let price = oracle_mint_price(
+                deps.as_ref(),
+                oracle_pricing,
+                &mint_denom,
+                bundle_mint_price,
+            )?;
+            bundle_mint_price = charge_oracle_payment(oracle_pricing, price, payment, &mint_denom)?;
+
+            if payment > bundle_mint_price {
+                refund_msg = Some(BankMsg::Send {
+                    to_address: info.sender.to_string(),
+                    amount: vec![coin((payment - bundle_mint_price).u128(), &mint_denom)],
+                });
+            }
+        }
+        _ => {
+            if payment != bundle_mint_price {
+                return Err(ContractError::IncorrectPaymentAmount {
+                    token: mint_denom,
+                    amt: bundle_mint_price,
+                });
+            }
+        }
     }
 
     if config
@@ -727,7 +1297,9 @@ fn execute_mint_bundle(
     }
 
     if config.start_time <= env.block.time {
-        return _execute_mint_bundle(deps, env, info, current_token_supply);
+        let res = _execute_mint_bundle(deps, env, info, bundle_mint_price, mint_denom, referrer)?;
+
+        return Ok(res.add_messages(refund_msg));
     }
 
     Err(ContractError::UnableToMint {})
@@ -738,28 +1310,39 @@ fn _execute_mint_bundle(
     mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    mut current_token_supply: u32,
+    bundle_mint_price: Uint128,
+    mint_denom: String,
+    referrer: Option<Addr>,
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-
     // address - address
     // value - collection_id
-    let collections: Vec<AddressValMsg> = CW721_ADDRS
-        .range(deps.storage, None, None, Order::Ascending)
-        .map(|item| {
-            let (coll_id, addr) = item?;
-            Ok(AddressValMsg {
-                address: addr.into_string(),
-                value: coll_id as u32,
-            })
+    let collections: Vec<AddressValMsg> = bundle_collections(deps.storage)?
+        .into_iter()
+        .map(|(coll_id, addr)| AddressValMsg {
+            address: addr.into_string(),
+            value: coll_id as u32,
         })
-        .collect::<StdResult<Vec<AddressValMsg>>>()
-        .unwrap();
+        .collect();
+
+    // leave enough supply for mints still waiting on the randomness proxy
+    let pending_mint_count = (PENDING_MINT_COUNT.may_load(deps.storage)?).unwrap_or(0);
 
+    if CURRENT_TOKEN_SUPPLY.load(deps.storage)? < pending_mint_count + collections.len() as u32 {
+        return Err(ContractError::BundleMintCompleted {});
+    }
+
+    let bundle_size = collections.len() as u32;
+    let entropy = get_draw_entropy(deps.storage)?;
+    let receipt = mint_receipt(
+        deps.storage,
+        &env,
+        bundle_mint_price / Uint128::from(bundle_size),
+        &mint_denom,
+        referrer.clone(),
+    )?;
     let mut res: Response = Response::new();
 
     for collection in collections {
-        current_token_supply -= 1;
         println!("collection.value {:?}", collection.value);
 
         let collection_current_token_supply =
@@ -770,769 +1353,3648 @@ fn _execute_mint_bundle(
             info.sender.clone(),
             collection.value as u64, // collection's id
             collection_current_token_supply,
+            &entropy,
+        )?;
+
+        record_draw(
+            deps.storage,
+            &env,
+            DrawKind::BundleMint {
+                seed: draw_seed(
+                    &env,
+                    &info.sender,
+                    collection.value as u64,
+                    collection_current_token_supply,
+                    &entropy,
+                ),
+                collection_id: collection.value as u64,
+                supply: collection_current_token_supply,
+                index: token_index,
+            },
         )?;
 
         res = res.add_message(process_and_get_mint_msg(
             deps.branch(),
             info.sender.clone(),
-            current_token_supply,
             collection.value as u64,
-            None,
-            Some(token_index),
+            MintToken::Drawn(token_index),
+            receipt.as_ref(),
         )?);
     }
 
     let current_bundle_mint_count =
         (BUNDLE_MINT_TRACKER.may_load(deps.storage, info.sender.clone())?).unwrap_or(0);
 
-    BUNDLE_MINT_TRACKER.save(deps.storage, info.sender, &(current_bundle_mint_count + 1))?;
+    BUNDLE_MINT_TRACKER.save(
+        deps.storage,
+        info.sender.clone(),
+        &(current_bundle_mint_count + 1),
+    )?;
+
+    record_raise_contribution(
+        deps.storage,
+        &env,
+        &info.sender,
+        bundle_size,
+        bundle_mint_price,
+        &mint_denom,
+    )?;
 
-    res = disburse_or_escrow_funds(deps, res, config.bundle_mint_price)?;
+    res = disburse_or_escrow_funds(
+        deps,
+        res,
+        bundle_mint_price,
+        mint_denom,
+        referrer.as_ref().map(|referrer| (referrer, bundle_size)),
+    )?;
 
     Ok(res)
 }
 
-fn disburse_or_escrow_funds(
-    mut deps: DepsMut,
-    mut res: Response,
-    mint_price: Uint128,
+/// mint paid by a cw20 `Send`. the payment becomes `info.funds` in the token's
+/// `cw20:` denom, so the mint is priced and split like a native one
+fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
-    // TODO: add other disbursement methods eg contract escrow so we dont blow up
-    // an address' tx history
-    if mint_price > Uint128::zero() {
-        let mut filtered_royalties = config.extension.mint_revenue_share;
-
-        // place the is_primary address at the bottom
-        // this address absorbs the remaining funds at the end of the calcs
-        filtered_royalties.sort_by(|a, b| b.is_primary.cmp(&a.is_primary));
-
-        let mut primary_royalty_addr: Option<Addr> = None;
-        let mut remaining_mint_amount: Uint128 = mint_price;
-        for (i, royalty) in filtered_royalties.iter().enumerate() {
-            if remaining_mint_amount > Uint128::zero() {
-                if primary_royalty_addr.is_none() && royalty.is_primary {
-                    primary_royalty_addr = Some(royalty.addr.clone())
-                }
+    match config.cw20_payment {
+        Some(cw20_payment) if cw20_payment.token_addr == info.sender => {}
+        _ => return Err(ContractError::InvalidCw20Token {}),
+    }
 
-                let amt: Uint128 = if i == filtered_royalties.len() && royalty.is_primary {
-                    remaining_mint_amount
-                } else {
-                    calculate_royalty_amount(mint_price, royalty.bps, remaining_mint_amount)
-                };
+    let info = MessageInfo {
+        sender: deps.api.addr_validate(&cw20_msg.sender)?,
+        funds: vec![coin(cw20_msg.amount.u128(), cw20_denom(&info.sender))],
+    };
 
-                remaining_mint_amount -= amt;
+    match from_binary(&cw20_msg.msg)? {
+        ReceiveMsg::Mint {
+            quantity,
+            collection_id,
+            referrer,
+        } => execute_mint(
+            deps,
+            env,
+            info,
+            false,
+            None,
+            quantity,
+            collection_id,
+            referrer,
+            None,
+        ),
+        ReceiveMsg::MintBundle { referrer } => execute_mint_bundle(deps, env, info, referrer),
+    }
+}
 
-                res = _disburse_or_escrow_funds(
-                    deps.branch(),
-                    res,
-                    config.escrow_funds,
-                    royalty.addr.clone(),
-                    amt,
-                    config.mint_denom.clone(),
-                )?;
-            }
+/// refund of a token sent back with cw721 `SendNft` within its refund window. the token
+/// is burned and returned to the draw, and what it was paid comes back out of the
+/// escrowed balances it was split into
+fn execute_receive_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    receive_msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    check_shuffle_not_in_progress(deps.storage)?;
+
+    let mut collection_id: Option<u64> = None;
+    for item in CW721_ADDRS.range(deps.storage, None, None, Order::Ascending) {
+        let (id, addr) = item?;
+        if addr == info.sender {
+            collection_id = Some(id);
+            break;
         }
+    }
+    let collection_id = collection_id.ok_or(ContractError::Unauthorized {})?;
+
+    let token_id: u32 = receive_msg
+        .token_id
+        .parse()
+        .map_err(|_| ContractError::NotRefundable {})?;
+    let receipt = MINT_RECEIPTS
+        .may_load(deps.storage, (collection_id, token_id))?
+        .ok_or(ContractError::NotRefundable {})?;
+
+    if receipt.refundable_until < env.block.time {
+        return Err(ContractError::RefundWindowClosed {});
+    }
 
-        if remaining_mint_amount > Uint128::zero() {
-            res = _disburse_or_escrow_funds(
-                deps.branch(),
-                res,
-                config.escrow_funds,
-                primary_royalty_addr.unwrap(),
-                remaining_mint_amount,
-                config.mint_denom,
-            )?;
+    let holder = deps.api.addr_validate(&receive_msg.sender)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    MINT_RECEIPTS.remove(deps.storage, (collection_id, token_id));
+    TOKEN_MINT_ORDER.remove(deps.storage, (collection_id, token_id));
+    return_token_to_draw(deps.storage, collection_id, token_id)?;
+
+    // undo what `disburse_or_escrow_funds` escrowed for the token
+    let mut refund_amount = receipt.paid.amount;
+
+    if let Some(referrer) = &receipt.referrer {
+        // what the referrer was credited, whatever `referral_bps` is now
+        let reward = receipt.referral_reward;
+        refund_amount -= reward;
+
+        let mut referral = REFERRALS
+            .may_load(deps.storage, referrer.clone())?
+            .ok_or(ContractError::ReferralRefundMismatch {})?;
+        referral.mint_count = referral
+            .mint_count
+            .checked_sub(1)
+            .ok_or(ContractError::ReferralRefundMismatch {})?;
+
+        if !reward.is_zero() {
+            let earned = referral
+                .earnings
+                .iter_mut()
+                .find(|earned| earned.denom == receipt.paid.denom)
+                .ok_or(ContractError::ReferralRefundMismatch {})?;
+
+            earned.amount = earned
+                .amount
+                .checked_sub(reward)
+                .map_err(|_| ContractError::ReferralRefundMismatch {})?;
         }
+
+        REFERRALS.save(deps.storage, referrer.clone(), &referral)?;
+
+        withdraw_escrowed_funds(deps.storage, referrer, &receipt.paid.denom, reward)?;
     }
 
-    Ok(res)
+    for (royalty_addr, amt) in revenue_split(&config, refund_amount) {
+        withdraw_escrowed_funds(deps.storage, &royalty_addr, &receipt.paid.denom, amt)?;
+    }
+
+    let burn_msg: Cw721ExecuteMsg<SharedCollectionInfo, Empty> = Cw721ExecuteMsg::Burn {
+        token_id: token_id.to_string(),
+    };
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: info.sender.into_string(),
+            msg: to_binary(&burn_msg)?,
+            funds: vec![],
+        }))
+        .add_message(transfer_msg(
+            &holder,
+            receipt.paid.amount,
+            receipt.paid.denom.clone(),
+        )?)
+        .add_attribute("method", "receive_nft")
+        .add_attribute("collection_id", collection_id.to_string())
+        .add_attribute("token_id", token_id.to_string())
+        .add_attribute("refund", receipt.paid.to_string())
+        .add_attribute("holder", holder))
 }
 
-fn _disburse_or_escrow_funds(
+/// pays back what the sender paid in while a `minimum_raise` that was missed was
+/// pending. the escrowed balances it was split into can no longer be disbursed
+fn execute_claim_raise_refund(
     deps: DepsMut,
-    mut res: Response,
-    escrow_funds: bool,
-    royalty_addr: Addr,
-    amount: Uint128,
-    mint_denom: String,
+    env: Env,
+    info: MessageInfo,
 ) -> Result<Response, ContractError> {
-    if escrow_funds {
-        let balance = (BANK_BALANCES.may_load(deps.storage, royalty_addr.clone())?)
-            .unwrap_or(Uint128::zero());
+    let config = CONFIG.load(deps.storage)?;
 
-        BANK_BALANCES.save(deps.storage, royalty_addr, &(balance + amount))?;
-    } else {
-        let msg = BankMsg::Send {
-            to_address: royalty_addr.to_string(),
-            amount: vec![Coin {
-                amount,
-                denom: mint_denom,
-            }],
-        };
+    if !minimum_raise_missed(deps.storage, &env, &config)? {
+        return Err(ContractError::MinimumRaiseNotMissed {});
+    }
 
-        res = res.add_message(msg);
+    let contributions: Vec<(String, Uint128)> = RAISE_CONTRIBUTIONS
+        .prefix(info.sender.clone())
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, Uint128)>>>()?;
+
+    if contributions.is_empty() {
+        return Err(ContractError::NoRaiseContribution {});
     }
 
-    Ok(res)
+    let mut res = Response::new();
+
+    for (denom, amount) in contributions {
+        RAISE_CONTRIBUTIONS.remove(deps.storage, (info.sender.clone(), denom.clone()));
+        res = res.add_message(transfer_msg(&info.sender, amount, denom)?);
+    }
+
+    Ok(res
+        .add_attribute("method", "claim_raise_refund")
+        .add_attribute("sender", info.sender))
 }
 
-fn execute_airdrop_token_distribution(
-    mut deps: DepsMut,
-    _env: Env,
+/// tickets are paid at `mint_price` when the first one is entered, and held until
+/// they either win or are refunded
+fn execute_enter_raffle(
+    deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    minter_address: Option<String>,
+    tickets: u32,
 ) -> Result<Response, ContractError> {
-    // default to self if no address passed in
-    let minter_addr: Addr =
-        (maybe_addr(deps.api, minter_address)?).unwrap_or_else(|| info.sender.clone());
-
     let config = CONFIG.load(deps.storage)?;
-    let mut current_token_supply = CURRENT_TOKEN_SUPPLY.load(deps.storage)?;
-    let airdropper_addr = AIRDROPPER_ADDR.load(deps.storage)?;
+    let raffle = config.raffle.ok_or(ContractError::NoRaffle {})?;
 
-    let mut res: Response = Response::new();
+    if raffle.deadline <= env.block.time {
+        return Err(ContractError::RaffleClosed {});
+    }
 
-    if minter_addr != info.sender
-        && config.admin != info.sender
-        && config.maintainer_addr != Some(info.sender.clone())
-    {
-        return Err(ContractError::Unauthorized {});
+    if tickets == 0 {
+        return Err(ContractError::InvalidMintQuantity {});
     }
 
-    // check if we have promised tokens
-    let check_airdropper_mint_res = check_airdrop_promises(
-        deps.as_ref(),
-        &info,
-        MintType::PromisedToken,
-        minter_addr.clone(),
-    )?;
+    let mut entry =
+        (RAFFLE_ENTRIES.may_load(deps.storage, info.sender.clone())?).unwrap_or(RaffleEntry {
+            tickets: 0,
+            won: 0,
+            refunded: false,
+        });
 
-    if check_airdropper_mint_res.can_mint {
-        for token in check_airdropper_mint_res.remaining_token_ids {
-            current_token_supply -= 1;
-            res = res.add_message(process_and_get_mint_msg(
-                deps.branch(),
-                minter_addr.clone(),
-                current_token_supply,
-                token.collection_id,
-                Some(token.token_id),
-                None,
-            )?);
+    if entry.tickets + tickets > raffle.max_tickets_per_address {
+        return Err(ContractError::RaffleTicketLimit {
+            max: raffle.max_tickets_per_address,
+        });
+    }
 
-            let update_msg = AD_MarkTokenIDClaimed(AD_AddressTokenMsg {
-                address: minter_addr.to_string(),
-                token: AD_TokenMsg {
-                    collection_id: token.collection_id,
-                    token_id: token.token_id,
-                },
-            });
+    let mut progress = (RAFFLE_PROGRESS.may_load(deps.storage)?).unwrap_or_default();
+    let ticket_price = progress
+        .ticket_price
+        .clone()
+        .unwrap_or_else(|| coin(config.mint_price.u128(), &config.mint_denom));
+    let price = ticket_price
+        .amount
+        .checked_mul(Uint128::from(tickets))
+        .map_err(StdError::from)?;
+
+    if may_pay(&info, &ticket_price.denom)? != price {
+        return Err(ContractError::IncorrectPaymentAmount {
+            token: ticket_price.denom,
+            amt: price,
+        });
+    }
 
-            res = res.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: airdropper_addr.clone().into_string(),
-                msg: to_binary(&update_msg)?,
-                funds: vec![],
-            }));
-        }
+    for _ in 0..tickets {
+        RAFFLE_TICKETS.save(deps.storage, progress.ticket_count, &info.sender)?;
+        progress.ticket_count += 1;
+    }
 
-        Ok(res)
-    } else {
-        Err(ContractError::NoPromisedMints {})
+    // a token is held back for each ticket while the supply lasts, so it is still
+    // there for the winners when the raffle is settled
+    for _ in 0..cmp::min(tickets, mintable_supply(deps.storage)?) {
+        reserve_pending_mint(deps.storage)?;
+        progress.reserved += 1;
     }
+
+    entry.tickets += tickets;
+    RAFFLE_ENTRIES.save(deps.storage, info.sender.clone(), &entry)?;
+
+    progress.ticket_price = Some(ticket_price);
+    RAFFLE_PROGRESS.save(deps.storage, &progress)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "enter_raffle")
+        .add_attribute("sender", info.sender)
+        .add_attribute("tickets", tickets.to_string()))
 }
 
-fn execute_clean_claimed_tokens_from_shuffle(
-    deps: DepsMut,
+/// the first call after the deadline fixes the winner count and the randomness they
+/// are drawn with, which can't be out yet. later calls draw up to `limit` winners,
+/// minting each a token and splitting what their ticket paid
+fn execute_settle_raffle(
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    limit: Option<u32>,
 ) -> Result<Response, ContractError> {
-    check_can_update(deps.as_ref(), &env, &info)?;
+    let config = CONFIG.load(deps.storage)?;
+    let raffle = config.raffle.clone().ok_or(ContractError::NoRaffle {})?;
 
-    if let Some(addr) = AIRDROPPER_ADDR.may_load(deps.storage)? {
-        let mut config = CONFIG.load(deps.storage)?;
-        let mut new_current_token_supply = CURRENT_TOKEN_SUPPLY.load(deps.storage)?;
+    if env.block.time < raffle.deadline {
+        return Err(ContractError::RaffleNotClosed {});
+    }
 
-        let assigned_token_ids: Vec<AD_TokenMsg> = deps.querier.query_wasm_smart(
-            addr,
-            &AirdropperQueryMsg::GetAssignedTokenIDs {
-                start_after: None,
-                limit: None,
-            },
-        )?;
+    if raffle_settled(deps.storage, &config)? {
+        return Err(ContractError::RaffleSettled {});
+    }
 
-        for msg in assigned_token_ids {
-            new_current_token_supply -= 1;
-
-            let mut collection_token_ids: Vec<u32> =
-                CW721_SHUFFLED_TOKEN_IDS.load(deps.storage, msg.collection_id)?;
-
-            // retrieve index of token id
-            let token_index = collection_token_ids
-                .iter()
-                .position(|&i| i == msg.token_id)
-                .map(|idx| idx as u32);
-
-            // if it exists remove token from vec
-            if let Some(idx) = token_index {
-                let collection_length = collection_token_ids.len() - 1;
-
-                collection_token_ids.swap(idx as usize, collection_length);
-                collection_token_ids.resize(collection_length, 0);
-
-                CW721_SHUFFLED_TOKEN_IDS.save(
-                    deps.storage,
-                    msg.collection_id,
-                    &collection_token_ids,
-                )?;
-
-                // collection_length should be the new collection current token supply
-                COLLECTION_CURRENT_TOKEN_SUPPLY.save(
-                    deps.storage,
-                    msg.collection_id,
-                    &(collection_length as u32),
-                )?;
-
-                if collection_length == 0 {
-                    config.bundle_completed = true;
-                    CONFIG.save(deps.storage, &config)?;
-                }
+    let mut progress = (RAFFLE_PROGRESS.may_load(deps.storage)?).unwrap_or_default();
+
+    let winner_count = match progress.winner_count {
+        Some(winner_count) => winner_count,
+        // nobody entered
+        None if progress.ticket_count == 0 => {
+            progress.winner_count = Some(0);
+            RAFFLE_PROGRESS.save(deps.storage, &progress)?;
+
+            return Ok(Response::new()
+                .add_attribute("method", "settle_raffle")
+                .add_attribute("sender", info.sender)
+                .add_attribute("winners_drawn", "0"));
+        }
+        None => {
+            let (commitment, drand_round) =
+                hidden_randomness_sources(deps.storage, &env, Some(raffle.deadline))?;
+
+            // tickets entered after the supply was held back win what is left
+            let top_up = cmp::min(
+                progress.ticket_count - progress.reserved,
+                mintable_supply(deps.storage)?,
+            );
+            for _ in 0..top_up {
+                reserve_pending_mint(deps.storage)?;
+                progress.reserved += 1;
             }
+
+            progress.winner_count = Some(progress.reserved);
+            progress.commitment = commitment;
+            progress.drand_round = drand_round;
+            RAFFLE_PROGRESS.save(deps.storage, &progress)?;
+
+            return Ok(Response::new()
+                .add_attribute("method", "settle_raffle")
+                .add_attribute("sender", info.sender)
+                .add_attribute("winner_count", progress.reserved.to_string()));
         }
+    };
 
-        CURRENT_TOKEN_SUPPLY.save(deps.storage, &new_current_token_supply)?;
+    let randomness = hidden_randomness(deps.storage, &progress.commitment, progress.drand_round)?;
 
-        Ok(Response::new()
-            .add_attribute("method", "clean_claimed_tokens_with_shuffle")
-            .add_attribute("sender", info.sender))
-    } else {
-        Err(ContractError::InvalidAirdropperAddress {})
-    }
-}
+    let draws = cmp::min(
+        winner_count - progress.winners_drawn,
+        cmp::min(
+            limit.unwrap_or(DEFAULT_RAFFLE_SETTLE_LIMIT),
+            MAX_RAFFLE_SETTLE_LIMIT,
+        ),
+    );
 
-fn execute_shuffle_token_order(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-) -> Result<Response, ContractError> {
-    let current_token_supply = CURRENT_TOKEN_SUPPLY.load(deps.storage)?;
+    let mut res = Response::new();
+    let mut drawn = 0u32;
 
-    if current_token_supply == 0 {
-        return Err(ContractError::MintCompleted {});
-    }
+    if let Some(ticket_price) = progress.ticket_price.clone() {
+        let receipt = mint_receipt(
+            deps.storage,
+            &env,
+            ticket_price.amount,
+            &ticket_price.denom,
+            None,
+        )?;
 
-    let config = CONFIG.load(deps.storage)?;
-    let mut res: Response = Response::new();
+        for _ in 0..draws {
+            release_pending_mint(deps.storage)?;
+            progress.reserved -= 1;
 
-    // if not admin or maintainer, a fee is needed to execute this function
-    if config.admin != info.sender && config.maintainer_addr != Some(info.sender.clone()) {
-        // check payment
-        let payment = must_pay(&info, &config.bonded_denom)?;
-
-        if payment != Uint128::from(DEFAULT_FEE_AMOUNT) {
-            return Err(ContractError::InvalidFeeAmount {
-                denom: config.bonded_denom,
-                fee: DEFAULT_FEE_AMOUNT,
-                operation: "shuffle_token_order".to_string(),
-            });
-        }
+            // the held back supply was taken some other way, so the tickets left lose
+            if mintable_supply(deps.storage)? == 0 {
+                for _ in 0..progress.reserved {
+                    release_pending_mint(deps.storage)?;
+                }
+                progress.reserved = 0;
+                progress.winner_count = Some(progress.winners_drawn);
+                break;
+            }
 
-        let fee_collection_addr = FEE_COLLECTION_ADDR.load(deps.storage)?;
+            let position = progress.winners_drawn;
+            let winner = draw_raffle_ticket(
+                deps.storage,
+                &env,
+                &randomness,
+                position,
+                progress.ticket_count,
+            )?;
 
-        let msg = BankMsg::Send {
-            to_address: fee_collection_addr.into_string(),
-            amount: vec![coin(DEFAULT_FEE_AMOUNT, config.bonded_denom)],
-        };
+            let mut entry = RAFFLE_ENTRIES.load(deps.storage, winner.clone())?;
+            entry.won += 1;
+            RAFFLE_ENTRIES.save(deps.storage, winner.clone(), &entry)?;
 
-        res = res.add_message(msg);
-    }
+            let current_mint_count =
+                (ADDRESS_MINT_TRACKER.may_load(deps.storage, winner.clone())?).unwrap_or(0);
+            ADDRESS_MINT_TRACKER.save(deps.storage, winner.clone(), &(current_mint_count + 1))?;
+
+            let (collection_id, token_index) = draw_mint_from_randomness(
+                deps.storage,
+                &env,
+                &randomness,
+                &format!("raffle-mint-{}", position),
+            )?;
 
-    // address - address
-    // value - collection_id
-    let collections: Vec<AddressValMsg> = CW721_ADDRS
-        .range(deps.storage, None, None, Order::Ascending)
-        .map(|item| {
-            let (coll_id, addr) = item?;
-            Ok(AddressValMsg {
-                address: addr.into_string(),
-                value: coll_id as u32,
-            })
-        })
-        .collect::<StdResult<Vec<AddressValMsg>>>()
-        .unwrap();
+            res = res.add_message(process_and_get_mint_msg(
+                deps.branch(),
+                winner,
+                collection_id,
+                MintToken::Drawn(token_index),
+                receipt.as_ref(),
+            )?);
 
-    for collection in collections {
-        let collection_id: u64 = collection.value as u64;
-        let collection_token_ids: Vec<u32> =
-            CW721_SHUFFLED_TOKEN_IDS.load(deps.storage, collection_id)?;
+            progress.winners_drawn += 1;
+            drawn += 1;
+        }
 
-        let shuffled_token_ids = shuffle_token_ids(
-            &env,
-            info.sender.clone(),
-            collection_token_ids.clone(),
-            collection.value as u64,
-        )?;
+        RAFFLE_PROGRESS.save(deps.storage, &progress)?;
 
-        CW721_SHUFFLED_TOKEN_IDS.save(deps.storage, collection_id, &shuffled_token_ids)?;
+        res = disburse_or_escrow_funds(
+            deps,
+            res,
+            ticket_price
+                .amount
+                .checked_mul(Uint128::from(drawn))
+                .map_err(StdError::from)?,
+            ticket_price.denom,
+            None,
+        )?;
     }
 
     Ok(res
-        .add_attribute("method", "shuffle_token_order")
-        .add_attribute("sender", info.sender))
+        .add_attribute("method", "settle_raffle")
+        .add_attribute("sender", info.sender)
+        .add_attribute("winners_drawn", drawn.to_string()))
 }
 
-fn execute_submodule_hook(
+fn execute_claim_raffle_refund(
     deps: DepsMut,
-    env: Env,
+    _env: Env,
     info: MessageInfo,
-    target: ExecutionTarget,
-    msg: CosmosMsg<Empty>,
 ) -> Result<Response, ContractError> {
-    check_can_update(deps.as_ref(), &env, &info)?;
+    let config = CONFIG.load(deps.storage)?;
 
-    // extract target contract address from cosmosmsg::wasmmsg
-    let target_contract_address: String = match msg.clone() {
-        CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr,
-            msg: _,
-            funds: _,
-        }) => contract_addr.to_lowercase(),
-        _ => {
-            return Err(ContractError::Unauthorized {});
-        }
-    };
+    if config.raffle.is_none() || !raffle_settled(deps.storage, &config)? {
+        return Err(ContractError::RaffleNotSettled {});
+    }
 
-    // extract stored contract address
-    let contract_address: String = match target {
-        ExecutionTarget::Airdropper => (AIRDROPPER_ADDR.load(deps.storage)?)
-            .into_string()
-            .to_lowercase(),
-        ExecutionTarget::Whitelist => (WHITELIST_ADDR.load(deps.storage)?)
-            .into_string()
-            .to_lowercase(),
-        _ => {
-            return Err(ContractError::Unauthorized {});
-        }
-    };
+    let mut entry = RAFFLE_ENTRIES
+        .may_load(deps.storage, info.sender.clone())?
+        .filter(|entry| !entry.refunded && entry.tickets > entry.won)
+        .ok_or(ContractError::NoRaffleRefund {})?;
+    let ticket_price = RAFFLE_PROGRESS
+        .load(deps.storage)?
+        .ticket_price
+        .ok_or(ContractError::NoRaffleRefund {})?;
 
-    // ensure addresses match
-    if contract_address != target_contract_address {
-        return Err(ContractError::InvalidTargetAddress {});
-    }
+    let refund = ticket_price
+        .amount
+        .checked_mul(Uint128::from(entry.tickets - entry.won))
+        .map_err(StdError::from)?;
 
-    Ok(Response::default()
-        .add_attribute("method", "submodule_hook")
-        .add_message(msg))
+    entry.refunded = true;
+    RAFFLE_ENTRIES.save(deps.storage, info.sender.clone(), &entry)?;
+
+    Ok(Response::new()
+        .add_message(transfer_msg(&info.sender, refund, ticket_price.denom)?)
+        .add_attribute("method", "claim_raffle_refund")
+        .add_attribute("sender", info.sender)
+        .add_attribute("refund", refund))
 }
 
-fn execute_disburse_funds(
+/// holds `auction.token_id` out of the draw for the auction, priced in `mint_denom`
+fn execute_create_token_auction(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    auction: TokenAuction,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
-    // EITHER admin (minting contract) or maintainer can update/
-    if config.admin != info.sender && config.maintainer_addr != Some(info.sender) {
+    if config.admin != info.sender && config.maintainer_addr != Some(info.sender.clone()) {
         return Err(ContractError::Unauthorized {});
     }
 
-    let mut remaining_balance: Uint128 = (deps
-        .querier
-        .query_balance(&env.contract.address, config.mint_denom.clone())?)
-    .amount;
+    // a sale outside the mint would have to be escrowed and paid back with the raise
+    if config.minimum_raise.is_some() {
+        return Err(ContractError::TokenAuctionWithMinimumRaise {});
+    }
 
-    let balances: Vec<AddrBal> = BANK_BALANCES
-        .range(deps.storage, None, None, Order::Ascending)
-        .map(|item| {
-            let (addr, balance) = item?;
-            Ok(AddrBal { addr, balance })
-        })
-        .collect::<StdResult<Vec<AddrBal>>>()
-        .unwrap();
+    if auction.min_increment.is_zero()
+        || auction.end_time <= auction.start_time
+        || auction.end_time <= env.block.time
+    {
+        return Err(ContractError::InvalidTokenAuction {});
+    }
 
-    //let mut remaining_balance: Uint128 = contract_balance.amount;
-    let mut msgs: Vec<BankMsg> = vec![];
+    let key = (auction.collection_id, auction.token_id);
 
-    for addr_bal in balances {
-        if addr_bal.balance > Uint128::zero() && remaining_balance >= addr_bal.balance {
-            msgs.push(BankMsg::Send {
-                to_address: addr_bal.addr.to_string(),
-                amount: vec![Coin {
-                    amount: addr_bal.balance,
-                    denom: config.mint_denom.clone(),
-                }],
-            });
+    // a token that went back into the draw unsold can be auctioned again
+    if (TOKEN_AUCTIONS.may_load(deps.storage, key)?).map_or(false, |existing| !existing.settled) {
+        return Err(ContractError::TokenAuctionExists {});
+    }
+
+    // a token the airdropper assigned belongs to whoever it is promised to, claimed or not
+    if let Some(airdropper_addr) = AIRDROPPER_ADDR.may_load(deps.storage)? {
+        let assigned_token_ids: Vec<AD_TokenMsg> = deps.querier.query_wasm_smart(
+            airdropper_addr,
+            &AirdropperQueryMsg::GetAssignedTokenIDs {
+                start_after: Some((auction.collection_id, auction.token_id.saturating_sub(1))),
+                limit: Some(1),
+            },
+        )?;
 
-            remaining_balance -= addr_bal.balance;
-            BANK_BALANCES.save(deps.storage, addr_bal.addr, &Uint128::zero())?;
+        if assigned_token_ids.first().map_or(false, |token| {
+            token.collection_id == auction.collection_id && token.token_id == auction.token_id
+        }) {
+            return Err(ContractError::TokenPromised {});
         }
     }
-    Ok(Response::default()
-        .add_attribute("method", "disburse_funds")
-        .add_messages(msgs))
-}
 
-// #region helper functions
+    // the supply held for mints waiting to be drawn can't be auctioned
+    if !take_token_from_draw(deps.storage, auction.collection_id, auction.token_id)? {
+        return Err(ContractError::TokenNotAvailable {});
+    }
 
-struct ValidateCollectionInfoResponse {
-    pub collection_infos: Vec<CollectionInfo>,
-    pub total_token_supply: u32,
+    TOKEN_AUCTIONS.save(
+        deps.storage,
+        key,
+        &TokenAuction {
+            denom: config.mint_denom,
+            ..auction
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "create_token_auction")
+        .add_attribute("collection_id", key.0.to_string())
+        .add_attribute("token_id", key.1.to_string())
+        .add_attribute("sender", info.sender))
 }
 
-/// also stores
-fn process_and_get_mint_msg(
+fn execute_bid_token_auction(
     deps: DepsMut,
-    minter_addr: Addr,
-    new_current_token_supply: u32,
+    env: Env,
+    info: MessageInfo,
     collection_id: u64,
-    mut token_id: Option<u32>,
-    mut token_index: Option<u32>,
-) -> Result<CosmosMsg, ContractError> {
-    if token_id.is_none() && token_index.is_none() {
-        return Err(ContractError::UnableToMint {});
+    token_id: u32,
+) -> Result<Response, ContractError> {
+    let mut auction = TOKEN_AUCTIONS
+        .may_load(deps.storage, (collection_id, token_id))?
+        .ok_or(ContractError::TokenAuctionNotFound {})?;
+
+    if env.block.time < auction.start_time || auction.end_time <= env.block.time {
+        return Err(ContractError::TokenAuctionNotOpen {});
     }
 
-    let mut config = CONFIG.load(deps.storage)?;
+    let min_bid = match &auction.highest_bid {
+        Some(highest_bid) => highest_bid.amount + auction.min_increment,
+        None => auction.reserve_price,
+    };
+    let amount = must_pay(&info, &auction.denom)?;
 
-    let mut collection_token_ids: Vec<u32> =
-        CW721_SHUFFLED_TOKEN_IDS.load(deps.storage, collection_id)?;
+    if amount < min_bid {
+        return Err(ContractError::BidTooLow { min: min_bid });
+    }
 
-    let collection_length = collection_token_ids.len() - 1;
+    let mut res = Response::new();
 
-    if token_id.is_none() || token_index.unwrap() > (collection_length as u32) {
-        token_id = Some(collection_token_ids[token_index.unwrap() as usize]);
+    // the bid it beats goes straight back
+    if let Some(outbid) = auction.highest_bid.take() {
+        res = res.add_message(transfer_msg(
+            &outbid.bidder,
+            outbid.amount,
+            auction.denom.clone(),
+        )?);
     }
 
-    // Create mint msgs
-    let coll_info: CollectionInfo = CW721_COLLECTION_INFO.load(deps.storage, collection_id)?;
+    // a late bid gives the others time to answer it
+    let extended_end_time = env.block.time.plus_seconds(auction.extension);
+    if auction.end_time < extended_end_time {
+        auction.end_time = extended_end_time;
+    }
 
-    let mint_msg: Cw721ExecuteMsg<SharedCollectionInfo, Empty> =
-        Cw721ExecuteMsg::Mint(MintMsg::<SharedCollectionInfo> {
-            token_id: token_id.unwrap().to_string(),
-            owner: minter_addr.into_string(),
-            token_uri: Some(format!(
-                "{}/{}",
-                coll_info.base_token_uri,
-                token_id.unwrap()
-            )),
-            extension: config.extension.clone(),
-        });
+    auction.highest_bid = Some(TokenAuctionBid {
+        bidder: info.sender.clone(),
+        amount,
+    });
+    TOKEN_AUCTIONS.save(deps.storage, (collection_id, token_id), &auction)?;
 
-    let token_address = CW721_ADDRS.load(deps.storage, coll_info.id)?;
+    Ok(res
+        .add_attribute("method", "bid_token_auction")
+        .add_attribute("collection_id", collection_id.to_string())
+        .add_attribute("token_id", token_id.to_string())
+        .add_attribute("bidder", info.sender)
+        .add_attribute("amount", amount)
+        .add_attribute("end_time", auction.end_time.to_string()))
+}
 
-    let msg = CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: token_address.into_string(),
-        msg: to_binary(&mint_msg)?,
-        funds: vec![],
-    });
+fn execute_settle_token_auction(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection_id: u64,
+    token_id: u32,
+) -> Result<Response, ContractError> {
+    let mut auction = TOKEN_AUCTIONS
+        .may_load(deps.storage, (collection_id, token_id))?
+        .ok_or(ContractError::TokenAuctionNotFound {})?;
 
-    // if we were not provided a token index, then go grab the token index based on token_id
-    if token_index.is_none() {
-        token_index = collection_token_ids
-            .iter()
-            .position(|&i| i == token_id.unwrap())
-            .map(|idx| idx as u32);
+    if env.block.time < auction.end_time {
+        return Err(ContractError::TokenAuctionNotEnded {});
+    }
+
+    if auction.settled {
+        return Err(ContractError::TokenAuctionSettled {});
+    }
+
+    auction.settled = true;
+    TOKEN_AUCTIONS.save(deps.storage, (collection_id, token_id), &auction)?;
+
+    let mut res = Response::new()
+        .add_attribute("method", "settle_token_auction")
+        .add_attribute("collection_id", collection_id.to_string())
+        .add_attribute("token_id", token_id.to_string())
+        .add_attribute("sender", info.sender);
+
+    match auction.highest_bid {
+        Some(highest_bid) => {
+            res = res.add_message(process_and_get_mint_msg(
+                deps.branch(),
+                highest_bid.bidder.clone(),
+                collection_id,
+                MintToken::Auctioned(token_id),
+                None,
+            )?);
+            res = disburse_or_escrow_funds(deps, res, highest_bid.amount, auction.denom, None)?;
+
+            Ok(res.add_attribute("winner", highest_bid.bidder))
+        }
+        None => {
+            return_token_to_draw(deps.storage, collection_id, token_id)?;
+
+            Ok(res)
+        }
+    }
+}
+
+/// takes `amount` back out of an address' escrowed balance
+fn withdraw_escrowed_funds(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    denom: &str,
+    amount: Uint128,
+) -> StdResult<()> {
+    let key = (addr.clone(), denom.to_string());
+    let balance = (BANK_BALANCES.may_load(storage, key.clone())?).unwrap_or(Uint128::zero());
+
+    BANK_BALANCES.save(storage, key, &balance.saturating_sub(amount))
+}
+
+/// splits `mint_price` by `mint_revenue_share`. `referral` is the referrer of the
+/// mint and the tokens it was for, whose `referral_bps` share is escrowed first
+fn disburse_or_escrow_funds(
+    mut deps: DepsMut,
+    mut res: Response,
+    mint_price: Uint128,
+    mint_denom: String,
+    referral: Option<(&Addr, u32)>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut mint_price = mint_price;
+
+    if let Some((referrer, quantity)) = referral {
+        let reward = mint_price.multiply_ratio(config.referral_bps.unwrap_or(0), MAX_BPS);
+        mint_price -= reward;
+
+        let mut referral =
+            (REFERRALS.may_load(deps.storage, referrer.clone())?).unwrap_or(Referral {
+                mint_count: 0,
+                earnings: vec![],
+            });
+        referral.mint_count += quantity;
+
+        if !reward.is_zero() {
+            match referral
+                .earnings
+                .iter_mut()
+                .find(|earned| earned.denom == mint_denom)
+            {
+                Some(earned) => earned.amount += reward,
+                None => referral.earnings.push(coin(reward.u128(), &mint_denom)),
+            }
+
+            // held for the referrer whether or not `escrow_funds` is set
+            res = _disburse_or_escrow_funds(
+                deps.branch(),
+                res,
+                true,
+                referrer.clone(),
+                reward,
+                mint_denom.clone(),
+            )?;
+        }
+
+        REFERRALS.save(deps.storage, referrer.clone(), &referral)?;
+    }
+
+    // held until the raise is met, in case it has to be paid back
+    let escrow_funds = config.escrow_funds || !minimum_raise_met(deps.storage, &config)?;
+
+    // TODO: add other disbursement methods eg contract escrow so we dont blow up
+    // an address' tx history
+    for (royalty_addr, amt) in revenue_split(&config, mint_price) {
+        res = _disburse_or_escrow_funds(
+            deps.branch(),
+            res,
+            escrow_funds,
+            royalty_addr,
+            amt,
+            mint_denom.clone(),
+        )?;
+    }
+
+    Ok(res)
+}
+
+/// each `mint_revenue_share` address and its share of `amount`. shares are
+/// listed per royalty, with whatever rounding leaves going to the primary address
+fn revenue_split(config: &Config, amount: Uint128) -> Vec<(Addr, Uint128)> {
+    let mut split: Vec<(Addr, Uint128)> = vec![];
+
+    if amount.is_zero() {
+        return split;
+    }
+
+    let mut filtered_royalties = config.extension.mint_revenue_share.clone();
+
+    // place the is_primary address at the bottom
+    // this address absorbs the remaining funds at the end of the calcs
+    filtered_royalties.sort_by(|a, b| b.is_primary.cmp(&a.is_primary));
+
+    let mut primary_royalty_addr: Option<Addr> = None;
+    let mut remaining_mint_amount: Uint128 = amount;
+    for (i, royalty) in filtered_royalties.iter().enumerate() {
+        if remaining_mint_amount > Uint128::zero() {
+            if primary_royalty_addr.is_none() && royalty.is_primary {
+                primary_royalty_addr = Some(royalty.addr.clone())
+            }
+
+            let amt: Uint128 = if i == filtered_royalties.len() && royalty.is_primary {
+                remaining_mint_amount
+            } else {
+                calculate_royalty_amount(amount, royalty.bps, remaining_mint_amount)
+            };
+
+            remaining_mint_amount -= amt;
+
+            split.push((royalty.addr.clone(), amt));
+        }
+    }
+
+    if remaining_mint_amount > Uint128::zero() {
+        split.push((primary_royalty_addr.unwrap(), remaining_mint_amount));
+    }
+
+    split
+}
+
+fn _disburse_or_escrow_funds(
+    deps: DepsMut,
+    mut res: Response,
+    escrow_funds: bool,
+    royalty_addr: Addr,
+    amount: Uint128,
+    mint_denom: String,
+) -> Result<Response, ContractError> {
+    if escrow_funds {
+        let balance = (BANK_BALANCES
+            .may_load(deps.storage, (royalty_addr.clone(), mint_denom.clone()))?)
+        .unwrap_or(Uint128::zero());
+
+        BANK_BALANCES.save(
+            deps.storage,
+            (royalty_addr, mint_denom),
+            &(balance + amount),
+        )?;
+    } else {
+        res = res.add_message(transfer_msg(&royalty_addr, amount, mint_denom)?);
+    }
+
+    Ok(res)
+}
+
+fn execute_airdrop_token_distribution(
+    mut deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    minter_address: Option<String>,
+) -> Result<Response, ContractError> {
+    // default to self if no address passed in
+    let minter_addr: Addr =
+        (maybe_addr(deps.api, minter_address)?).unwrap_or_else(|| info.sender.clone());
+
+    let config = CONFIG.load(deps.storage)?;
+    let airdropper_addr = AIRDROPPER_ADDR.load(deps.storage)?;
+
+    let mut res: Response = Response::new();
+
+    if minter_addr != info.sender
+        && config.admin != info.sender
+        && config.maintainer_addr != Some(info.sender.clone())
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // check if we have promised tokens
+    let check_airdropper_mint_res = check_airdrop_promises(
+        deps.as_ref(),
+        &info,
+        MintType::PromisedToken,
+        minter_addr.clone(),
+        1,
+    )?;
+
+    if check_airdropper_mint_res.can_mint {
+        for token in check_airdropper_mint_res.remaining_token_ids {
+            res = res.add_message(process_and_get_mint_msg(
+                deps.branch(),
+                minter_addr.clone(),
+                token.collection_id,
+                MintToken::Promised(token.token_id),
+                None,
+            )?);
+
+            let update_msg = AD_MarkTokenIDClaimed(AD_AddressTokenMsg {
+                address: minter_addr.to_string(),
+                token: AD_TokenMsg {
+                    collection_id: token.collection_id,
+                    token_id: token.token_id,
+                },
+            });
+
+            res = res.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: airdropper_addr.clone().into_string(),
+                msg: to_binary(&update_msg)?,
+                funds: vec![],
+            }));
+        }
+
+        Ok(res)
+    } else {
+        Err(ContractError::NoPromisedMints {})
+    }
+}
+
+fn execute_clean_claimed_tokens_from_shuffle(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    check_can_update(deps.as_ref(), &env, &info)?;
+
+    if let Some(addr) = AIRDROPPER_ADDR.may_load(deps.storage)? {
+        let assigned_token_ids: Vec<AD_TokenMsg> = deps.querier.query_wasm_smart(
+            addr,
+            &AirdropperQueryMsg::GetAssignedTokenIDs {
+                start_after: None,
+                limit: None,
+            },
+        )?;
+
+        for msg in assigned_token_ids {
+            // tokens that were already pulled out of the draw are skipped so running this
+            // twice does not double count the supply
+            take_token_from_draw(deps.storage, msg.collection_id, msg.token_id)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("method", "clean_claimed_tokens_with_shuffle")
+            .add_attribute("sender", info.sender))
+    } else {
+        Err(ContractError::InvalidAirdropperAddress {})
+    }
+}
+
+fn execute_shuffle_token_order(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    // a call that makes no progress would only hold up minting
+    if limit == Some(0) {
+        return Err(ContractError::InvalidShuffleLimit {});
+    }
+
+    // the order being shuffled has to be in the swap table first
+    if TOKEN_ORDER_MIGRATION.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::TokenOrderMigrationInProgress {});
+    }
+
+    let mut res: Response = Response::new();
+
+    let mut progress = match SHUFFLE_PROGRESS.may_load(deps.storage)? {
+        // anyone can push a started shuffle along
+        Some(progress) => progress,
+        None => {
+            let current_token_supply = CURRENT_TOKEN_SUPPLY.load(deps.storage)?;
+
+            if current_token_supply == 0 {
+                return Err(ContractError::MintCompleted {});
+            }
+
+            // pending mints are drawn later and would land on a half shuffled table
+            if (PENDING_MINT_COUNT.may_load(deps.storage)?).unwrap_or(0) > 0 {
+                return Err(ContractError::PendingMintsOutstanding {});
+            }
+
+            let config = CONFIG.load(deps.storage)?;
+
+            // if not admin or maintainer, a fee is needed to start a shuffle
+            if config.admin != info.sender && config.maintainer_addr != Some(info.sender.clone()) {
+                // minting is blocked while a shuffle runs, so only before it opens
+                if config.start_time <= env.block.time {
+                    return Err(ContractError::Unauthorized {});
+                }
+
+                // check payment
+                let payment = must_pay(&info, &config.bonded_denom)?;
+
+                if payment != Uint128::from(DEFAULT_FEE_AMOUNT) {
+                    return Err(ContractError::InvalidFeeAmount {
+                        denom: config.bonded_denom,
+                        fee: DEFAULT_FEE_AMOUNT,
+                        operation: "shuffle_token_order".to_string(),
+                    });
+                }
+
+                let fee_collection_addr = FEE_COLLECTION_ADDR.load(deps.storage)?;
+
+                let msg = BankMsg::Send {
+                    to_address: fee_collection_addr.into_string(),
+                    amount: vec![coin(DEFAULT_FEE_AMOUNT, config.bonded_denom)],
+                };
+
+                res = res.add_message(msg);
+            }
+
+            let collection_supplies: Vec<(u64, u32)> = COLLECTION_CURRENT_TOKEN_SUPPLY
+                .range(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<(u64, u32)>>>()?;
+
+            let (collection_id, supply) = collection_supplies[0];
+
+            ShuffleProgress {
+                collection_id,
+                position: supply.saturating_sub(1),
+                swaps_done: 0,
+                total_swaps: collection_supplies
+                    .iter()
+                    .map(|&(_, supply)| supply.saturating_sub(1) as u64)
+                    .sum(),
+            }
+        }
     };
 
-    // remove token from vec by swapping the item at token index to the end of the vec
-    // then resize the vec to the new length and save
-    if let Some(idx) = token_index {
-        collection_token_ids.swap(idx as usize, collection_length);
-        collection_token_ids.resize(collection_length, 0);
+    let mut swaps_left = cmp::min(limit.unwrap_or(DEFAULT_SHUFFLE_LIMIT), MAX_SHUFFLE_LIMIT);
+
+    let collection_supplies: Vec<(u64, u32)> = COLLECTION_CURRENT_TOKEN_SUPPLY
+        .range(
+            deps.storage,
+            Some(Bound::inclusive(progress.collection_id)),
+            None,
+            Order::Ascending,
+        )
+        .collect::<StdResult<Vec<(u64, u32)>>>()?;
+
+    let mut is_complete = true;
+
+    for (collection_id, supply) in collection_supplies {
+        let from_position = if collection_id == progress.collection_id {
+            progress.position
+        } else {
+            supply.saturating_sub(1)
+        };
 
-        CW721_SHUFFLED_TOKEN_IDS.save(deps.storage, collection_id, &collection_token_ids)?;
+        let swaps = cmp::min(from_position, swaps_left);
 
-        COLLECTION_CURRENT_TOKEN_SUPPLY.save(
+        let next_position = shuffle_token_ids(
             deps.storage,
+            &env,
+            info.sender.clone(),
             collection_id,
-            &(collection_length as u32),
+            from_position,
+            swaps,
         )?;
 
-        if collection_length == 0 {
-            config.bundle_completed = true;
-            CONFIG.save(deps.storage, &config)?;
-        }
+        progress.swaps_done += swaps as u64;
+        swaps_left -= swaps;
+
+        if next_position > 0 {
+            progress.collection_id = collection_id;
+            progress.position = next_position;
+            is_complete = false;
+            break;
+        }
+    }
+
+    if is_complete {
+        SHUFFLE_PROGRESS.remove(deps.storage);
+    } else {
+        SHUFFLE_PROGRESS.save(deps.storage, &progress)?;
+    }
+
+    Ok(res
+        .add_attribute("method", "shuffle_token_order")
+        .add_attribute("swaps_done", progress.swaps_done.to_string())
+        .add_attribute("total_swaps", progress.total_swaps.to_string())
+        .add_attribute("sender", info.sender))
+}
+
+/// copies `limit` positions of the 0.1.x shuffled vecs into the swap table, picking up
+/// where the last call stopped. a collection's vec is dropped once it is copied
+fn execute_migrate_token_order(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    if limit == Some(0) {
+        return Err(ContractError::InvalidTokenOrderMigrationLimit {});
+    }
+
+    let mut migration = TOKEN_ORDER_MIGRATION
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoTokenOrderMigration {})?;
+
+    let mut positions_left = cmp::min(
+        limit.unwrap_or(DEFAULT_TOKEN_ORDER_MIGRATION_LIMIT),
+        MAX_TOKEN_ORDER_MIGRATION_LIMIT,
+    );
+    let mut positions_done = 0u32;
+    let mut is_complete = false;
+
+    while positions_left > 0 {
+        let token_ids =
+            LEGACY_CW721_SHUFFLED_TOKEN_IDS.load(deps.storage, migration.collection_id)?;
+        let to_position = cmp::min(migration.position + positions_left, token_ids.len() as u32);
+
+        for position in migration.position..to_position {
+            set_shuffled_token_id(
+                deps.storage,
+                migration.collection_id,
+                position,
+                token_ids[position as usize],
+            )?;
+        }
+
+        positions_done += to_position - migration.position;
+        positions_left -= to_position - migration.position;
+        migration.position = to_position;
+
+        if (to_position as usize) < token_ids.len() {
+            break;
+        }
+
+        LEGACY_CW721_SHUFFLED_TOKEN_IDS.remove(deps.storage, migration.collection_id);
+
+        match LEGACY_CW721_SHUFFLED_TOKEN_IDS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .transpose()?
+        {
+            Some(collection_id) => {
+                migration = TokenOrderMigration {
+                    collection_id,
+                    position: 0,
+                }
+            }
+            None => {
+                is_complete = true;
+                break;
+            }
+        }
+    }
+
+    if is_complete {
+        TOKEN_ORDER_MIGRATION.remove(deps.storage);
+    } else {
+        TOKEN_ORDER_MIGRATION.save(deps.storage, &migration)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate_token_order")
+        .add_attribute("positions_done", positions_done.to_string())
+        .add_attribute("is_complete", is_complete.to_string())
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_submodule_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    target: ExecutionTarget,
+    msg: CosmosMsg<Empty>,
+) -> Result<Response, ContractError> {
+    check_can_update(deps.as_ref(), &env, &info)?;
+
+    // extract target contract address from cosmosmsg::wasmmsg
+    let target_contract_address: String = match msg.clone() {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            msg: _,
+            funds: _,
+        }) => contract_addr.to_lowercase(),
+        _ => {
+            return Err(ContractError::Unauthorized {});
+        }
+    };
+
+    // extract stored contract address
+    let contract_address: String = match target {
+        ExecutionTarget::Airdropper => (AIRDROPPER_ADDR.load(deps.storage)?)
+            .into_string()
+            .to_lowercase(),
+        ExecutionTarget::Whitelist => (WHITELIST_ADDR.load(deps.storage)?)
+            .into_string()
+            .to_lowercase(),
+        _ => {
+            return Err(ContractError::Unauthorized {});
+        }
+    };
+
+    // ensure addresses match
+    if contract_address != target_contract_address {
+        return Err(ContractError::InvalidTargetAddress {});
+    }
+
+    Ok(Response::default()
+        .add_attribute("method", "submodule_hook")
+        .add_message(msg))
+}
+
+fn execute_disburse_funds(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // EITHER admin (minting contract) or maintainer can update/
+    if config.admin != info.sender && config.maintainer_addr != Some(info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // a raise that is not met may still have to be paid back
+    if !minimum_raise_met(deps.storage, &config)? {
+        return Err(ContractError::MinimumRaiseNotMet {});
+    }
+
+    // refunds are paid out of the escrow
+    if let Some(until) = ESCROW_LOCKED_UNTIL.may_load(deps.storage)? {
+        if env.block.time < until {
+            return Err(ContractError::EscrowLocked { until });
+        }
+    }
+
+    let balances: Vec<AddrBal> = BANK_BALANCES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let ((addr, denom), balance) = item?;
+            Ok(AddrBal {
+                addr,
+                denom,
+                balance,
+            })
+        })
+        .collect::<StdResult<Vec<AddrBal>>>()
+        .unwrap();
+
+    // contract balance of each escrowed denom, queried the first time it comes up
+    let mut remaining_balances: Vec<Coin> = vec![];
+    let mut msgs: Vec<CosmosMsg> = vec![];
+
+    for addr_bal in balances {
+        let position = match remaining_balances
+            .iter()
+            .position(|remaining| remaining.denom == addr_bal.denom)
+        {
+            Some(position) => position,
+            None => {
+                remaining_balances.push(coin(
+                    query_contract_balance(deps.as_ref(), &env, &addr_bal.denom)?.u128(),
+                    addr_bal.denom.clone(),
+                ));
+                remaining_balances.len() - 1
+            }
+        };
+        let remaining_balance = &mut remaining_balances[position].amount;
+
+        if addr_bal.balance > Uint128::zero() && *remaining_balance >= addr_bal.balance {
+            msgs.push(transfer_msg(
+                &addr_bal.addr,
+                addr_bal.balance,
+                addr_bal.denom.clone(),
+            )?);
+
+            *remaining_balance -= addr_bal.balance;
+            BANK_BALANCES.save(
+                deps.storage,
+                (addr_bal.addr, addr_bal.denom),
+                &Uint128::zero(),
+            )?;
+        }
+    }
+    Ok(Response::default()
+        .add_attribute("method", "disburse_funds")
+        .add_messages(msgs))
+}
+
+fn execute_commit_randomness(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    commitment: String,
+) -> Result<Response, ContractError> {
+    match RANDOMNESS_COMMIT.may_load(deps.storage)? {
+        // a revealed secret is public, so the mints after it need a new one
+        Some(RandomnessCommit {
+            secret: Some(_), ..
+        }) => {
+            let config = CONFIG.load(deps.storage)?;
+
+            if config.admin != info.sender && config.maintainer_addr != Some(info.sender.clone()) {
+                return Err(ContractError::Unauthorized {});
+            }
+        }
+        // a closed raffle is drawn with a secret committed after its deadline
+        None if raffle_closed(deps.storage, &env)? => {
+            let config = CONFIG.load(deps.storage)?;
+
+            if config.admin != info.sender && config.maintainer_addr != Some(info.sender.clone()) {
+                return Err(ContractError::Unauthorized {});
+            }
+        }
+        // a pending commitment can be replaced until the campaign starts
+        _ => {
+            check_can_update(deps.as_ref(), &env, &info)?;
+        }
+    }
+
+    if commitment.len() != 64 || !commitment.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ContractError::InvalidRandomnessCommitment {});
+    }
+
+    let commitment = commitment.to_lowercase();
+
+    if RANDOMNESS_SECRETS.has(deps.storage, commitment.clone()) {
+        return Err(ContractError::RandomnessAlreadyRevealed {});
+    }
+
+    RANDOMNESS_COMMIT.save(
+        deps.storage,
+        &RandomnessCommit {
+            commitment: commitment.clone(),
+            secret: None,
+            committed_at: env.block.time,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "commit_randomness")
+        .add_attribute("commitment", commitment)
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_reveal_randomness(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    secret: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // EITHER admin (minting contract) or maintainer can reveal
+    if config.admin != info.sender && config.maintainer_addr != Some(info.sender.clone()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut randomness_commit = RANDOMNESS_COMMIT
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoRandomnessCommitment {})?;
+
+    if randomness_commit.secret.is_some() {
+        return Err(ContractError::RandomnessAlreadyRevealed {});
+    }
+
+    if format!("{:x}", Sha256::digest(secret.as_bytes())) != randomness_commit.commitment {
+        return Err(ContractError::RandomnessRevealMismatch {});
+    }
+
+    RANDOMNESS_SECRETS.save(deps.storage, randomness_commit.commitment.clone(), &secret)?;
+
+    randomness_commit.secret = Some(secret);
+    RANDOMNESS_COMMIT.save(deps.storage, &randomness_commit)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "reveal_randomness")
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_configure_drand(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pubkey: Binary,
+    genesis_time: u64,
+    period: u64,
+) -> Result<Response, ContractError> {
+    check_can_update(deps.as_ref(), &env, &info)?;
+
+    g1_from_variable(pubkey.as_slice()).map_err(|_| ContractError::InvalidDrandPubkey {})?;
+
+    if period == 0 {
+        return Err(ContractError::InvalidDrandPeriod {});
+    }
+
+    // every mint after this waits for a round, which a bundle can't
+    if CONFIG.load(deps.storage)?.bundle_enabled {
+        return Err(ContractError::DrandWithBundleMint {});
+    }
+
+    DRAND_CONFIG.save(
+        deps.storage,
+        &DrandConfig {
+            pubkey,
+            genesis_time,
+            period,
+        },
+    )?;
+
+    // beacons verified against a previous key no longer count
+    let rounds: Vec<u64> = DRAND_BEACONS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<u64>>>()?;
+    for round in rounds {
+        DRAND_BEACONS.remove(deps.storage, round);
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "configure_drand")
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_submit_drand_beacon(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    round: u64,
+    previous_signature: Binary,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    let drand = DRAND_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::DrandNotConfigured {})?;
+
+    // any round can be submitted, draws only use the one fixed when they were paid
+    if DRAND_BEACONS.has(deps.storage, round) {
+        return Err(ContractError::DrandBeaconExists { round });
+    }
+
+    let pk = g1_from_variable(drand.pubkey.as_slice())
+        .map_err(|_| ContractError::InvalidDrandPubkey {})?;
+    let is_valid = verify(
+        &pk,
+        round,
+        previous_signature.as_slice(),
+        signature.as_slice(),
+    )
+    .unwrap_or(false);
+
+    if !is_valid {
+        return Err(ContractError::InvalidDrandBeacon { round });
+    }
+
+    DRAND_BEACONS.save(
+        deps.storage,
+        round,
+        &DrandBeacon {
+            round,
+            randomness: Binary::from(derive_randomness(signature.as_slice()).to_vec()),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "submit_drand_beacon")
+        .add_attribute("round", round.to_string())
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_configure_randomness_proxy(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: Option<String>,
+) -> Result<Response, ContractError> {
+    check_can_update(deps.as_ref(), &env, &info)?;
+
+    match maybe_addr(deps.api, address)? {
+        Some(proxy_addr) => RANDOMNESS_PROXY.save(deps.storage, &proxy_addr)?,
+        None => RANDOMNESS_PROXY.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "configure_randomness_proxy")
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_receive_randomness(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    callback: RandomnessCallback,
+) -> Result<Response, ContractError> {
+    if RANDOMNESS_PROXY.may_load(deps.storage)? != Some(info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pending_mint = PENDING_MINTS
+        .may_load(deps.storage, callback.job_id.clone())?
+        .ok_or(ContractError::UnknownRandomnessJob {
+            job_id: callback.job_id.clone(),
+        })?;
+
+    PENDING_MINTS.remove(deps.storage, callback.job_id.clone());
+
+    let mut res = Response::new();
+
+    let receipt = mint_receipt(
+        deps.storage,
+        &env,
+        pending_mint.paid / Uint128::from(pending_mint.quantity),
+        &pending_mint.denom,
+        pending_mint.referrer.clone(),
+    )?;
+
+    for nonce in 0..pending_mint.quantity {
+        release_pending_mint(deps.storage)?;
+
+        let draw_id = match nonce {
+            0 => callback.job_id.clone(),
+            _ => format!("{}-{}", callback.job_id, nonce),
+        };
+        let (collection_id, token_index) =
+            draw_mint_from_randomness(deps.storage, &env, &callback.randomness, &draw_id)?;
+
+        res = res.add_message(process_and_get_mint_msg(
+            deps.branch(),
+            pending_mint.minter_addr.clone(),
+            collection_id,
+            MintToken::Drawn(token_index),
+            receipt.as_ref(),
+        )?);
+    }
+
+    res = settle_mint_payment(
+        deps,
+        &env,
+        res,
+        &pending_mint.minter_addr,
+        &pending_mint.mint_type,
+        pending_mint.quantity,
+        pending_mint.paid,
+        pending_mint.denom,
+        pending_mint.referrer.as_ref(),
+    )?;
+
+    Ok(res
+        .add_attribute("method", "receive_randomness")
+        .add_attribute("job_id", callback.job_id)
+        .add_attribute("minter", pending_mint.minter_addr))
+}
+
+fn execute_refund_pending_mint(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    job_id: String,
+) -> Result<Response, ContractError> {
+    let pending_mint = PENDING_MINTS
+        .may_load(deps.storage, job_id.clone())?
+        .ok_or(ContractError::UnknownRandomnessJob {
+            job_id: job_id.clone(),
+        })?;
+
+    if env.block.height <= pending_mint.expires_at_height {
+        return Err(ContractError::PendingMintNotExpired {});
+    }
+
+    // a late callback for this job is rejected as unknown
+    PENDING_MINTS.remove(deps.storage, job_id.clone());
+    release_unpaid_mint(
+        deps.storage,
+        &pending_mint.minter_addr,
+        &pending_mint.mint_type,
+        pending_mint.quantity,
+    )?;
+
+    let mut res = Response::new();
+
+    if !pending_mint.paid.is_zero() {
+        res = res.add_message(transfer_msg(
+            &pending_mint.minter_addr,
+            pending_mint.paid,
+            pending_mint.denom,
+        )?);
+    }
+
+    Ok(res
+        .add_attribute("method", "refund_pending_mint")
+        .add_attribute("job_id", job_id)
+        .add_attribute("minter", pending_mint.minter_addr)
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_configure_delayed_mint(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    expiry_blocks: Option<u64>,
+) -> Result<Response, ContractError> {
+    check_can_update(deps.as_ref(), &env, &info)?;
+
+    match expiry_blocks {
+        Some(0) => return Err(ContractError::InvalidMintTicketExpiry {}),
+        Some(expiry_blocks) => DELAYED_MINT_EXPIRY_BLOCKS.save(deps.storage, &expiry_blocks)?,
+        None => DELAYED_MINT_EXPIRY_BLOCKS.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "configure_delayed_mint")
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_settle_mint_ticket(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    minter_address: String,
+    height: u64,
+) -> Result<Response, ContractError> {
+    let minter_addr = deps.api.addr_validate(&minter_address)?;
+    let ticket = MINT_TICKETS
+        .may_load(deps.storage, (minter_addr.clone(), height))?
+        .ok_or(ContractError::MintTicketNotFound {})?;
+
+    // the draw has to come from a block that did not exist when the ticket was paid for
+    if env.block.height <= ticket.height {
+        return Err(ContractError::MintTicketNotReady {});
+    }
+
+    if env.block.height > ticket.expires_at_height {
+        return Err(ContractError::MintTicketExpired {});
+    }
+
+    let randomness = hidden_randomness(deps.storage, &ticket.commitment, ticket.drand_round)?;
+
+    MINT_TICKETS.remove(deps.storage, (minter_addr.clone(), height));
+
+    let mut res = Response::new();
+
+    let mint_denom = match ticket.denom {
+        Some(denom) => denom,
+        None => CONFIG.load(deps.storage)?.mint_denom,
+    };
+    let receipt = mint_receipt(
+        deps.storage,
+        &env,
+        ticket.paid / Uint128::from(ticket.quantity),
+        &mint_denom,
+        ticket.referrer.clone(),
+    )?;
+
+    for nonce in 0..ticket.quantity {
+        release_pending_mint(deps.storage)?;
+
+        let (collection_id, token_index) = draw_mint_from_randomness(
+            deps.storage,
+            &env,
+            &randomness,
+            &format!("ticket-{}-{}-{}", minter_addr, height, nonce),
+        )?;
+
+        res = res.add_message(process_and_get_mint_msg(
+            deps.branch(),
+            minter_addr.clone(),
+            collection_id,
+            MintToken::Drawn(token_index),
+            receipt.as_ref(),
+        )?);
+    }
+
+    // a ticket settled after the raise is missed stays refundable as a ticket instead
+    res = settle_mint_payment(
+        deps,
+        &env,
+        res,
+        &minter_addr,
+        &ticket.mint_type,
+        ticket.quantity,
+        ticket.paid,
+        mint_denom,
+        ticket.referrer.as_ref(),
+    )?;
+
+    Ok(res
+        .add_attribute("method", "settle_mint_ticket")
+        .add_attribute("minter", minter_addr)
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_refund_mint_ticket(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    minter_address: String,
+    height: u64,
+) -> Result<Response, ContractError> {
+    let minter_addr = deps.api.addr_validate(&minter_address)?;
+    let ticket = MINT_TICKETS
+        .may_load(deps.storage, (minter_addr.clone(), height))?
+        .ok_or(ContractError::MintTicketNotFound {})?;
+
+    if env.block.height <= ticket.expires_at_height {
+        return Err(ContractError::MintTicketNotExpired {});
+    }
+
+    MINT_TICKETS.remove(deps.storage, (minter_addr.clone(), height));
+    release_unpaid_mint(
+        deps.storage,
+        &minter_addr,
+        &ticket.mint_type,
+        ticket.quantity,
+    )?;
+
+    let mut res = Response::new();
+
+    if !ticket.paid.is_zero() {
+        let mint_denom = match ticket.denom {
+            Some(denom) => denom,
+            None => CONFIG.load(deps.storage)?.mint_denom,
+        };
+
+        res = res.add_message(transfer_msg(&minter_addr, ticket.paid, mint_denom)?);
+    }
+
+    Ok(res
+        .add_attribute("method", "refund_mint_ticket")
+        .add_attribute("minter", minter_addr)
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_settle_auction_rebate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    minter_address: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let auction = config
+        .dutch_auction
+        .clone()
+        .ok_or(ContractError::NoDutchAuction {})?;
+
+    let clearing_price = auction_clearing_price(deps.storage, &env, &config, &auction)?
+        .ok_or(ContractError::AuctionNotFinal {})?;
+
+    let minter_addr = deps.api.addr_validate(&minter_address)?;
+    let purchase = AUCTION_PURCHASES
+        .may_load(deps.storage, minter_addr.clone())?
+        .ok_or(ContractError::NoAuctionPurchase {})?;
+
+    AUCTION_PURCHASES.remove(deps.storage, minter_addr.clone());
+
+    // the floor price went to the revenue split at mint time
+    let count = Uint128::from(purchase.count);
+    let owed = clearing_price.checked_mul(count).map_err(StdError::from)?;
+    let rebate = purchase.paid.saturating_sub(owed);
+    let release = owed.saturating_sub(
+        auction
+            .floor_price
+            .checked_mul(count)
+            .map_err(StdError::from)?,
+    );
+
+    let mut res = Response::new();
+
+    if !rebate.is_zero() {
+        res = res.add_message(BankMsg::Send {
+            to_address: minter_addr.to_string(),
+            amount: vec![coin(rebate.u128(), config.mint_denom.clone())],
+        });
+    }
+
+    res = disburse_or_escrow_funds(deps, res, release, config.mint_denom, None)?;
+
+    Ok(res
+        .add_attribute("method", "settle_auction_rebate")
+        .add_attribute("minter", minter_addr)
+        .add_attribute("clearing_price", clearing_price)
+        .add_attribute("rebate", rebate)
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_update_collection_mint(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    collection_id: u64,
+    mint_price: Option<Uint128>,
+    mint_enabled: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if config.admin != info.sender && config.maintainer_addr != Some(info.sender.clone()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut coll_info = CW721_COLLECTION_INFO
+        .may_load(deps.storage, collection_id)?
+        .ok_or(ContractError::CollectionNotFound { collection_id })?;
+
+    coll_info.mint_price = mint_price;
+    coll_info.mint_enabled = Some(mint_enabled);
+
+    CW721_COLLECTION_INFO.save(deps.storage, collection_id, &coll_info)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_collection_mint")
+        .add_attribute("collection_id", collection_id.to_string())
+        .add_attribute("mint_enabled", mint_enabled.to_string())
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_register_mint_codes(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    codes: Vec<MintCodeMsg>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if config.admin != info.sender && config.maintainer_addr != Some(info.sender.clone()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for code in &codes {
+        let valid_discount = match code.discount {
+            MintCodeDiscount::Bps(bps) => bps <= MAX_BPS,
+            MintCodeDiscount::FixedPrice(_) => true,
+        };
+
+        if code.hash.len() != 64
+            || !code.hash.chars().all(|c| c.is_ascii_hexdigit())
+            || code.uses == 0
+            || !valid_discount
+        {
+            return Err(ContractError::InvalidMintCodes {});
+        }
+
+        MINT_CODES.save(
+            deps.storage,
+            code.hash.to_lowercase(),
+            &MintCode {
+                uses_remaining: code.uses,
+                discount: code.discount.clone(),
+            },
+        )?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "register_mint_codes")
+        .add_attribute("count", codes.len().to_string())
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_remove_mint_codes(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    hashes: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if config.admin != info.sender && config.maintainer_addr != Some(info.sender.clone()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for hash in &hashes {
+        MINT_CODES.remove(deps.storage, hash.to_lowercase());
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "remove_mint_codes")
+        .add_attribute("count", hashes.len().to_string())
+        .add_attribute("sender", info.sender))
+}
+
+fn execute_update_provenance_hash(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection_id: u64,
+    provenance_hash: String,
+) -> Result<Response, ContractError> {
+    // also locks the hash once the campaign starts
+    check_can_update(deps.as_ref(), &env, &info)?;
+
+    let mut coll_info = CW721_COLLECTION_INFO
+        .may_load(deps.storage, collection_id)?
+        .ok_or(ContractError::CollectionNotFound { collection_id })?;
+
+    let provenance_hash = validate_provenance_hash(provenance_hash)?;
+    coll_info.provenance_hash = Some(provenance_hash.clone());
+
+    CW721_COLLECTION_INFO.save(deps.storage, collection_id, &coll_info)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_provenance_hash")
+        .add_attribute("collection_id", collection_id.to_string())
+        .add_attribute("provenance_hash", provenance_hash)
+        .add_attribute("sender", info.sender))
+}
+
+// #region helper functions
+
+struct ValidateCollectionInfoResponse {
+    pub collection_infos: Vec<CollectionInfo>,
+    pub total_token_supply: u32,
+}
+
+/// where the token of a mint comes from
+enum MintToken {
+    /// drawn from this position of the collection's draw
+    Drawn(u32),
+    /// promised by the airdropper
+    Promised(u32),
+    /// won in a token auction, which already holds it out of the draw
+    Auctioned(u32),
+}
+
+/// also stores
+fn process_and_get_mint_msg(
+    deps: DepsMut,
+    minter_addr: Addr,
+    collection_id: u64,
+    token: MintToken,
+    receipt: Option<&MintReceipt>,
+) -> Result<CosmosMsg, ContractError> {
+    let token_id: u32 = match token {
+        MintToken::Drawn(position) => {
+            remove_token_from_draw(deps.storage, collection_id, position)?
+        }
+        MintToken::Promised(token_id) => {
+            // only a token that went back into the draw unsold is free again
+            if (TOKEN_AUCTIONS.may_load(deps.storage, (collection_id, token_id))?)
+                .map_or(false, |auction| {
+                    !auction.settled || auction.highest_bid.is_some()
+                })
+            {
+                return Err(ContractError::TokenAuctioned {});
+            }
+
+            // promised tokens may already have been pulled out of the draw by
+            // `CleanClaimedTokensFromShuffle`, in which case the supply is already updated
+            take_token_from_draw(deps.storage, collection_id, token_id)?;
+
+            token_id
+        }
+        MintToken::Auctioned(token_id) => token_id,
+    };
+
+    // record the order tokens went out in for provenance checks
+    let mint_order = (MINT_ORDER_COUNTER.may_load(deps.storage)?).unwrap_or(0) + 1;
+    MINT_ORDER_COUNTER.save(deps.storage, &mint_order)?;
+    TOKEN_MINT_ORDER.save(deps.storage, (collection_id, token_id), &mint_order)?;
+
+    if let Some(receipt) = receipt {
+        MINT_RECEIPTS.save(deps.storage, (collection_id, token_id), receipt)?;
+
+        let locked_until = ESCROW_LOCKED_UNTIL.may_load(deps.storage)?;
+        if locked_until.map_or(true, |until| until < receipt.refundable_until) {
+            ESCROW_LOCKED_UNTIL.save(deps.storage, &receipt.refundable_until)?;
+        }
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+
+    // Create mint msgs
+    let coll_info: CollectionInfo = CW721_COLLECTION_INFO.load(deps.storage, collection_id)?;
+
+    let mint_msg: Cw721ExecuteMsg<SharedCollectionInfo, Empty> =
+        Cw721ExecuteMsg::Mint(MintMsg::<SharedCollectionInfo> {
+            token_id: token_id.to_string(),
+            owner: minter_addr.into_string(),
+            token_uri: Some(format!("{}/{}", coll_info.base_token_uri, token_id)),
+            extension: config.extension,
+        });
+
+    let token_address = CW721_ADDRS.load(deps.storage, coll_info.id)?;
+
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token_address.into_string(),
+        msg: to_binary(&mint_msg)?,
+        funds: vec![],
+    }))
+}
+
+/// token_id currently sitting at `position` of a collection's draw. positions without an
+/// entry in the swap table still hold their original token_id
+fn shuffled_token_id_at(
+    storage: &dyn Storage,
+    collection_id: u64,
+    position: u32,
+) -> StdResult<u32> {
+    Ok(CW721_SHUFFLED_TOKEN_IDS
+        .may_load(storage, (collection_id, position))?
+        .unwrap_or(position + 1))
+}
+
+/// writes `token_id` to `position`, keeping the reverse lookup in sync. identity entries
+/// are removed instead of stored so the table only grows with the number of swaps
+fn set_shuffled_token_id(
+    storage: &mut dyn Storage,
+    collection_id: u64,
+    position: u32,
+    token_id: u32,
+) -> StdResult<()> {
+    if token_id == position + 1 {
+        CW721_SHUFFLED_TOKEN_IDS.remove(storage, (collection_id, position));
+        CW721_TOKEN_ID_POSITIONS.remove(storage, (collection_id, token_id));
+    } else {
+        CW721_SHUFFLED_TOKEN_IDS.save(storage, (collection_id, position), &token_id)?;
+        CW721_TOKEN_ID_POSITIONS.save(storage, (collection_id, token_id), &position)?;
+    }
+
+    Ok(())
+}
+
+/// position of `token_id` if it is still available to draw
+pub fn get_shuffled_token_position(
+    storage: &dyn Storage,
+    collection_id: u64,
+    token_id: u32,
+) -> StdResult<Option<u32>> {
+    if let Some(position) = CW721_TOKEN_ID_POSITIONS.may_load(storage, (collection_id, token_id))? {
+        return Ok(Some(position));
+    }
+
+    if token_id == 0 {
+        return Ok(None);
+    }
+
+    // never swapped, so it can only be at its original position if that is still in range
+    // and nothing else has been moved on top of it
+    let remaining = COLLECTION_CURRENT_TOKEN_SUPPLY
+        .may_load(storage, collection_id)?
+        .unwrap_or(0);
+    let position = token_id - 1;
+
+    if position < remaining && shuffled_token_id_at(storage, collection_id, position)? == token_id {
+        Ok(Some(position))
+    } else {
+        Ok(None)
+    }
+}
+
+/// lazy Fisher-Yates removal. returns the token_id at `position` and moves the last
+/// remaining token into its place, so a draw only touches a handful of storage keys
+/// no matter how large the collection is
+pub fn remove_token_from_draw(
+    storage: &mut dyn Storage,
+    collection_id: u64,
+    position: u32,
+) -> Result<u32, ContractError> {
+    // a part-way shuffle holds a cursor into the table, so nothing can move under it
+    check_shuffle_not_in_progress(storage)?;
+
+    let remaining = COLLECTION_CURRENT_TOKEN_SUPPLY.load(storage, collection_id)?;
+
+    if position >= remaining {
+        return Err(ContractError::UnableToMint {});
+    }
+
+    let last_position = remaining - 1;
+    let token_id = shuffled_token_id_at(storage, collection_id, position)?;
+    let last_token_id = shuffled_token_id_at(storage, collection_id, last_position)?;
+
+    CW721_SHUFFLED_TOKEN_IDS.remove(storage, (collection_id, last_position));
+    CW721_TOKEN_ID_POSITIONS.remove(storage, (collection_id, last_token_id));
+    CW721_TOKEN_ID_POSITIONS.remove(storage, (collection_id, token_id));
+
+    if position != last_position {
+        set_shuffled_token_id(storage, collection_id, position, last_token_id)?;
+    }
+
+    COLLECTION_CURRENT_TOKEN_SUPPLY.save(storage, collection_id, &last_position)?;
+
+    let current_token_supply = CURRENT_TOKEN_SUPPLY.load(storage)?;
+    CURRENT_TOKEN_SUPPLY.save(storage, &current_token_supply.saturating_sub(1))?;
+    record_auction_clearing_price(storage)?;
+
+    if last_position == 0 {
+        let mut config = CONFIG.load(storage)?;
+        config.bundle_completed = true;
+        CONFIG.save(storage, &config)?;
+    }
+
+    Ok(token_id)
+}
+
+/// pulls a given token out of the draw, as long as enough supply is left for the
+/// mints already paid and waiting to be drawn. returns false if it was already out
+fn take_token_from_draw(
+    storage: &mut dyn Storage,
+    collection_id: u64,
+    token_id: u32,
+) -> Result<bool, ContractError> {
+    match get_shuffled_token_position(storage, collection_id, token_id)? {
+        Some(position) => {
+            check_mintable_supply(storage, 1)?;
+            remove_token_from_draw(storage, collection_id, position)?;
+
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// puts a refunded token back at the end of its collection's draw. a collection that
+/// ran out has supply again, so bundles are checked against it once more
+fn return_token_to_draw(
+    storage: &mut dyn Storage,
+    collection_id: u64,
+    token_id: u32,
+) -> Result<(), ContractError> {
+    check_shuffle_not_in_progress(storage)?;
+
+    let position = COLLECTION_CURRENT_TOKEN_SUPPLY.load(storage, collection_id)?;
+    set_shuffled_token_id(storage, collection_id, position, token_id)?;
+    COLLECTION_CURRENT_TOKEN_SUPPLY.save(storage, collection_id, &(position + 1))?;
+
+    let current_token_supply = CURRENT_TOKEN_SUPPLY.load(storage)?;
+    CURRENT_TOKEN_SUPPLY.save(storage, &(current_token_supply + 1))?;
+
+    if position == 0 {
+        let mut config = CONFIG.load(storage)?;
+
+        if config.bundle_completed {
+            config.bundle_completed = false;
+            CONFIG.save(storage, &config)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_collection_info(
+    _deps: Deps,
+    msgs: Vec<CollectionInfoMsg>,
+) -> Result<ValidateCollectionInfoResponse, ContractError> {
+    let mut collection_infos: Vec<CollectionInfo> = vec![];
+
+    let mut total_token_supply: u32 = 0;
+    let mut id: u64 = INSTANTIATE_TOKEN_REPLY_ID;
+    for msg in msgs {
+        id += 1;
+        validate_uri(msg.base_token_uri.clone())?;
+
+        let secondary_metadata_uri: Option<String> = match msg.secondary_metadata_uri {
+            Some(uri) => {
+                validate_uri(uri.clone())?;
+                Some(uri)
+            }
+            None => None,
+        };
+
+        let provenance_hash: Option<String> = match msg.provenance_hash {
+            Some(hash) => Some(validate_provenance_hash(hash)?),
+            None => None,
+        };
+
+        if let Some(weight) = msg.weight {
+            if !(1..=MAX_COLLECTION_WEIGHT).contains(&weight) {
+                return Err(ContractError::InvalidCollectionWeight {
+                    max: MAX_COLLECTION_WEIGHT,
+                });
+            }
+        }
+
+        total_token_supply += msg.token_supply;
+
+        collection_infos.push(CollectionInfo {
+            id,
+            token_supply: msg.token_supply,
+            name: msg.name,
+            symbol: msg.symbol,
+            base_token_uri: msg.base_token_uri,
+            secondary_metadata_uri,
+            provenance_hash,
+            mint_price: msg.mint_price,
+            mint_enabled: msg.mint_enabled,
+            weight: msg.weight,
+        })
+    }
+
+    if !(1..=MAX_TOKEN_SUPPLY).contains(&total_token_supply) {
+        return Err(ContractError::InvalidMaxTokenSupply {
+            max: MAX_TOKEN_SUPPLY,
+            input: total_token_supply,
+        });
+    }
+
+    Ok(ValidateCollectionInfoResponse {
+        collection_infos,
+        total_token_supply,
+    })
+}
+
+fn validate_shared_collection_info(
+    deps: Deps,
+    msg: SharedCollectionInfoMsg,
+) -> Result<SharedCollectionInfo, ContractError> {
+    let shared_collection_info: SharedCollectionInfo = SharedCollectionInfo {
+        mint_revenue_share: validate_royalties(deps, msg.mint_revenue_share, true)?,
+        secondary_market_royalties: validate_royalties(
+            deps,
+            msg.secondary_market_royalties,
+            false,
+        )?,
+    };
+
+    Ok(shared_collection_info)
+}
+
+fn validate_uri(uri: String) -> Result<String, ContractError> {
+    // url is too short
+    if uri.len() < 4 {
+        return Err(ContractError::InvalidBaseTokenURI {});
+    }
+
+    // url crate was causing wasm
+
+    Ok(uri)
+}
+
+fn validate_pricing_modes(
+    dutch_auction: &Option<DutchAuction>,
+    bonding_curve: &Option<BondingCurve>,
+    bulk_discounts: &Option<BulkDiscounts>,
+    denom_mint_prices: &Option<Vec<Coin>>,
+    cw20_payment: &Option<Cw20Payment>,
+    oracle_pricing: &Option<OraclePricing>,
+) -> Result<(), ContractError> {
+    // auction rebates are settled in `mint_denom`
+    if dutch_auction.is_some() && oracle_pricing.is_some() {
+        return Err(ContractError::ConflictingPricingModes {});
+    }
+
+    // auction and curve prices are only quoted in `mint_denom`
+    if (denom_mint_prices.iter().flatten().next().is_some() || cw20_payment.is_some())
+        && (dutch_auction.is_some() || bonding_curve.is_some())
+    {
+        return Err(ContractError::ConflictingPricingModes {});
+    }
+
+    if let Some(discounts) = bulk_discounts {
+        validate_discount_tiers(&discounts.whitelist)?;
+        validate_discount_tiers(&discounts.public)?;
+
+        // auction rebates settle against a single clearing price
+        if dutch_auction.is_some() && !discounts.public.is_empty() {
+            return Err(ContractError::ConflictingPricingModes {});
+        }
+    }
+
+    match (dutch_auction, bonding_curve) {
+        (Some(_), Some(_)) => Err(ContractError::ConflictingPricingModes {}),
+        (Some(auction), None) => validate_dutch_auction(auction),
+        _ => Ok(()),
+    }
+}
+
+fn validate_denom_mint_prices(mint_denom: &str, prices: &[Coin]) -> Result<(), ContractError> {
+    for (i, price) in prices.iter().enumerate() {
+        validate_native_denom(price.denom.clone())?;
+
+        if price.amount.is_zero()
+            || price.denom == mint_denom
+            || prices[..i].iter().any(|other| other.denom == price.denom)
+        {
+            return Err(ContractError::InvalidDenomMintPrices {});
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_cw20_payment(
+    deps: Deps,
+    cw20_payment: Option<Cw20PaymentMsg>,
+) -> Result<Option<Cw20Payment>, ContractError> {
+    cw20_payment
+        .map(|cw20_payment| {
+            // a cw20 `Send` can't carry a zero amount
+            if cw20_payment.mint_price.is_zero()
+                || cw20_payment.bundle_mint_price.is_zero()
+                || cw20_payment.whitelist_mint_price == Some(Uint128::zero())
+            {
+                return Err(ContractError::InvalidCw20Payment {});
+            }
+
+            Ok(Cw20Payment {
+                token_addr: deps.api.addr_validate(&cw20_payment.token_address)?,
+                mint_price: cw20_payment.mint_price,
+                whitelist_mint_price: cw20_payment.whitelist_mint_price,
+                bundle_mint_price: cw20_payment.bundle_mint_price,
+            })
+        })
+        .transpose()
+}
+
+fn validate_oracle_pricing(
+    deps: Deps,
+    oracle_pricing: Option<OraclePricingMsg>,
+) -> Result<Option<OraclePricing>, ContractError> {
+    oracle_pricing
+        .map(|oracle_pricing| {
+            if oracle_pricing.slippage_bps > MAX_BPS {
+                return Err(ContractError::InvalidOraclePricing {});
+            }
+
+            Ok(OraclePricing {
+                oracle_addr: deps.api.addr_validate(&oracle_pricing.oracle_address)?,
+                slippage_bps: oracle_pricing.slippage_bps,
+            })
+        })
+        .transpose()
+}
+
+fn validate_referral_bps(referral_bps: Option<u32>) -> Result<(), ContractError> {
+    if referral_bps.unwrap_or(0) > MAX_BPS {
+        return Err(ContractError::InvalidReferralBps {});
+    }
+
+    Ok(())
+}
+
+/// refunds are paid back out of `BANK_BALANCES`, so funds have to be escrowed. a
+/// refunded Dutch auction mint would also leave its rebate unaccounted for
+fn validate_refund_policy(config: &Config) -> Result<(), ContractError> {
+    if config.refund_period.is_some() && (!config.escrow_funds || config.dutch_auction.is_some()) {
+        return Err(ContractError::InvalidRefundPolicy {});
+    }
+
+    Ok(())
+}
+
+/// a raise has to be reachable by `end_time`, and is paid back as a whole rather than
+/// per token or with an auction rebate. token auction bids don't count towards it, so
+/// none can be open
+fn validate_minimum_raise(storage: &dyn Storage, config: &Config) -> Result<(), ContractError> {
+    let valid_threshold = match &config.minimum_raise {
+        None => return Ok(()),
+        Some(MinimumRaise::Tokens(tokens)) => (1..=config.total_token_supply).contains(tokens),
+        Some(MinimumRaise::Revenue(revenue)) => !revenue.is_zero(),
+    };
+
+    let has_open_token_auction = TOKEN_AUCTIONS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(_, TokenAuction)>>>()?
+        .iter()
+        .any(|(_, auction)| !auction.settled);
+
+    if !valid_threshold
+        || config.end_time.is_none()
+        || config.dutch_auction.is_some()
+        || config.refund_period.is_some()
+        || has_open_token_auction
+    {
+        return Err(ContractError::InvalidMinimumRaise {});
+    }
+
+    Ok(())
+}
+
+/// raffle tickets are paid at `mint_price` in `mint_denom`, so the public price can't
+/// move with time or supply. winners count towards `max_per_address_mint`
+fn validate_raffle(config: &Config) -> Result<(), ContractError> {
+    let raffle = match &config.raffle {
+        None => return Ok(()),
+        Some(raffle) => raffle,
+    };
+
+    if !(1..=config.max_per_address_mint).contains(&raffle.max_tickets_per_address)
+        || config
+            .end_time
+            .map_or(false, |end_time| raffle.deadline >= end_time)
+        || config.dutch_auction.is_some()
+        || config.bonding_curve.is_some()
+        || config.oracle_pricing.is_some()
+        || config.minimum_raise.is_some()
+    {
+        return Err(ContractError::InvalidRaffle {});
+    }
+
+    Ok(())
+}
+
+/// past the deadline of a `raffle` that has no winner count yet
+fn raffle_closed(storage: &dyn Storage, env: &Env) -> StdResult<bool> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(match &config.raffle {
+        Some(raffle) => {
+            raffle.deadline <= env.block.time
+                && (RAFFLE_PROGRESS.may_load(storage)?)
+                    .map_or(true, |progress| progress.winner_count.is_none())
+        }
+        None => false,
+    })
+}
+
+/// always true without a `raffle`
+pub fn raffle_settled(storage: &dyn Storage, config: &Config) -> StdResult<bool> {
+    if config.raffle.is_none() {
+        return Ok(true);
+    }
+
+    Ok(match RAFFLE_PROGRESS.may_load(storage)? {
+        Some(RaffleProgress {
+            winner_count: Some(winner_count),
+            winners_drawn,
+            ..
+        }) => winners_drawn >= winner_count,
+        _ => false,
+    })
+}
+
+/// draws a winner out of the tickets from `position` on and swaps it to `position`,
+/// so the tickets before it are the winners drawn so far. the seed only depends on
+/// `randomness` and `position`, so neither the block nor `limit` moves the result
+fn draw_raffle_ticket(
+    storage: &mut dyn Storage,
+    env: &Env,
+    randomness: &Binary,
+    position: u32,
+    ticket_count: u32,
+) -> Result<Addr, ContractError> {
+    let draw_id = format!("raffle-ticket-{}", position);
+    let tickets = ticket_count - position;
+
+    let mut seed = randomness.to_vec();
+    seed.extend_from_slice(draw_id.as_bytes());
+
+    let index = draw_index_from_seed(&seed, tickets);
+
+    record_draw(
+        storage,
+        env,
+        DrawKind::RaffleTicket {
+            draw_id,
+            randomness: randomness.clone(),
+            position,
+            tickets,
+            index,
+        },
+    )?;
+
+    let winner = RAFFLE_TICKETS.load(storage, position + index)?;
+
+    if index != 0 {
+        let holder = RAFFLE_TICKETS.load(storage, position)?;
+        RAFFLE_TICKETS.save(storage, position + index, &holder)?;
+        RAFFLE_TICKETS.save(storage, position, &winner)?;
+    }
+
+    Ok(winner)
+}
+
+/// always true without a `minimum_raise`
+pub fn minimum_raise_met(storage: &dyn Storage, config: &Config) -> StdResult<bool> {
+    let progress = (RAISE_PROGRESS.may_load(storage)?).unwrap_or_default();
+
+    Ok(match &config.minimum_raise {
+        None => true,
+        Some(MinimumRaise::Tokens(tokens)) => progress.tokens_sold >= *tokens,
+        Some(MinimumRaise::Revenue(revenue)) => progress.revenue >= *revenue,
+    })
+}
+
+/// `end_time` has passed without the `minimum_raise` being met
+pub fn minimum_raise_missed(storage: &dyn Storage, env: &Env, config: &Config) -> StdResult<bool> {
+    let ended = config
+        .end_time
+        .map_or(false, |end_time| end_time <= env.block.time);
+
+    Ok(ended && !minimum_raise_met(storage, config)?)
+}
+
+/// counts a paid mint towards the `minimum_raise`, and while it is pending records
+/// what `payer` paid so it can be claimed back
+fn record_raise_contribution(
+    storage: &mut dyn Storage,
+    env: &Env,
+    payer: &Addr,
+    quantity: u32,
+    paid: Uint128,
+    denom: &str,
+) -> Result<(), ContractError> {
+    let config = CONFIG.load(storage)?;
+
+    if config.minimum_raise.is_none() {
+        return Ok(());
+    }
+
+    if minimum_raise_missed(storage, env, &config)? {
+        return Err(ContractError::MinimumRaiseMissed {});
+    }
+
+    if !paid.is_zero() && !minimum_raise_met(storage, &config)? {
+        let key = (payer.clone(), denom.to_string());
+        let contribution =
+            (RAISE_CONTRIBUTIONS.may_load(storage, key.clone())?).unwrap_or_default();
+        RAISE_CONTRIBUTIONS.save(storage, key, &(contribution + paid))?;
+    }
+
+    let mut progress = (RAISE_PROGRESS.may_load(storage)?).unwrap_or_default();
+    progress.tokens_sold += quantity;
+    if denom == config.mint_denom {
+        progress.revenue += paid;
+    }
+    RAISE_PROGRESS.save(storage, &progress)?;
+
+    Ok(())
+}
+
+/// receipt for each token of a mint paid `paid_per_token`, if it can be refunded
+fn mint_receipt(
+    storage: &dyn Storage,
+    env: &Env,
+    paid_per_token: Uint128,
+    denom: &str,
+    referrer: Option<Addr>,
+) -> StdResult<Option<MintReceipt>> {
+    let config = CONFIG.load(storage)?;
+
+    let refund_period = match config.refund_period {
+        Some(refund_period) if !paid_per_token.is_zero() => refund_period,
+        _ => return Ok(None),
+    };
+
+    // the token's share of what `disburse_or_escrow_funds` credits the referrer
+    let referral_reward = match referrer {
+        Some(_) => paid_per_token.multiply_ratio(config.referral_bps.unwrap_or(0), MAX_BPS),
+        None => Uint128::zero(),
+    };
+
+    Ok(Some(MintReceipt {
+        paid: coin(paid_per_token.u128(), denom),
+        referrer,
+        referral_reward,
+        refundable_until: env.block.time.plus_seconds(refund_period),
+    }))
+}
+
+/// `referrer` of a mint for `minter_addr`, which can't be the minter or the payer
+fn validate_referrer(
+    deps: Deps,
+    info: &MessageInfo,
+    minter_addr: &Addr,
+    referrer: Option<String>,
+) -> Result<Option<Addr>, ContractError> {
+    let referrer = maybe_addr(deps.api, referrer)?;
+
+    if let Some(referrer) = &referrer {
+        if referrer == minter_addr || *referrer == info.sender {
+            return Err(ContractError::InvalidReferrer {});
+        }
+    }
+
+    Ok(referrer)
+}
+
+fn validate_discount_tiers(tiers: &[DiscountTier]) -> Result<(), ContractError> {
+    let mut last_min_quantity = 0u32;
+
+    for tier in tiers {
+        if tier.min_quantity <= last_min_quantity || tier.discount_bps > MAX_BPS {
+            return Err(ContractError::InvalidBulkDiscounts {});
+        }
+
+        last_min_quantity = tier.min_quantity;
+    }
+
+    Ok(())
+}
+
+fn validate_dutch_auction(auction: &DutchAuction) -> Result<(), ContractError> {
+    if auction.start_price <= auction.floor_price
+        || auction.step.is_zero()
+        || auction.decay_interval == 0
+    {
+        return Err(ContractError::InvalidDutchAuction {});
+    }
+
+    Ok(())
+}
+
+fn validate_provenance_hash(hash: String) -> Result<String, ContractError> {
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ContractError::InvalidProvenanceHash {});
+    }
+
+    Ok(hash.to_lowercase())
+}
+
+fn validate_royalties(
+    deps: Deps,
+    royalties: Vec<RoyaltyInfoMsg>,
+    is_mint_royalties: bool,
+) -> Result<Vec<RoyaltyInfo>, ContractError> {
+    let mut royalty_infos: Vec<RoyaltyInfo> = vec![];
+    let mut running_bps: u32 = 0;
+    let mut is_primary_ctr: u32 = 0;
+
+    for royalty_info in royalties {
+        running_bps += royalty_info.bps;
+
+        if royalty_info.is_primary {
+            is_primary_ctr += 1;
+        }
+
+        royalty_infos.push(RoyaltyInfo {
+            addr: deps.api.addr_validate(&royalty_info.address)?,
+            bps: royalty_info.bps,
+            is_primary: royalty_info.is_primary,
+        });
+    }
+
+    if is_mint_royalties {
+        if running_bps != MAX_BPS {
+            return Err(ContractError::InvalidBPS {
+                running: running_bps,
+                max: MAX_BPS,
+            });
+        }
+
+        if is_primary_ctr != 1 {
+            return Err(ContractError::NoRoyalPrimaryAddress {});
+        }
+    } else {
+        if running_bps > MAX_BPS_FOR_SECONDARY {
+            return Err(ContractError::InvalidBPS {
+                running: running_bps,
+                max: MAX_BPS_FOR_SECONDARY,
+            });
+        }
+
+        if is_primary_ctr > 1 {
+            return Err(ContractError::NoRoyalPrimaryAddress {});
+        }
+    }
+
+    Ok(royalty_infos)
+}
+
+fn calculate_royalty_amount(
+    mint_price: Uint128,
+    bps: u32,
+    remaining_royalty_amount: Uint128,
+) -> Uint128 {
+    // bps should have been verified earlier
+    // for mints, total should not be more than MAX_BPS (10000) -- representing 100.00%
+    // for secondary, up to `MAX_BPS_FOR_SECONDARY` (5000), but ideally a portion will go to the original owner of the cw721
+    let mut amt: Uint128 = mint_price * Uint128::from(bps) / Uint128::from(MAX_BPS);
+
+    if amt >= remaining_royalty_amount {
+        amt = remaining_royalty_amount;
+    }
+
+    amt
+}
+
+/// base shuffle logic drawn from stargaze's minter
+/// Fisher-Yates over a collection's swap table, `swaps` steps down from `from_position`.
+/// returns the position the next page starts from, 0 once the collection is done
+pub fn shuffle_token_ids(
+    storage: &mut dyn Storage,
+    env: &Env,
+    sender: Addr,
+    collection_id: u64,
+    from_position: u32,
+    swaps: u32,
+) -> Result<u32, ContractError> {
+    let entropy = get_draw_entropy(storage)?;
+
+    let tx_index = if let Some(tx) = &env.transaction {
+        tx.index
+    } else {
+        0
+    };
+
+    let seed = format!(
+        "{}{}{}{}{}",
+        sender,
+        env.block.height + 69 + collection_id,
+        from_position as usize + 69 + collection_id as usize,
+        tx_index,
+        entropy
+    );
+    let mut rng = rng_from_seed(seed.as_bytes());
+
+    let to_position = from_position.saturating_sub(swaps);
+
+    record_draw(
+        storage,
+        env,
+        DrawKind::Shuffle {
+            seed,
+            collection_id,
+            from_position,
+            to_position,
+        },
+    )?;
+
+    for position in ((to_position + 1)..=from_position).rev() {
+        let swap_position = draw_uniform_index(&mut rng, position + 1);
+
+        if swap_position != position {
+            let token_id = shuffled_token_id_at(storage, collection_id, position)?;
+            let swap_token_id = shuffled_token_id_at(storage, collection_id, swap_position)?;
+
+            set_shuffled_token_id(storage, collection_id, position, swap_token_id)?;
+            set_shuffled_token_id(storage, collection_id, swap_position, token_id)?;
+        }
+    }
+
+    Ok(to_position)
+}
+
+/// base shuffle logic drawn from stargaze's minter
+/// `nonce` tells apart the draws of a multi-token mint, which would otherwise share
+/// a seed. the first draw uses 0 and keeps the single mint seed
+fn randomize_and_draw_mint(
+    storage: &mut dyn Storage,
+    env: &Env,
+    sender: Addr,
+    supply: Option<u32>,
+    nonce: u32,
+) -> Result<(u64, u32), ContractError> {
+    let entropy = get_mint_draw_entropy(storage, nonce)?;
+
+    // usable collection_ids
+    let available_collections = mintable_collections(storage)?;
+    let collection_weights = collection_draw_weights(storage, &available_collections)?;
+    let available_collections_ids: Vec<u64> = available_collections
+        .into_iter()
+        .map(|(collection_id, _)| collection_id)
+        .collect();
+
+    // a position out of the collections, or a ticket out of their weights
+    let collection_draw_limit: u32 = match &collection_weights {
+        Some(weights) => weights.iter().sum(),
+        None => available_collections_ids.len() as u32,
+    };
+
+    // grab a collection id
+    let collection_index_draw: u32 =
+        randomize_and_draw_index(env, sender.clone(), 69u64, collection_draw_limit, &entropy)?;
+
+    let collection_id: u64 = match &collection_weights {
+        Some(weights) => available_collections_ids[weighted_index(weights, collection_index_draw)],
+        None => available_collections_ids[collection_index_draw as usize],
+    };
+
+    // retrieve supply of collection
+    let collection_current_token_supply: u32 =
+        supply.unwrap_or(COLLECTION_CURRENT_TOKEN_SUPPLY.load(storage, collection_id)?);
+
+    // grab a collection id
+    let index: u32 = randomize_and_draw_index(
+        env,
+        sender.clone(),
+        collection_id,
+        collection_current_token_supply,
+        &entropy,
+    )?;
+
+    record_draw(
+        storage,
+        env,
+        DrawKind::Mint {
+            collection_seed: draw_seed(env, &sender, 69u64, collection_draw_limit, &entropy),
+            available_collection_ids: available_collections_ids,
+            collection_weights,
+            collection_id,
+            index_seed: draw_seed(
+                env,
+                &sender,
+                collection_id,
+                collection_current_token_supply,
+                &entropy,
+            ),
+            supply: collection_current_token_supply,
+            index,
+        },
+    )?;
+
+    Ok((collection_id, index))
+}
+
+/// draws a position out of `collection_id` for a mint that picked its collection
+fn randomize_and_draw_collection_mint(
+    storage: &mut dyn Storage,
+    env: &Env,
+    sender: Addr,
+    collection_id: u64,
+    nonce: u32,
+) -> Result<u32, ContractError> {
+    let entropy = get_mint_draw_entropy(storage, nonce)?;
+    let supply = COLLECTION_CURRENT_TOKEN_SUPPLY.load(storage, collection_id)?;
+
+    let index = randomize_and_draw_index(env, sender.clone(), collection_id, supply, &entropy)?;
+
+    record_draw(
+        storage,
+        env,
+        DrawKind::CollectionMint {
+            seed: draw_seed(env, &sender, collection_id, supply, &entropy),
+            collection_id,
+            supply,
+            index,
+        },
+    )?;
+
+    Ok(index)
+}
+
+/// collections a random mint can land in: tokens left and minting not disabled
+fn mintable_collections(storage: &dyn Storage) -> StdResult<Vec<(u64, u32)>> {
+    let mut collections: Vec<(u64, u32)> = vec![];
+
+    for item in COLLECTION_CURRENT_TOKEN_SUPPLY.range(storage, None, None, Order::Ascending) {
+        let (collection_id, supply) = item?;
+
+        if supply > 0
+            && CW721_COLLECTION_INFO
+                .load(storage, collection_id)?
+                .mint_enabled
+                != Some(false)
+        {
+            collections.push((collection_id, supply));
+        }
+    }
+
+    Ok(collections)
+}
+
+/// collections a bundle mints one token out of. ones with minting disabled are left out
+fn bundle_collections(storage: &dyn Storage) -> StdResult<Vec<(u64, Addr)>> {
+    let mut collections: Vec<(u64, Addr)> = vec![];
+
+    for item in CW721_ADDRS.range(storage, None, None, Order::Ascending) {
+        let (collection_id, addr) = item?;
+
+        if CW721_COLLECTION_INFO
+            .load(storage, collection_id)?
+            .mint_enabled
+            != Some(false)
+        {
+            collections.push((collection_id, addr));
+        }
+    }
+
+    Ok(collections)
+}
+
+/// weights of `collections` for the random collection draw. `None` when every
+/// collection is equally likely
+fn collection_draw_weights(
+    storage: &dyn Storage,
+    collections: &[(u64, u32)],
+) -> StdResult<Option<Vec<u32>>> {
+    if CONFIG.load(storage)?.collection_selection != Some(CollectionSelection::Weighted) {
+        return Ok(None);
+    }
+
+    collections
+        .iter()
+        .map(|&(collection_id, supply)| {
+            Ok(CW721_COLLECTION_INFO
+                .load(storage, collection_id)?
+                .weight
+                .unwrap_or(supply))
+        })
+        .collect::<StdResult<Vec<u32>>>()
+        .map(Some)
+}
+
+/// position in `weights` that `ticket`, drawn out of the sum of `weights`, falls into
+pub fn weighted_index(weights: &[u32], ticket: u32) -> usize {
+    let mut upper_bound: u32 = 0;
+
+    for (i, weight) in weights.iter().enumerate() {
+        upper_bound += weight;
+
+        if ticket < upper_bound {
+            return i;
+        }
+    }
+
+    weights.len() - 1
+}
+
+/// base shuffle logic drawn from stargaze's minter
+/// draws an index in `0..limit` with equal probability across the whole range
+/// `entropy` is the revealed commit-reveal secret, or empty when none was committed
+pub fn randomize_and_draw_index(
+    env: &Env,
+    sender: Addr,
+    collection_id: u64,
+    limit: u32,
+    entropy: &str,
+) -> Result<u32, ContractError> {
+    if limit == 0 {
+        return Err(ContractError::MintCompleted {});
+    }
+
+    let seed = draw_seed(env, &sender, collection_id, limit, entropy);
+
+    Ok(draw_index_from_seed(seed.as_bytes(), limit))
+}
+
+/// seed string hashed by `randomize_and_draw_index`
+fn draw_seed(env: &Env, sender: &Addr, collection_id: u64, limit: u32, entropy: &str) -> String {
+    let tx_index = if let Some(tx) = &env.transaction {
+        tx.index
+    } else {
+        0
+    };
+
+    format!(
+        "{}{}{}{}{}",
+        sender,
+        env.block.height + collection_id,
+        limit + collection_id as u32,
+        tx_index,
+        entropy
+    )
+}
+
+/// re-runs a single draw from its recorded seed
+pub fn draw_index_from_seed(seed: &[u8], limit: u32) -> u32 {
+    draw_uniform_index(&mut rng_from_seed(seed), limit)
+}
+
+fn rng_from_seed(seed: &[u8]) -> Xoshiro128PlusPlus {
+    let sha256 = Sha256::digest(seed);
+    // Cut first 16 bytes from 32 byte value
+    let randomness: [u8; 16] = sha256.to_vec()[0..16].try_into().unwrap();
+
+    Xoshiro128PlusPlus::from_seed(randomness)
+}
+
+fn record_draw(storage: &mut dyn Storage, env: &Env, kind: DrawKind) -> StdResult<()> {
+    let draw_id = (DRAW_COUNTER.may_load(storage)?).unwrap_or(0) + 1;
+    DRAW_COUNTER.save(storage, &draw_id)?;
+
+    DRAW_HISTORY.save(
+        storage,
+        draw_id,
+        &DrawRecord {
+            height: env.block.height,
+            kind,
+        },
+    )
+}
+
+/// id of the next job requested from the randomness proxy
+fn next_mint_job_id(storage: &mut dyn Storage) -> StdResult<String> {
+    let job_number = (MINT_JOB_COUNTER.may_load(storage)?).unwrap_or(0) + 1;
+    MINT_JOB_COUNTER.save(storage, &job_number)?;
+
+    Ok(format!("mint-{}", job_number))
+}
+
+/// pays out a mint once its tokens are drawn. promised mints are given away
+/// rather than sold, so they don't count towards the `minimum_raise`
+#[allow(clippy::too_many_arguments)]
+fn settle_mint_payment(
+    deps: DepsMut,
+    env: &Env,
+    res: Response,
+    minter_addr: &Addr,
+    mint_type: &MintType,
+    quantity: u32,
+    paid: Uint128,
+    denom: String,
+    referrer: Option<&Addr>,
+) -> Result<Response, ContractError> {
+    if *mint_type != MintType::PromisedMint {
+        record_raise_contribution(deps.storage, env, minter_addr, quantity, paid, &denom)?;
+    }
+
+    let disburse_amount =
+        hold_auction_rebate(deps.storage, minter_addr, mint_type, paid, quantity)?;
+    disburse_or_escrow_funds(
+        deps,
+        res,
+        disburse_amount,
+        denom,
+        referrer.map(|referrer| (referrer, quantity)),
+    )
+}
+
+/// undoes a mint that was paid for but never drawn. the reserved tokens go back
+/// to the supply and public mints are given back to the address
+fn release_unpaid_mint(
+    storage: &mut dyn Storage,
+    minter_addr: &Addr,
+    mint_type: &MintType,
+    quantity: u32,
+) -> StdResult<()> {
+    for _ in 0..quantity {
+        release_pending_mint(storage)?;
+    }
+
+    if *mint_type == MintType::Public {
+        let current_mint_count =
+            (ADDRESS_MINT_TRACKER.may_load(storage, minter_addr.clone())?).unwrap_or(0);
+
+        ADDRESS_MINT_TRACKER.save(
+            storage,
+            minter_addr.clone(),
+            &current_mint_count.saturating_sub(quantity),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// holds back one token of supply for a mint that is paid but not yet drawn
+fn reserve_pending_mint(storage: &mut dyn Storage) -> StdResult<()> {
+    let pending_mint_count = (PENDING_MINT_COUNT.may_load(storage)?).unwrap_or(0);
+    PENDING_MINT_COUNT.save(storage, &(pending_mint_count + 1))?;
+
+    record_auction_clearing_price(storage)
+}
+
+fn release_pending_mint(storage: &mut dyn Storage) -> StdResult<()> {
+    let pending_mint_count = PENDING_MINT_COUNT.load(storage)?;
+    PENDING_MINT_COUNT.save(storage, &pending_mint_count.saturating_sub(1))
+}
+
+/// tokens left once pending mints are set aside
+fn mintable_supply(storage: &dyn Storage) -> StdResult<u32> {
+    Ok(CURRENT_TOKEN_SUPPLY
+        .load(storage)?
+        .saturating_sub((PENDING_MINT_COUNT.may_load(storage)?).unwrap_or(0)))
+}
+
+/// errors unless `quantity` tokens are left once pending mints are set aside
+fn check_mintable_supply(storage: &dyn Storage, quantity: u32) -> Result<(), ContractError> {
+    let remaining = mintable_supply(storage)?;
+
+    if remaining == 0 {
+        return Err(ContractError::MintCompleted {});
+    }
+
+    if remaining < quantity {
+        return Err(ContractError::InsufficientTokenSupply { remaining });
+    }
+
+    Ok(())
+}
+
+/// blocks a mint ticket can wait to be settled in. `None` while mints are drawn as
+/// they are paid, which includes the time between a reveal and the next commitment
+fn mint_ticket_expiry_blocks(storage: &dyn Storage) -> StdResult<Option<u64>> {
+    let secret_hidden = matches!(
+        RANDOMNESS_COMMIT.may_load(storage)?,
+        Some(RandomnessCommit { secret: None, .. })
+    );
+
+    match DELAYED_MINT_EXPIRY_BLOCKS.may_load(storage)? {
+        Some(expiry_blocks) => Ok(Some(expiry_blocks)),
+        // mints paid against a hidden commitment or a drand round wait for it
+        None if secret_hidden || DRAND_CONFIG.may_load(storage)?.is_some() => {
+            Ok(Some(DEFAULT_MINT_TICKET_EXPIRY_BLOCKS))
+        }
+        None => Ok(None),
+    }
+}
+
+/// mints are drawn after they are paid, by the randomness proxy or as tickets
+fn mints_drawn_later(storage: &dyn Storage) -> StdResult<bool> {
+    Ok(RANDOMNESS_PROXY.may_load(storage)?.is_some()
+        || mint_ticket_expiry_blocks(storage)?.is_some())
+}
+
+/// randomness a ticket paid now is drawn with, which nobody can know yet: the
+/// commitment while its secret is hidden, and a drand round not yet published. block
+/// data is known to whoever settles, so a ticket without either can't be paid. a
+/// commitment made before `committed_since` is left out
+fn hidden_randomness_sources(
+    storage: &dyn Storage,
+    env: &Env,
+    committed_since: Option<Timestamp>,
+) -> Result<(Option<String>, Option<u64>), ContractError> {
+    let drand_round = (DRAND_CONFIG.may_load(storage)?)
+        .map(|drand| drand_round_after(&drand, env.block.time) + 1);
+
+    let commitment = match RANDOMNESS_COMMIT.may_load(storage)? {
+        Some(RandomnessCommit {
+            commitment,
+            secret: None,
+            committed_at,
+        }) if committed_since.map_or(true, |since| committed_at >= since) => Some(commitment),
+        _ => None,
+    };
+
+    if commitment.is_none() && drand_round.is_none() {
+        return Err(ContractError::HiddenRandomnessUnavailable {});
+    }
+
+    Ok((commitment, drand_round))
+}
+
+/// first drand round published after `time`. tickets wait one round more, so a block
+/// time running behind the drand clock can't land on a round that is already out
+fn drand_round_after(drand: &DrandConfig, time: Timestamp) -> u64 {
+    match time.seconds().checked_sub(drand.genesis_time) {
+        Some(elapsed) => elapsed / drand.period + 2,
+        None => 1,
+    }
+}
+
+/// randomness a ticket or raffle is drawn with: the secret behind its commitment
+/// followed by its drand round, once both are out
+fn hidden_randomness(
+    storage: &dyn Storage,
+    commitment: &Option<String>,
+    drand_round: Option<u64>,
+) -> Result<Binary, ContractError> {
+    if commitment.is_none() && drand_round.is_none() {
+        return Err(ContractError::HiddenRandomnessUnavailable {});
+    }
+
+    let mut randomness: Vec<u8> = vec![];
+
+    if let Some(commitment) = commitment {
+        let secret = RANDOMNESS_SECRETS
+            .may_load(storage, commitment.clone())?
+            .ok_or(ContractError::RandomnessNotRevealed {})?;
+
+        randomness.extend_from_slice(secret.as_bytes());
+    }
+
+    if let Some(round) = drand_round {
+        let beacon = DRAND_BEACONS
+            .may_load(storage, round)?
+            .ok_or(ContractError::DrandBeaconMissing {})?;
+
+        randomness.extend_from_slice(beacon.randomness.as_slice());
+    }
+
+    Ok(Binary::from(randomness))
+}
+
+/// same draw as `randomize_and_draw_mint`, a uniform collection and then a uniform
+/// index inside it, seeded from randomness delivered after the mint was paid
+fn draw_mint_from_randomness(
+    storage: &mut dyn Storage,
+    env: &Env,
+    randomness: &Binary,
+    job_id: &str,
+) -> Result<(u64, u32), ContractError> {
+    let mut seed = randomness.to_vec();
+    seed.extend_from_slice(job_id.as_bytes());
+
+    let mut rng = rng_from_seed(&seed);
+
+    let available_collections = mintable_collections(storage)?;
+
+    if available_collections.is_empty() {
+        return Err(ContractError::MintCompleted {});
+    }
+
+    let collection_weights = collection_draw_weights(storage, &available_collections)?;
+
+    let (collection_id, supply) = match &collection_weights {
+        Some(weights) => {
+            available_collections
+                [weighted_index(weights, draw_uniform_index(&mut rng, weights.iter().sum()))]
+        }
+        None => {
+            available_collections
+                [draw_uniform_index(&mut rng, available_collections.len() as u32) as usize]
+        }
+    };
+    let index = draw_uniform_index(&mut rng, supply);
+
+    record_draw(
+        storage,
+        env,
+        DrawKind::DeferredMint {
+            job_id: job_id.to_string(),
+            randomness: randomness.clone(),
+            available_collection_ids: available_collections.iter().map(|&(id, _)| id).collect(),
+            collection_weights,
+            collection_id,
+            supply,
+            index,
+        },
+    )?;
+
+    Ok((collection_id, index))
+}
+
+/// total public price of the next `quantity` mints: the live auction price, the curve
+/// price of each token in turn, or the fixed `mint_price`. a mint that picks
+/// `collection_id` pays that collection's own `mint_price` in place of the fixed one
+pub fn quote_public_mint_price(
+    storage: &dyn Storage,
+    env: &Env,
+    config: &Config,
+    quantity: u32,
+    collection_id: Option<u64>,
+) -> StdResult<Uint128> {
+    if let Some(auction) = &config.dutch_auction {
+        let (price, _) = dutch_auction_price(auction, config.start_time, env.block.time);
+
+        return Ok(price.checked_mul(Uint128::from(quantity))?);
+    }
+
+    if let Some(curve) = &config.bonding_curve {
+        let current_token_supply = CURRENT_TOKEN_SUPPLY.load(storage)?;
+        let pending_mint_count = (PENDING_MINT_COUNT.may_load(storage)?).unwrap_or(0);
+
+        // priced token by token, so never past what is left to sell
+        let remaining = current_token_supply.saturating_sub(pending_mint_count);
+        if quantity > remaining {
+            return Err(StdError::generic_err(format!(
+                "Only {} tokens left to mint",
+                remaining
+            )));
+        }
+
+        // tokens reserved by pending mints are already sold
+        let sold = TOTAL_TOKEN_SUPPLY.load(storage)? - current_token_supply + pending_mint_count;
+
+        let mut total = Uint128::zero();
+        for i in 0..quantity {
+            total = total.checked_add(bonding_curve_price(curve, sold + i)?)?;
+        }
+
+        return Ok(total);
+    }
+
+    let mint_price = match collection_id {
+        Some(collection_id) => CW721_COLLECTION_INFO
+            .load(storage, collection_id)?
+            .mint_price
+            .unwrap_or(config.mint_price),
+        // the draw can land in any mintable collection, so it costs the priciest one.
+        // the price has to be paid before the draw, which may happen blocks later, so
+        // minters that want a cheaper collection pick it
+        None => {
+            let mut mint_price: Option<Uint128> = None;
+
+            for (collection_id, _) in mintable_collections(storage)? {
+                let collection_price = CW721_COLLECTION_INFO
+                    .load(storage, collection_id)?
+                    .mint_price
+                    .unwrap_or(config.mint_price);
+
+                mint_price = Some(cmp::max(mint_price.unwrap_or_default(), collection_price));
+            }
+
+            mint_price.unwrap_or(config.mint_price)
+        }
+    };
+
+    Ok(mint_price.checked_mul(Uint128::from(quantity))?)
+}
+
+/// the `denom_mint_prices` entry that `info` pays in. a payment in any other denom is
+/// checked against `mint_denom`
+pub fn denom_mint_price(config: &Config, info: &MessageInfo) -> Option<Coin> {
+    match info.funds.as_slice() {
+        [payment] => config
+            .denom_mint_prices
+            .iter()
+            .flatten()
+            .find(|price| price.denom == payment.denom)
+            .cloned(),
+        _ => None,
+    }
+}
+
+/// `config.cw20_payment` if `info` pays in its token, which only `Receive` can do
+fn cw20_mint_payment<'a>(config: &'a Config, info: &MessageInfo) -> Option<&'a Cw20Payment> {
+    let cw20_payment = config.cw20_payment.as_ref()?;
+
+    match info.funds.as_slice() {
+        [payment] if payment.denom == cw20_denom(&cw20_payment.token_addr) => Some(cw20_payment),
+        _ => None,
+    }
+}
+
+pub fn cw20_denom(token_addr: &Addr) -> String {
+    format!("{}{}", CW20_DENOM_PREFIX, token_addr)
+}
+
+/// sends `amount` of `denom` to `recipient`, as a cw20 `Transfer` for `cw20:` denoms
+fn transfer_msg(recipient: &Addr, amount: Uint128, denom: String) -> StdResult<CosmosMsg> {
+    match denom.strip_prefix(CW20_DENOM_PREFIX) {
+        Some(token_addr) => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: token_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        })),
+        None => Ok(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin { amount, denom }],
+        })),
+    }
+}
+
+fn query_contract_balance(deps: Deps, env: &Env, denom: &str) -> StdResult<Uint128> {
+    match denom.strip_prefix(CW20_DENOM_PREFIX) {
+        Some(token_addr) => {
+            let res: BalanceResponse = deps.querier.query_wasm_smart(
+                token_addr,
+                &Cw20QueryMsg::Balance {
+                    address: env.contract.address.to_string(),
+                },
+            )?;
+
+            Ok(res.balance)
+        }
+        None => Ok(deps
+            .querier
+            .query_balance(&env.contract.address, denom)?
+            .amount),
+    }
+}
+
+/// `reference_price` in `denom`, at the rate of the `oracle_pricing` oracle
+pub fn oracle_mint_price(
+    deps: Deps,
+    oracle_pricing: &OraclePricing,
+    denom: &str,
+    reference_price: Uint128,
+) -> StdResult<Uint128> {
+    let res: OraclePriceResponse = deps.querier.query_wasm_smart(
+        &oracle_pricing.oracle_addr,
+        &OracleQueryMsg::Price {
+            denom: denom.to_string(),
+        },
+    )?;
 
-        CURRENT_TOKEN_SUPPLY.save(deps.storage, &new_current_token_supply)?;
-    } else {
-        return Err(ContractError::UnableToMint {});
+    if res.rate.is_zero() {
+        return Err(StdError::generic_err(format!(
+            "oracle has no rate for {}",
+            denom
+        )));
     }
 
-    Ok(msg)
+    Ok(reference_price.multiply_ratio(Decimal::one().atomics(), res.rate.atomics()))
 }
 
-fn validate_collection_info(
-    _deps: Deps,
-    msgs: Vec<CollectionInfoMsg>,
-) -> Result<ValidateCollectionInfoResponse, ContractError> {
-    let mut collection_infos: Vec<CollectionInfo> = vec![];
-
-    let mut total_token_supply: u32 = 0;
-    let mut id: u64 = INSTANTIATE_TOKEN_REPLY_ID;
-    for msg in msgs {
-        id += 1;
-        validate_uri(msg.base_token_uri.clone())?;
+/// what an oracle priced mint of `price` charges for `payment`, which is always
+/// `price`. a payment has to cover it and can be up to `slippage_bps` over it, and
+/// the excess is left to be refunded
+fn charge_oracle_payment(
+    oracle_pricing: &OraclePricing,
+    price: Uint128,
+    payment: Uint128,
+    denom: &str,
+) -> Result<Uint128, ContractError> {
+    let max_payment =
+        price.saturating_add(price.multiply_ratio(oracle_pricing.slippage_bps, MAX_BPS));
+
+    if payment < price || payment > max_payment {
+        return Err(ContractError::IncorrectPaymentAmount {
+            token: denom.to_string(),
+            amt: price,
+        });
+    }
 
-        let secondary_metadata_uri: Option<String> = match msg.secondary_metadata_uri {
-            Some(uri) => {
-                validate_uri(uri.clone())?;
-                Some(uri)
-            }
-            None => None,
-        };
+    Ok(price)
+}
 
-        total_token_supply += msg.token_supply;
+/// `mint_price` of `quantity` tokens after the discount of `code`, which is used up
+/// by that many tokens. a code is registered for the address paying with it, so one
+/// copied out of someone else's pending transaction doesn't match
+fn redeem_mint_code(
+    storage: &mut dyn Storage,
+    sender: &Addr,
+    mint_type: &MintType,
+    mint_denom: &str,
+    code: &str,
+    quantity: u32,
+    mint_price: Uint128,
+) -> Result<Uint128, ContractError> {
+    let config = CONFIG.load(storage)?;
+
+    // auction rebates and oracle refunds are worked out from the undiscounted price
+    let accepted = match mint_type {
+        MintType::Whitelist => true,
+        MintType::Public => config.dutch_auction.is_none() && config.oracle_pricing.is_none(),
+        _ => false,
+    };
 
-        collection_infos.push(CollectionInfo {
-            id,
-            token_supply: msg.token_supply,
-            name: msg.name,
-            symbol: msg.symbol,
-            base_token_uri: msg.base_token_uri,
-            secondary_metadata_uri,
-        })
+    if !accepted || mint_denom != config.mint_denom {
+        return Err(ContractError::MintCodeNotAccepted {});
     }
 
-    if !(1..=MAX_TOKEN_SUPPLY).contains(&total_token_supply) {
-        return Err(ContractError::InvalidMaxTokenSupply {
-            max: MAX_TOKEN_SUPPLY,
-            input: total_token_supply,
-        });
+    let hash = format!("{:x}", Sha256::digest(format!("{}:{}", sender, code)));
+    let mut mint_code = MINT_CODES
+        .may_load(storage, hash.clone())?
+        .filter(|mint_code| mint_code.uses_remaining >= quantity)
+        .ok_or(ContractError::InvalidMintCode {})?;
+
+    mint_code.uses_remaining -= quantity;
+
+    if mint_code.uses_remaining == 0 {
+        MINT_CODES.remove(storage, hash);
+    } else {
+        MINT_CODES.save(storage, hash, &mint_code)?;
     }
 
-    Ok(ValidateCollectionInfoResponse {
-        collection_infos,
-        total_token_supply,
+    Ok(match mint_code.discount {
+        MintCodeDiscount::Bps(bps) => mint_price - mint_price.multiply_ratio(bps, MAX_BPS),
+        MintCodeDiscount::FixedPrice(price) => cmp::min(
+            price
+                .checked_mul(Uint128::from(quantity))
+                .map_err(StdError::from)?,
+            mint_price,
+        ),
     })
 }
 
-fn validate_shared_collection_info(
-    deps: Deps,
-    msg: SharedCollectionInfoMsg,
-) -> Result<SharedCollectionInfo, ContractError> {
-    let shared_collection_info: SharedCollectionInfo = SharedCollectionInfo {
-        mint_revenue_share: validate_royalties(deps, msg.mint_revenue_share, true)?,
-        secondary_market_royalties: validate_royalties(
-            deps,
-            msg.secondary_market_royalties,
-            false,
-        )?,
+/// `price` of `quantity` tokens less the bulk discount of the `mint_type` phase, for an
+/// address that already minted `current_mint_count` in it. returns the discounted
+/// price and the discount in bps
+pub fn apply_bulk_discount(
+    config: &Config,
+    mint_type: &MintType,
+    current_mint_count: u32,
+    quantity: u32,
+    price: Uint128,
+) -> (Uint128, u32) {
+    let tiers = match (&config.bulk_discounts, mint_type) {
+        (Some(discounts), MintType::Whitelist) => &discounts.whitelist,
+        (Some(discounts), MintType::Public) => &discounts.public,
+        _ => return (price, 0),
     };
 
-    Ok(shared_collection_info)
+    let total_mint_count = current_mint_count.saturating_add(quantity);
+    let discount_bps = tiers
+        .iter()
+        .rev()
+        .find(|tier| tier.min_quantity <= total_mint_count)
+        .map(|tier| tier.discount_bps)
+        .unwrap_or(0);
+
+    (
+        price.multiply_ratio(MAX_BPS - discount_bps, MAX_BPS),
+        discount_bps,
+    )
 }
 
-fn validate_uri(uri: String) -> Result<String, ContractError> {
-    // url is too short
-    if uri.len() < 4 {
-        return Err(ContractError::InvalidBaseTokenURI {});
+/// curve price of the token minted after `sold` others
+pub fn bonding_curve_price(curve: &BondingCurve, sold: u32) -> StdResult<Uint128> {
+    match curve {
+        BondingCurve::Linear { base_price, slope } => {
+            Ok(base_price.checked_add(slope.checked_mul(Uint128::from(sold))?)?)
+        }
+        BondingCurve::Exponential {
+            base_price,
+            growth_bps,
+        } => {
+            let growth =
+                (Decimal::one() + Decimal::from_ratio(*growth_bps, MAX_BPS)).checked_pow(sold)?;
+            let price =
+                base_price.full_mul(growth.atomics()) / Uint256::from(Decimal::one().atomics());
+
+            Ok(Uint128::try_from(price)
+                .map_err(|_| OverflowError::new(OverflowOperation::Mul, base_price, growth))?)
+        }
     }
-
-    // url crate was causing wasm
-
-    Ok(uri)
 }
 
-fn validate_royalties(
-    deps: Deps,
-    royalties: Vec<RoyaltyInfoMsg>,
-    is_mint_royalties: bool,
-) -> Result<Vec<RoyaltyInfo>, ContractError> {
-    let mut royalty_infos: Vec<RoyaltyInfo> = vec![];
-    let mut running_bps: u32 = 0;
-    let mut is_primary_ctr: u32 = 0;
+/// current auction price, and the time and price of the next drop until the floor is reached
+pub fn dutch_auction_price(
+    auction: &DutchAuction,
+    start_time: Timestamp,
+    now: Timestamp,
+) -> (Uint128, Option<(Timestamp, Uint128)>) {
+    let drops = now.seconds().saturating_sub(start_time.seconds()) / auction.decay_interval;
+    let price = cmp::max(
+        auction
+            .start_price
+            .saturating_sub(auction.step.saturating_mul(Uint128::from(drops))),
+        auction.floor_price,
+    );
 
-    for royalty_info in royalties {
-        running_bps += royalty_info.bps;
+    if price == auction.floor_price {
+        return (price, None);
+    }
 
-        if royalty_info.is_primary {
-            is_primary_ctr += 1;
-        }
+    let next_drop_time = start_time.plus_seconds((drops + 1) * auction.decay_interval);
+    let next_price = cmp::max(price.saturating_sub(auction.step), auction.floor_price);
 
-        royalty_infos.push(RoyaltyInfo {
-            addr: deps.api.addr_validate(&royalty_info.address)?,
-            bps: royalty_info.bps,
-            is_primary: royalty_info.is_primary,
-        });
+    (price, Some((next_drop_time, next_price)))
+}
+
+/// the price rebates settle against. once the public mint sells out or ends it is the
+/// last price paid, and once the price reaches the floor it is the floor
+pub fn auction_clearing_price(
+    storage: &dyn Storage,
+    env: &Env,
+    config: &Config,
+    auction: &DutchAuction,
+) -> StdResult<Option<Uint128>> {
+    if let Some(clearing_price) = AUCTION_CLEARING_PRICE.may_load(storage)? {
+        return Ok(Some(clearing_price));
     }
 
-    if is_mint_royalties {
-        if running_bps != MAX_BPS {
-            return Err(ContractError::InvalidBPS {
-                running: running_bps,
-                max: MAX_BPS,
-            });
-        }
+    let ended = matches!(config.end_time, Some(end_time) if end_time <= env.block.time);
 
-        if is_primary_ctr != 1 {
-            return Err(ContractError::NoRoyalPrimaryAddress {});
-        }
-    } else {
-        if running_bps > MAX_BPS_FOR_SECONDARY {
-            return Err(ContractError::InvalidBPS {
-                running: running_bps,
-                max: MAX_BPS_FOR_SECONDARY,
-            });
-        }
+    if ended {
+        return Ok(Some(
+            (AUCTION_LAST_PRICE.may_load(storage)?).unwrap_or(auction.floor_price),
+        ));
+    }
 
-        if is_primary_ctr > 1 {
-            return Err(ContractError::NoRoyalPrimaryAddress {});
-        }
+    let (price, _) = dutch_auction_price(auction, config.start_time, env.block.time);
+
+    if price == auction.floor_price {
+        return Ok(Some(price));
     }
 
-    Ok(royalty_infos)
+    Ok(None)
 }
 
-fn calculate_royalty_amount(
-    mint_price: Uint128,
-    bps: u32,
-    remaining_royalty_amount: Uint128,
-) -> Uint128 {
-    // bps should have been verified earlier
-    // for mints, total should not be more than MAX_BPS (10000) -- representing 100.00%
-    // for secondary, up to `MAX_BPS_FOR_SECONDARY` (5000), but ideally a portion will go to the original owner of the cw721
-    let mut amt: Uint128 = mint_price * Uint128::from(bps) / Uint128::from(MAX_BPS);
+/// stores the clearing price the first time the auction sells out, pending mints
+/// included, so refunded or returned tokens can't reopen it
+fn record_auction_clearing_price(storage: &mut dyn Storage) -> StdResult<()> {
+    let auction = match CONFIG.load(storage)?.dutch_auction {
+        Some(auction) => auction,
+        None => return Ok(()),
+    };
 
-    if amt >= remaining_royalty_amount {
-        amt = remaining_royalty_amount;
+    if AUCTION_CLEARING_PRICE.may_load(storage)?.is_some() {
+        return Ok(());
     }
 
-    amt
+    let sold_out =
+        CURRENT_TOKEN_SUPPLY.load(storage)? <= (PENDING_MINT_COUNT.may_load(storage)?).unwrap_or(0);
+
+    if sold_out {
+        AUCTION_CLEARING_PRICE.save(
+            storage,
+            &(AUCTION_LAST_PRICE.may_load(storage)?).unwrap_or(auction.floor_price),
+        )?;
+    }
+
+    Ok(())
 }
 
-/// base shuffle logic drawn from stargaze's minter
-fn shuffle_token_ids(
-    env: &Env,
-    sender: Addr,
-    mut tokens: Vec<u32>,
-    seeded_randomness: u64,
-) -> Result<Vec<u32>, ContractError> {
-    let tx_index = if let Some(tx) = &env.transaction {
-        tx.index
-    } else {
-        0
-    };
+/// with rebates on, only the floor price of a public mint goes to the revenue split and
+/// the rest is held against the buyer until `SettleAuctionRebate`. returns the amount
+/// to disburse now
+fn hold_auction_rebate(
+    storage: &mut dyn Storage,
+    minter_addr: &Addr,
+    mint_type: &MintType,
+    mint_price: Uint128,
+    quantity: u32,
+) -> StdResult<Uint128> {
+    let config = CONFIG.load(storage)?;
+
+    match config.dutch_auction {
+        Some(auction) if auction.rebate && *mint_type == MintType::Public => {
+            let mut purchase = AUCTION_PURCHASES
+                .may_load(storage, minter_addr.clone())?
+                .unwrap_or(AuctionPurchase {
+                    count: 0,
+                    paid: Uint128::zero(),
+                });
+
+            purchase.count += quantity;
+            purchase.paid += mint_price;
+
+            AUCTION_PURCHASES.save(storage, minter_addr.clone(), &purchase)?;
+
+            Ok(cmp::min(
+                mint_price,
+                auction.floor_price.checked_mul(Uint128::from(quantity))?,
+            ))
+        }
+        _ => Ok(mint_price),
+    }
+}
 
-    let sha256 = Sha256::digest(
-        format!(
-            "{}{}{}{}",
-            sender,
-            env.block.height + 69 + seeded_randomness,
-            tokens.len() + 69 + seeded_randomness as usize,
-            tx_index
-        )
-        .into_bytes(),
-    );
-    // Cut first 16 bytes from 32 byte value
-    let randomness: [u8; 16] = sha256.to_vec()[0..16].try_into().unwrap();
-    let mut rng = Xoshiro128PlusPlus::from_seed(randomness);
-    let mut shuffler = FisherYates::default();
+/// `get_draw_entropy` for one of the draws of a multi-token mint. `nonce` tells the
+/// draws apart, and the first draw uses 0 and keeps the single mint seed
+fn get_mint_draw_entropy(storage: &dyn Storage, nonce: u32) -> Result<String, ContractError> {
+    let mut entropy = get_draw_entropy(storage)?;
 
-    shuffler
-        .shuffle(&mut tokens, &mut rng)
-        .map_err(StdError::generic_err)?;
+    if nonce > 0 {
+        entropy.push_str(&format!("#{}", nonce));
+    }
 
-    Ok(tokens)
+    Ok(entropy)
 }
 
-/// base shuffle logic drawn from stargaze's minter
-fn randomize_and_draw_mint(
-    deps: Deps,
-    env: &Env,
-    sender: Addr,
-    supply: Option<u32>,
-) -> Result<(u64, u32), ContractError> {
-    // get collections
-    let collection_supplies: Vec<(u64, u32)> = COLLECTION_CURRENT_TOKEN_SUPPLY
-        .range(deps.storage, None, None, Order::Ascending)
-        .map(|item| {
-            let (collection_id, supply) = item?;
-            Ok((collection_id, supply))
-        })
-        .collect::<StdResult<Vec<(u64, u32)>>>()
-        .unwrap();
+/// a mint that picks `collection_id` needs the collection to be mintable with
+/// `quantity` tokens left. picked mints are drawn straight away, so they can't be
+/// combined with the randomness proxy or tickets
+fn check_collection_mint(
+    storage: &dyn Storage,
+    collection_id: u64,
+    quantity: u32,
+) -> Result<(), ContractError> {
+    let coll_info = CW721_COLLECTION_INFO
+        .may_load(storage, collection_id)?
+        .ok_or(ContractError::CollectionNotFound { collection_id })?;
+
+    if coll_info.mint_enabled == Some(false) {
+        return Err(ContractError::CollectionMintDisabled { collection_id });
+    }
 
-    // filter down to usable collection_ids
-    let available_collections_ids: Vec<u64> = collection_supplies
-        .into_iter()
-        .filter(|&(_, v)| v > 0)
-        .map(|item| Ok(item.0))
-        .collect::<StdResult<Vec<u64>>>()
-        .unwrap();
+    if mints_drawn_later(storage)? {
+        return Err(ContractError::CollectionPickUnavailable {});
+    }
 
-    // grab a collection id
-    let collection_index_draw: u32 = randomize_and_draw_index(
-        env,
-        sender.clone(),
-        69u64,
-        available_collections_ids.len() as u32,
-    )?;
+    let remaining = COLLECTION_CURRENT_TOKEN_SUPPLY.load(storage, collection_id)?;
 
-    let collection_id: u64 = available_collections_ids[collection_index_draw as usize];
+    if remaining == 0 {
+        return Err(ContractError::MintCompleted {});
+    }
 
-    // retrieve supply of collection
-    let collection_current_token_supply: u32 =
-        supply.unwrap_or(COLLECTION_CURRENT_TOKEN_SUPPLY.load(deps.storage, collection_id)?);
+    if remaining < quantity {
+        return Err(ContractError::InsufficientTokenSupply { remaining });
+    }
 
-    // grab a collection id
-    let index: u32 =
-        randomize_and_draw_index(env, sender, collection_id, collection_current_token_supply)?;
+    Ok(())
+}
 
-    Ok((collection_id, index))
+/// errors while the token order is being rewritten, by a shuffle or by a 0.1.x order
+/// still being moved into the swap table
+fn check_shuffle_not_in_progress(storage: &dyn Storage) -> Result<(), ContractError> {
+    if SHUFFLE_PROGRESS.may_load(storage)?.is_some() {
+        return Err(ContractError::ShuffleInProgress {});
+    }
+
+    if TOKEN_ORDER_MIGRATION.may_load(storage)?.is_some() {
+        return Err(ContractError::TokenOrderMigrationInProgress {});
+    }
+
+    Ok(())
 }
 
-/// base shuffle logic drawn from stargaze's minter
-fn randomize_and_draw_index(
-    env: &Env,
-    sender: Addr,
-    collection_id: u64,
-    limit: u32,
-) -> Result<u32, ContractError> {
-    let tx_index = if let Some(tx) = &env.transaction {
-        tx.index
-    } else {
-        0
+/// extra entropy mixed into draws made as they are paid and shuffles: the revealed
+/// commit-reveal secret followed by the latest submitted drand round. with neither
+/// mode configured this is empty, which keeps the seed identical to the block based one
+fn get_draw_entropy(storage: &dyn Storage) -> Result<String, ContractError> {
+    let mut entropy = match RANDOMNESS_COMMIT.may_load(storage)? {
+        None => String::new(),
+        Some(RandomnessCommit {
+            secret: Some(secret),
+            ..
+        }) => secret,
+        Some(_) => return Err(ContractError::RandomnessNotRevealed {}),
     };
 
-    let sha256 = Sha256::digest(
-        format!(
-            "{}{}{}{}",
-            sender,
-            env.block.height + collection_id,
-            limit + collection_id as u32,
-            tx_index
-        )
-        .into_bytes(),
-    );
+    if DRAND_CONFIG.may_load(storage)?.is_some() {
+        let (_, beacon) = DRAND_BEACONS
+            .range(storage, None, None, Order::Descending)
+            .next()
+            .transpose()?
+            .ok_or(ContractError::DrandBeaconMissing {})?;
 
-    // Cut first 16 bytes from 32 byte value
-    let randomness: [u8; 16] = sha256.to_vec()[0..16].try_into().unwrap();
-    let mut rng = Xoshiro128PlusPlus::from_seed(randomness);
+        entropy.push_str(&beacon.randomness.to_base64());
+    }
 
-    let r = rng.next_u32();
+    Ok(entropy)
+}
 
-    let mut rem = 50;
-    if rem > limit {
-        rem = limit;
-    }
-    let n = r % rem;
+/// Rejection sampling over `u32`. `r % limit` on its own favors the low indices
+/// whenever `limit` does not divide 2^32, so values from the incomplete window at
+/// the top of the range are thrown away and redrawn
+fn draw_uniform_index(rng: &mut Xoshiro128PlusPlus, limit: u32) -> u32 {
+    // largest multiple of `limit` that fits in a u32
+    let zone: u32 = (u32::MAX / limit) * limit;
 
-    // pull either front or go near back of vec
-    let mut index: u32 = match r % 2 {
-        1 => n,
-        _ => limit - n,
-    };
+    loop {
+        let r = rng.next_u32();
 
-    // push index_id down to a 0 based index for the array
-    // bound should be 0..(vec_length - 1)
-    if index >= limit {
-        index = limit - 1;
-    } else if index > 0 {
-        index -= 1;
+        if r < zone {
+            return r % limit;
+        }
     }
-
-    Ok(index)
 }
 
 // #endregion
@@ -1574,6 +5036,7 @@ fn check_can_update(deps: Deps, env: &Env, info: &MessageInfo) -> Result<bool, C
 fn check_whitelist(
     deps: Deps,
     info: &MessageInfo,
+    quantity: u32,
 ) -> Result<MintParametersResponse, ContractError> {
     if let Some(whitelist_addr) = WHITELIST_ADDR.may_load(deps.storage)? {
         let wl_config: CheckWhitelistResponse = deps.querier.query_wasm_smart(
@@ -1593,7 +5056,7 @@ fn check_whitelist(
                 return Err(ContractError::WhitelistNotInProgress {});
             }
 
-            if wl_config.current_mint_count >= wl_config.max_per_address_mint {
+            if wl_config.current_mint_count + quantity > wl_config.max_per_address_mint {
                 return Err(ContractError::WhitelistMaxMintReached(
                     wl_config.max_per_address_mint,
                 ));
@@ -1603,6 +5066,7 @@ fn check_whitelist(
                 can_mint: true,
                 mint_price: Some(wl_config.mint_price),
                 remaining_token_ids: vec![],
+                current_mint_count: wl_config.current_mint_count,
             })
         } else {
             Err(ContractError::WhitelistClosed {})
@@ -1612,11 +5076,14 @@ fn check_whitelist(
     }
 }
 
+/// `quantity` is the number of promised mints asked for. promised tokens are always
+/// claimed all at once
 fn check_airdrop_promises(
     deps: Deps,
     info: &MessageInfo,
     mint_type: MintType,
     minter_addr: Addr,
+    quantity: u32,
 ) -> Result<MintParametersResponse, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
@@ -1632,6 +5099,7 @@ fn check_airdrop_promises(
             can_mint: false,
             mint_price: None,
             remaining_token_ids: vec![],
+            current_mint_count: 0,
         };
 
         match mint_type {
@@ -1653,8 +5121,11 @@ fn check_airdrop_promises(
                         return Err(ContractError::BeforePremintStarttime {});
                     }
 
-                    // if an address' claimed mint count >= promised mint count, then kick them out
-                    if promised_mints.claimed_mint_count >= promised_mints.promised_mint_count {
+                    // if an address' claimed mint count would pass its promised mint count,
+                    // then kick them out
+                    if promised_mints.claimed_mint_count + quantity
+                        > promised_mints.promised_mint_count
+                    {
                         return Err(ContractError::AllPromisesFulfilled {});
                     } else {
                         mint_params.can_mint = true;
@@ -1710,7 +5181,12 @@ fn check_airdrop_promises(
     }
 }
 
-fn check_public_mint(deps: Deps, env: Env, info: &MessageInfo) -> Result<bool, ContractError> {
+fn check_public_mint(
+    deps: Deps,
+    env: Env,
+    info: &MessageInfo,
+    quantity: u32,
+) -> Result<bool, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let mut can_mint: bool = false;
 
@@ -1738,7 +5214,7 @@ fn check_public_mint(deps: Deps, env: Env, info: &MessageInfo) -> Result<bool, C
     let current_mint_count =
         (ADDRESS_MINT_TRACKER.may_load(deps.storage, info.sender.clone())?).unwrap_or(0);
 
-    if current_mint_count >= config.max_per_address_mint {
+    if current_mint_count + quantity > config.max_per_address_mint {
         return Err(ContractError::PublicMaxMintReached(
             config.max_per_address_mint,
         ));
@@ -1777,6 +5253,147 @@ pub fn validate_native_denom(denom: String) -> Result<bool, ContractError> {
     Ok(true)
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+
+    // only allow migrations from a previous version of this contract
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidMigrationContract {
+            stored: stored.contract,
+            expected: CONTRACT_NAME.to_string(),
+        });
+    }
+
+    let stored_version = parse_contract_version(&stored.version)?;
+    let current_version = parse_contract_version(CONTRACT_VERSION)?;
+
+    if stored_version > current_version {
+        return Err(ContractError::CannotMigrateToOlderVersion {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    migrate_state(deps.branch(), &stored_version)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+/// Runs the state transformations needed to bring storage written by `stored_version`
+/// up to the current layout. Each step takes the storage of one release to the next
+/// and they run in order, so a campaign can skip versions.
+fn migrate_state(mut deps: DepsMut, stored_version: &Version) -> Result<(), ContractError> {
+    if *stored_version < Version::new(0, 2, 0) {
+        migrate_from_0_1(deps.branch())?;
+    }
+
+    Ok(())
+}
+
+/// 0.1.x -> 0.2.0
+fn migrate_from_0_1(deps: DepsMut) -> Result<(), ContractError> {
+    // the pricing and sale modes added since are all off for a 0.1.x campaign
+    let legacy_config = LEGACY_CONFIG.load(deps.storage)?;
+    let config = Config {
+        admin: legacy_config.admin,
+        maintainer_addr: legacy_config.maintainer_addr,
+        start_time: legacy_config.start_time,
+        end_time: legacy_config.end_time,
+        total_token_supply: legacy_config.total_token_supply,
+        max_per_address_mint: legacy_config.max_per_address_mint,
+        max_per_address_bundle_mint: legacy_config.max_per_address_bundle_mint,
+        mint_price: legacy_config.mint_price,
+        bundle_mint_price: legacy_config.bundle_mint_price,
+        mint_denom: legacy_config.mint_denom,
+        token_code_id: legacy_config.token_code_id,
+        extension: legacy_config.extension,
+        escrow_funds: legacy_config.escrow_funds,
+        bundle_enabled: legacy_config.bundle_enabled,
+        bundle_completed: legacy_config.bundle_completed,
+        bonded_denom: legacy_config.bonded_denom,
+        dutch_auction: None,
+        bonding_curve: None,
+        bulk_discounts: None,
+        collection_selection: None,
+        denom_mint_prices: None,
+        cw20_payment: None,
+        oracle_pricing: None,
+        referral_bps: None,
+        refund_period: None,
+        minimum_raise: None,
+        raffle: None,
+    };
+    CONFIG.save(deps.storage, &config)?;
+
+    let legacy_collection_infos: Vec<(u64, LegacyCollectionInfo)> = LEGACY_CW721_COLLECTION_INFO
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(u64, LegacyCollectionInfo)>>>()?;
+
+    for (collection_id, info) in legacy_collection_infos {
+        CW721_COLLECTION_INFO.save(
+            deps.storage,
+            collection_id,
+            &CollectionInfo {
+                id: info.id,
+                token_supply: info.token_supply,
+                name: info.name,
+                symbol: info.symbol,
+                base_token_uri: info.base_token_uri,
+                secondary_metadata_uri: info.secondary_metadata_uri,
+                provenance_hash: None,
+                mint_price: None,
+                mint_enabled: None,
+                weight: None,
+            },
+        )?;
+    }
+
+    // each collection's shuffled token_ids were one `Vec<u32>`, kept in step with
+    // `COLLECTION_CURRENT_TOKEN_SUPPLY`. a large campaign can't be rewritten into the
+    // swap table in one transaction, so `MigrateTokenOrder` moves it over in pages
+    if let Some(collection_id) = LEGACY_CW721_SHUFFLED_TOKEN_IDS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .next()
+        .transpose()?
+    {
+        TOKEN_ORDER_MIGRATION.save(
+            deps.storage,
+            &TokenOrderMigration {
+                collection_id,
+                position: 0,
+            },
+        )?;
+    }
+
+    // escrow was `mint_denom` only and keyed by recipient
+    let legacy_balances: Vec<(Addr, Uint128)> = LEGACY_BANK_BALANCES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Addr, Uint128)>>>()?;
+
+    for (addr, balance) in legacy_balances {
+        BANK_BALANCES.save(
+            deps.storage,
+            (addr.clone(), config.mint_denom.clone()),
+            &balance,
+        )?;
+        LEGACY_BANK_BALANCES.remove(deps.storage, addr);
+    }
+
+    Ok(())
+}
+
+fn parse_contract_version(version: &str) -> Result<Version, ContractError> {
+    Version::parse(version).map_err(|_| ContractError::InvalidContractVersion {
+        version: version.to_string(),
+    })
+}
+
 // Reply callback triggered from cw721 contract instantiation
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
@@ -1,10 +1,10 @@
-use cosmwasm_std::{StdError, Uint128};
+use cosmwasm_std::{StdError, Timestamp, Uint128};
 use cw721_base::ContractError as Cw721ContractError;
 use cw_utils::ParseReplyError;
 use cw_utils::PaymentError;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
@@ -140,6 +140,258 @@ pub enum ContractError {
     #[error("invalid character ({c}) in native denom")]
     InvalidCharacter { c: char },
 
+    #[error("Cannot migrate contract {stored} to {expected}")]
+    InvalidMigrationContract { stored: String, expected: String },
+
+    #[error("Cannot migrate from version {stored} to older version {current}")]
+    CannotMigrateToOlderVersion { stored: String, current: String },
+
+    #[error("Invalid contract version: {version}")]
+    InvalidContractVersion { version: String },
+
+    #[error("Randomness commitment must be a hex encoded sha256 hash")]
+    InvalidRandomnessCommitment {},
+
+    #[error("No randomness commitment found")]
+    NoRandomnessCommitment {},
+
+    #[error("Randomness has not been revealed yet")]
+    RandomnessNotRevealed {},
+
+    #[error("Randomness has already been revealed")]
+    RandomnessAlreadyRevealed {},
+
+    #[error("Secret does not match the randomness commitment")]
+    RandomnessRevealMismatch {},
+
+    #[error("No hidden randomness left to draw mints with, a new secret has to be committed")]
+    HiddenRandomnessUnavailable {},
+
+    #[error("Invalid drand public key")]
+    InvalidDrandPubkey {},
+
+    #[error("Drand randomness is not configured")]
+    DrandNotConfigured {},
+
+    #[error("Invalid drand beacon for round {round}")]
+    InvalidDrandBeacon { round: u64 },
+
+    #[error("Drand period must be at least 1 second")]
+    InvalidDrandPeriod {},
+
+    #[error("Drand can't be used with bundles, which are drawn as they are paid")]
+    DrandWithBundleMint {},
+
+    #[error("Drand round {round} was already submitted")]
+    DrandBeaconExists { round: u64 },
+
+    #[error("Waiting on a drand beacon")]
+    DrandBeaconMissing {},
+
+    #[error("No pending mint for randomness job {job_id}")]
+    UnknownRandomnessJob { job_id: String },
+
+    #[error("Mint ticket expiry must be at least 1 block")]
+    InvalidMintTicketExpiry {},
+
+    #[error("Address already has a mint ticket in this block")]
+    MintTicketExists {},
+
+    #[error("Mint ticket not found")]
+    MintTicketNotFound {},
+
+    #[error("Mint ticket can only be settled in a later block")]
+    MintTicketNotReady {},
+
+    #[error("Mint ticket has expired")]
+    MintTicketExpired {},
+
+    #[error("Mint ticket has not expired yet")]
+    MintTicketNotExpired {},
+
+    #[error("Pending mint has not expired yet")]
+    PendingMintNotExpired {},
+
+    #[error("Dutch auction and bonding curve can't be combined with each other, denom mint prices or cw20 payments, nor an auction with public bulk discounts or oracle pricing")]
+    ConflictingPricingModes {},
+
+    #[error("Bulk discount tiers must be in increasing min_quantity and at most 10000 bps")]
+    InvalidBulkDiscounts {},
+
+    #[error("Denom mint prices must be non-zero, unique and not in mint_denom")]
+    InvalidDenomMintPrices {},
+
+    #[error("Cw20 mint prices must be non-zero")]
+    InvalidCw20Payment {},
+
+    #[error("Payment token is not the configured cw20")]
+    InvalidCw20Token {},
+
+    #[error("Oracle slippage can be at most 10000 bps")]
+    InvalidOraclePricing {},
+
+    #[error("Referral bps can be at most 10000")]
+    InvalidReferralBps {},
+
+    #[error("Minters can't refer themselves")]
+    InvalidReferrer {},
+
+    #[error("Mint codes need a hex encoded sha256, at least one use and at most 10000 bps")]
+    InvalidMintCodes {},
+
+    #[error("Mint code is unknown or has too few uses left")]
+    InvalidMintCode {},
+
+    #[error("Refunds need escrow_funds and can't be combined with a Dutch auction")]
+    InvalidRefundPolicy {},
+
+    #[error("Token has no refundable mint")]
+    NotRefundable {},
+
+    #[error("Refund window of the token has closed")]
+    RefundWindowClosed {},
+
+    #[error("Referrer of the token was credited less than its receipt records")]
+    ReferralRefundMismatch {},
+
+    #[error("Escrow is locked for refunds until {until}")]
+    EscrowLocked { until: Timestamp },
+
+    #[error("Minimum raise needs a threshold within the token supply and an end_time, and can't be combined with a Dutch auction, refunds or open token auctions")]
+    InvalidMinimumRaise {},
+
+    #[error("Minimum raise and end_time can't change once minting has started")]
+    MinimumRaiseLocked {},
+
+    #[error("Minimum raise has not been met")]
+    MinimumRaiseNotMet {},
+
+    #[error("Minimum raise was missed")]
+    MinimumRaiseMissed {},
+
+    #[error("Minimum raise has not been missed")]
+    MinimumRaiseNotMissed {},
+
+    #[error("No payments to claim back")]
+    NoRaiseContribution {},
+
+    #[error("Raffle needs at most max_per_address_mint tickets per address and a deadline before end_time, and can't be combined with an auction, bonding curve, oracle pricing or minimum raise")]
+    InvalidRaffle {},
+
+    #[error("No raffle is configured")]
+    NoRaffle {},
+
+    #[error("Raffle can't change once tickets are entered")]
+    RaffleLocked {},
+
+    #[error("Raffle is closed to entries")]
+    RaffleClosed {},
+
+    #[error("Raffle is still taking entries")]
+    RaffleNotClosed {},
+
+    #[error("At most {max} raffle tickets per address")]
+    RaffleTicketLimit { max: u32 },
+
+    #[error("Raffle has not been settled")]
+    RaffleNotSettled {},
+
+    #[error("Raffle is already settled")]
+    RaffleSettled {},
+
+    #[error("No losing raffle tickets to refund")]
+    NoRaffleRefund {},
+
+    #[error("Token auction needs a min_increment and an end_time after its start_time and now")]
+    InvalidTokenAuction {},
+
+    #[error("Token is not in the draw")]
+    TokenNotAvailable {},
+
+    #[error("Token is already auctioned")]
+    TokenAuctionExists {},
+
+    #[error("Token auctions can't be used with a minimum raise")]
+    TokenAuctionWithMinimumRaise {},
+
+    #[error("Token is promised by the airdropper")]
+    TokenPromised {},
+
+    #[error("Token is held or sold by an auction")]
+    TokenAuctioned {},
+
+    #[error("Token auction not found")]
+    TokenAuctionNotFound {},
+
+    #[error("Token auction is not taking bids")]
+    TokenAuctionNotOpen {},
+
+    #[error("Bid has to be at least {min}")]
+    BidTooLow { min: Uint128 },
+
+    #[error("Token auction has not ended")]
+    TokenAuctionNotEnded {},
+
+    #[error("Token auction is already settled")]
+    TokenAuctionSettled {},
+
+    #[error("Mint codes only apply to whitelist and public mints paid in mint_denom, and not to auction or oracle priced ones")]
+    MintCodeNotAccepted {},
+
+    #[error("Invalid Dutch auction")]
+    InvalidDutchAuction {},
+
+    #[error("No Dutch auction configured")]
+    NoDutchAuction {},
+
+    #[error("Auction clearing price is not known yet")]
+    AuctionNotFinal {},
+
+    #[error("No auction purchases to settle")]
+    NoAuctionPurchase {},
+
+    #[error("Provenance hash must be a hex encoded sha256")]
+    InvalidProvenanceHash {},
+
+    #[error("Collection not found: {collection_id}")]
+    CollectionNotFound { collection_id: u64 },
+
+    #[error("Token shuffle is in progress")]
+    ShuffleInProgress {},
+
+    #[error("Cannot shuffle while mints are waiting to be drawn")]
+    PendingMintsOutstanding {},
+
+    #[error("Shuffle limit must be at least 1 swap")]
+    InvalidShuffleLimit {},
+
+    #[error("Token order of the previous version is still being migrated")]
+    TokenOrderMigrationInProgress {},
+
+    #[error("No token order is waiting to be migrated")]
+    NoTokenOrderMigration {},
+
+    #[error("Token order migration limit must be at least 1 position")]
+    InvalidTokenOrderMigrationLimit {},
+
+    #[error("Mint quantity must be at least 1")]
+    InvalidMintQuantity {},
+
+    #[error("Only {remaining} tokens left to mint")]
+    InsufficientTokenSupply { remaining: u32 },
+
+    #[error("Collection weight must be between 1 and {max}")]
+    InvalidCollectionWeight { max: u32 },
+
+    #[error("Minting is disabled for collection {collection_id}")]
+    CollectionMintDisabled { collection_id: u64 },
+
+    #[error("A collection can't be picked while mints are drawn later")]
+    CollectionPickUnavailable {},
+
+    #[error("Bundles can't be minted while mints are drawn later")]
+    BundleMintUnavailable {},
+
     #[error("Custom Error val: {val:?}")]
     CustomError { val: String },
     // Add any other custom errors you like here.
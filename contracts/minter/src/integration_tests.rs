@@ -2,15 +2,20 @@
 mod tests {
     use crate::helpers::CwTemplateContract;
     use crate::msg::{
-        AddrBal, AddressValMsg, Admin, BaseInitMsg, CollectionInfoMsg, ConfigResponse, ExecuteMsg,
-        ExecutionTarget, InstantiateMsg, ModuleInstantiateInfo, QueryMsg, RoyaltyInfoMsg,
-        SharedCollectionInfoMsg, TokenDataResponse,
+        AddrBal, AddressValMsg, Admin, BaseInitMsg, CollectionInfoMsg, ConfigResponse,
+        Cw20PaymentMsg, ExecuteMsg, ExecutionTarget, InstantiateMsg, ModuleInstantiateInfo,
+        OraclePricingMsg, QueryMsg, RoyaltyInfoMsg, SharedCollectionInfoMsg, TokenDataResponse,
     };
+    use cosmwasm_schema::cw_serde;
     use cosmwasm_std::{
         coin, coins, to_binary, Addr, Coin, CosmosMsg, Empty, Timestamp, Uint128, WasmMsg,
     };
+    use cw721_base::QueryMsg as Cw721QueryMsg;
 
-    use cw_multi_test::{App, AppBuilder, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+    use crate::ContractError;
+    use cw_multi_test::{
+        App, AppBuilder, AppResponse, BankSudo, Contract, ContractWrapper, Executor, SudoMsg,
+    };
 
     use whitelist::{
         msg::ConfigResponse as WhitelistConfig, msg::ExecuteMsg as WhitelistExecuteMsg,
@@ -35,7 +40,8 @@ mod tests {
             crate::contract::instantiate,
             crate::query::query,
         )
-        .with_reply(crate::contract::reply);
+        .with_reply(crate::contract::reply)
+        .with_migrate(crate::contract::migrate);
         Box::new(contract)
     }
 
@@ -53,7 +59,8 @@ mod tests {
             airdropper::contract::execute,
             airdropper::contract::instantiate,
             airdropper::query::query,
-        );
+        )
+        .with_migrate(airdropper::contract::migrate);
         Box::new(contract)
     }
 
@@ -62,7 +69,8 @@ mod tests {
             whitelist::contract::execute,
             whitelist::contract::instantiate,
             whitelist::query::query,
-        );
+        )
+        .with_migrate(whitelist::contract::migrate);
         Box::new(contract)
     }
 
@@ -94,8 +102,161 @@ mod tests {
     const INSTANTIATE_WHITELIST_REPLY_ID: u64 = 2;
 
     const MAX_PER_ADDRESS_MINT: u32 = 4;
+
+    /// cw721's `AllTokens`/`Tokens` response
+    #[cw_serde]
+    struct TokensResponse {
+        tokens: Vec<String>,
+    }
+
+    fn query_all_tokens(app: &App, cw721_addr: String) -> Vec<String> {
+        let res: TokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                cw721_addr,
+                &Cw721QueryMsg::<Empty>::AllTokens {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+
+        res.tokens
+    }
     //const MAX_TOKEN_SUPPLY: u32 = 5;
 
+    /// `UpdateConfig` fields matching the current config
+    fn base_init_msg_from_config(app: &App, contract_addr: Addr) -> BaseInitMsg {
+        let config: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+
+        BaseInitMsg {
+            maintainer_address: config.maintainer_addr.map(|addr| addr.into_string()),
+            start_time: config.start_time,
+            end_time: config.end_time,
+            max_per_address_mint: config.max_per_address_mint,
+            max_per_address_bundle_mint: config.max_per_address_bundle_mint,
+            mint_price: config.mint_price,
+            bundle_mint_price: config.bundle_mint_price,
+            mint_denom: config.mint_denom,
+            escrow_funds: false,
+            bundle_enabled: config.bundle_enabled,
+            airdropper_address: config.airdropper_addr.map(|addr| addr.into_string()),
+            whitelist_address: config.whitelist_addr.map(|addr| addr.into_string()),
+            dutch_auction: config.dutch_auction,
+            bonding_curve: config.bonding_curve,
+            bulk_discounts: config.bulk_discounts,
+            collection_selection: config.collection_selection,
+            denom_mint_prices: config.denom_mint_prices,
+            cw20_payment: config.cw20_payment.map(|cw20_payment| Cw20PaymentMsg {
+                token_address: cw20_payment.token_addr.into_string(),
+                mint_price: cw20_payment.mint_price,
+                whitelist_mint_price: cw20_payment.whitelist_mint_price,
+                bundle_mint_price: cw20_payment.bundle_mint_price,
+            }),
+            oracle_pricing: config
+                .oracle_pricing
+                .map(|oracle_pricing| OraclePricingMsg {
+                    oracle_address: oracle_pricing.oracle_addr.into_string(),
+                    slippage_bps: oracle_pricing.slippage_bps,
+                }),
+            referral_bps: config.referral_bps,
+            refund_period: config.refund_period,
+            minimum_raise: config.minimum_raise,
+            raffle: config.raffle,
+        }
+    }
+
+    fn remaining_tokens(app: &App, contract_addr: Addr) -> u32 {
+        let token_data: TokenDataResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr, &QueryMsg::GetRemainingTokens {})
+            .unwrap();
+
+        token_data.remaining_token_supply
+    }
+
+    fn balance(app: &App, address: impl Into<String>) -> Uint128 {
+        app.wrap()
+            .query_balance(address, NATIVE_DENOM)
+            .unwrap()
+            .amount
+    }
+
+    fn fund(app: &mut App, address: &str, amount: u128) {
+        app.sudo(SudoMsg::Bank({
+            BankSudo::Mint {
+                to_address: address.to_string(),
+                amount: coins(amount, NATIVE_DENOM),
+            }
+        }))
+        .unwrap();
+    }
+
+    /// no modules or bundle, and `funded` hold 10_000_000 `NATIVE_DENOM` each
+    fn setup_funded(funded: &[&str]) -> (App, Addr) {
+        let (mut app, cw_template_contract) = proper_instantiate(false, false, false, None, None);
+
+        for address in funded {
+            fund(&mut app, address, 10_000_000);
+        }
+
+        (app, cw_template_contract.addr())
+    }
+
+    /// `UpdateConfig` by the admin, with `update` applied to the current fields
+    fn update_config(
+        app: &mut App,
+        contract_addr: Addr,
+        update: impl FnOnce(BaseInitMsg) -> BaseInitMsg,
+    ) -> Result<AppResponse, ContractError> {
+        let msg = update(base_init_msg_from_config(app, contract_addr.clone()));
+
+        app.execute_contract(
+            Addr::unchecked(ADMIN),
+            contract_addr,
+            &ExecuteMsg::UpdateConfig(msg),
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+    }
+
+    /// public `Mint` of `quantity` tokens for the sender
+    fn mint_msg(
+        quantity: u32,
+        collection_id: Option<u64>,
+        referrer: Option<&str>,
+        code: Option<&str>,
+    ) -> ExecuteMsg {
+        ExecuteMsg::Mint {
+            is_promised_mint: false,
+            minter_address: None,
+            quantity: Some(quantity),
+            collection_id,
+            referrer: referrer.map(String::from),
+            code: code.map(String::from),
+        }
+    }
+
+    /// `minter` mints `quantity` tokens, paying `amount` of `NATIVE_DENOM`
+    fn mint(
+        app: &mut App,
+        contract_addr: Addr,
+        minter: &str,
+        quantity: u32,
+        amount: u128,
+    ) -> Result<AppResponse, ContractError> {
+        app.execute_contract(
+            Addr::unchecked(minter),
+            contract_addr,
+            &mint_msg(quantity, None, None, None),
+            &coins(amount, NATIVE_DENOM),
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+    }
+
     fn mock_app() -> App {
         AppBuilder::new().build(|router, _, storage| {
             router
@@ -196,6 +357,10 @@ mod tests {
             secondary_metadata_uri: Some(
                 "ipfs://QmSw2yJjwYbdVnn27KQFg5ex2Q6G24RxorgX7v72NpFs4v".to_string(),
             ),
+            provenance_hash: None,
+            mint_price: None,
+            mint_enabled: None,
+            weight: None,
         }];
 
         for id in 2u32..=multiple_collection_count.unwrap_or(0) {
@@ -213,6 +378,10 @@ mod tests {
                 secondary_metadata_uri: Some(
                     "ipfs://QmSw2yJjwYbdVnn27KQFg5ex2Q6G24RxorgX7v72NpFs4v".to_string(),
                 ),
+                provenance_hash: None,
+                mint_price: None,
+                mint_enabled: None,
+                weight: None,
             });
         }
 
@@ -230,6 +399,17 @@ mod tests {
                 bundle_enabled: bundle,
                 airdropper_address: None,
                 whitelist_address: None,
+                dutch_auction: None,
+                bonding_curve: None,
+                bulk_discounts: None,
+                collection_selection: None,
+                denom_mint_prices: None,
+                cw20_payment: None,
+                oracle_pricing: None,
+                referral_bps: None,
+                refund_period: None,
+                minimum_raise: None,
+                raffle: None,
             },
             token_code_id: cw721_id,
             name: "TESTNFTPROJECT".to_string(),
@@ -247,7 +427,7 @@ mod tests {
                 &[],
                 //&[coin(1_000_000_000, NATIVE_DENOM)],
                 "test",
-                None,
+                Some(ADMIN.to_string()),
             )
             .unwrap();
 
@@ -394,6 +574,10 @@ mod tests {
             secondary_metadata_uri: Some(
                 "ipfs://QmSw2yJjwYbdVnn27KQFg5ex2Q6G24RxorgX7v72NpFs4v".to_string(),
             ),
+            provenance_hash: None,
+            mint_price: None,
+            mint_enabled: None,
+            weight: None,
         }];
 
         if multiple_collections {
@@ -405,6 +589,10 @@ mod tests {
                 secondary_metadata_uri: Some(
                     "ipfs://QmSw2yJjwYbdVnn27KQFg5ex2Q6G24RxorgX7v72NpFs4v".to_string(),
                 ),
+                provenance_hash: None,
+                mint_price: None,
+                mint_enabled: None,
+                weight: None,
             });
         }
 
@@ -422,6 +610,17 @@ mod tests {
                 bundle_enabled: bundle,
                 airdropper_address: None,
                 whitelist_address: None,
+                dutch_auction: None,
+                bonding_curve: None,
+                bulk_discounts: None,
+                collection_selection: None,
+                denom_mint_prices: None,
+                cw20_payment: None,
+                oracle_pricing: None,
+                referral_bps: None,
+                refund_period: None,
+                minimum_raise: None,
+                raffle: None,
             },
             token_code_id: cw721_id,
             name: "TESTNFTPROJECT".to_string(),
@@ -439,7 +638,7 @@ mod tests {
                 &[],
                 //&[coin(1_000_000_000, NATIVE_DENOM)],
                 "test",
-                None,
+                Some(ADMIN.to_string()),
             )
             .unwrap();
 
@@ -496,7 +695,7 @@ mod tests {
         use super::*;
         use crate::msg::QueryMsg;
         use crate::state::CollectionInfo;
-        use cw721_base::{MinterResponse, QueryMsg as Cw721QueryMsg};
+        use cw721_base::MinterResponse;
 
         #[test]
         fn proper_init() {
@@ -642,6 +841,7 @@ mod tests {
 
     mod updates {
         use super::*;
+        use crate::ContractError;
 
         #[test]
         fn test_update_maintainer() {
@@ -678,6 +878,17 @@ mod tests {
                 bundle_enabled: config.bundle_enabled,
                 airdropper_address,
                 whitelist_address,
+                dutch_auction: None,
+                bonding_curve: None,
+                bulk_discounts: None,
+                collection_selection: None,
+                denom_mint_prices: None,
+                cw20_payment: None,
+                oracle_pricing: None,
+                referral_bps: None,
+                refund_period: None,
+                minimum_raise: None,
+                raffle: None,
             };
 
             assert_eq!(
@@ -814,18 +1025,40 @@ mod tests {
             app.execute_contract(
                 Addr::unchecked(USER.to_owned()),
                 cw_template_contract.addr(),
-                &ExecuteMsg::ShuffleTokenOrder {},
+                &ExecuteMsg::ShuffleTokenOrder { limit: None },
                 &[],
             )
             .unwrap_err();
 
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN.to_owned()),
+                    cw_template_contract.addr(),
+                    &ExecuteMsg::ShuffleTokenOrder { limit: Some(0) },
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidShuffleLimit {}
+            );
+
             app.execute_contract(
                 Addr::unchecked(ADMIN.to_owned()),
                 cw_template_contract.addr(),
-                &ExecuteMsg::ShuffleTokenOrder {},
+                &ExecuteMsg::ShuffleTokenOrder { limit: None },
                 &[],
             )
             .unwrap();
+
+            // non admins pay the fee to start one before minting opens
+            app.execute_contract(
+                Addr::unchecked(USER.to_owned()),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ShuffleTokenOrder { limit: None },
+                &[coin(1_000_000, NATIVE_DENOM)],
+            )
+            .unwrap();
         }
 
         #[test]
@@ -844,6 +1077,10 @@ mod tests {
                 &ExecuteMsg::Mint {
                     is_promised_mint: false,
                     minter_address: None,
+                    quantity: None,
+                    collection_id: None,
+                    referrer: None,
+                    code: None,
                 },
                 &[coin(2_000_000, NATIVE_DENOM)],
             )
@@ -854,7 +1091,7 @@ mod tests {
             app.execute_contract(
                 Addr::unchecked(USER.to_owned()),
                 cw_template_contract.addr(),
-                &ExecuteMsg::ShuffleTokenOrder {},
+                &ExecuteMsg::ShuffleTokenOrder { limit: None },
                 &[],
             )
             .unwrap_err();
@@ -862,27 +1099,33 @@ mod tests {
             app.execute_contract(
                 Addr::unchecked(USER.to_owned()),
                 cw_template_contract.addr(),
-                &ExecuteMsg::ShuffleTokenOrder {},
+                &ExecuteMsg::ShuffleTokenOrder { limit: None },
                 &[coin(3_000_000, NATIVE_DENOM)],
             )
             .unwrap_err();
 
             app.update_block(|mut block| block.height += 1);
 
-            app.execute_contract(
-                Addr::unchecked(USER.to_owned()),
-                cw_template_contract.addr(),
-                &ExecuteMsg::ShuffleTokenOrder {},
-                &[coin(1_000_000, NATIVE_DENOM)],
-            )
-            .unwrap();
+            // a shuffle would hold up minting, so not even with the fee once it is open
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER.to_owned()),
+                    cw_template_contract.addr(),
+                    &ExecuteMsg::ShuffleTokenOrder { limit: None },
+                    &[coin(1_000_000, NATIVE_DENOM)],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::Unauthorized {}
+            );
 
             app.update_block(|mut block| block.height += 1);
 
             app.execute_contract(
                 Addr::unchecked(ADMIN.to_owned()),
                 cw_template_contract.addr(),
-                &ExecuteMsg::ShuffleTokenOrder {},
+                &ExecuteMsg::ShuffleTokenOrder { limit: None },
                 &[],
             )
             .unwrap();
@@ -942,7 +1185,7 @@ mod tests {
             app.execute_contract(
                 Addr::unchecked(ADMIN.to_owned()),
                 cw_template_contract.addr(),
-                &ExecuteMsg::ShuffleTokenOrder {},
+                &ExecuteMsg::ShuffleTokenOrder { limit: None },
                 &[],
             )
             .unwrap();
@@ -953,7 +1196,7 @@ mod tests {
             app.execute_contract(
                 Addr::unchecked(ADMIN.to_owned()),
                 cw_template_contract.addr(),
-                &ExecuteMsg::ShuffleTokenOrder {},
+                &ExecuteMsg::ShuffleTokenOrder { limit: None },
                 &[],
             )
             .unwrap();
@@ -969,7 +1212,7 @@ mod tests {
             app.execute_contract(
                 Addr::unchecked(ADMIN.to_owned()),
                 cw_template_contract.addr(),
-                &ExecuteMsg::ShuffleTokenOrder {},
+                &ExecuteMsg::ShuffleTokenOrder { limit: None },
                 &[],
             )
             .unwrap();
@@ -2320,6 +2563,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -2342,6 +2589,10 @@ mod tests {
             let msg = ExecuteMsg::Mint {
                 is_promised_mint: false,
                 minter_address: None,
+                quantity: None,
+                collection_id: None,
+                referrer: None,
+                code: None,
             };
 
             app.update_block(|mut block| {
@@ -2374,6 +2625,10 @@ mod tests {
             let msg = ExecuteMsg::Mint {
                 is_promised_mint: false,
                 minter_address: None,
+                quantity: None,
+                collection_id: None,
+                referrer: None,
+                code: None,
             };
 
             app.update_block(|mut block| {
@@ -2437,6 +2692,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -2492,6 +2751,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -2587,6 +2850,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_001, NATIVE_DENOM)],
                 )
@@ -2650,6 +2917,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -2665,6 +2936,10 @@ mod tests {
                         &ExecuteMsg::Mint {
                             is_promised_mint: false,
                             minter_address: None,
+                            quantity: None,
+                            collection_id: None,
+                            referrer: None,
+                            code: None,
                         },
                         &[coin(1_000_000, NATIVE_DENOM)],
                     )
@@ -2678,6 +2953,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -2704,6 +2983,10 @@ mod tests {
                         &ExecuteMsg::Mint {
                             is_promised_mint: false,
                             minter_address: None,
+                            quantity: None,
+                            collection_id: None,
+                            referrer: None,
+                            code: None,
                         },
                         &[coin(1_000_000, NATIVE_DENOM)],
                     )
@@ -2717,6 +3000,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -2782,6 +3069,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -2797,6 +3088,10 @@ mod tests {
                         &ExecuteMsg::Mint {
                             is_promised_mint: false,
                             minter_address: None,
+                            quantity: None,
+                            collection_id: None,
+                            referrer: None,
+                            code: None,
                         },
                         &[coin(1_000_000, NATIVE_DENOM)],
                     )
@@ -2810,6 +3105,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -2834,6 +3133,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -2870,6 +3173,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -2886,6 +3193,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -2898,6 +3209,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(2_000_000, NATIVE_DENOM)],
                 )
@@ -2910,6 +3225,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(2_000_000, NATIVE_DENOM)],
                 )
@@ -2951,6 +3270,17 @@ mod tests {
                 bundle_enabled: config.bundle_enabled,
                 airdropper_address,
                 whitelist_address,
+                dutch_auction: None,
+                bonding_curve: None,
+                bulk_discounts: None,
+                collection_selection: None,
+                denom_mint_prices: None,
+                cw20_payment: None,
+                oracle_pricing: None,
+                referral_bps: None,
+                refund_period: None,
+                minimum_raise: None,
+                raffle: None,
             };
 
             msg.escrow_funds = true;
@@ -2987,6 +3317,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -3002,6 +3336,10 @@ mod tests {
                         &ExecuteMsg::Mint {
                             is_promised_mint: false,
                             minter_address: None,
+                            quantity: None,
+                            collection_id: None,
+                            referrer: None,
+                            code: None,
                         },
                         &[coin(1_000_000, NATIVE_DENOM)],
                     )
@@ -3015,6 +3353,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -3039,6 +3381,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -3075,6 +3421,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -3091,6 +3441,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(1_000_000, NATIVE_DENOM)],
                 )
@@ -3103,6 +3457,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(2_000_000, NATIVE_DENOM)],
                 )
@@ -3115,6 +3473,10 @@ mod tests {
                     &ExecuteMsg::Mint {
                         is_promised_mint: false,
                         minter_address: None,
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: None,
                     },
                     &[coin(2_000_000, NATIVE_DENOM)],
                 )
@@ -3147,6 +3509,7 @@ mod tests {
                     &cw_template_contract.addr(),
                     &QueryMsg::GetEscrowBalances {
                         start_after: None,
+                        start_after_denom: None,
                         limit: None,
                     },
                 )
@@ -3157,10 +3520,12 @@ mod tests {
                 vec![
                     AddrBal {
                         addr: Addr::unchecked(ADMIN.to_owned()),
+                        denom: NATIVE_DENOM.to_string(),
                         balance: Uint128::from(4_200_000u128)
                     },
                     AddrBal {
                         addr: Addr::unchecked(MAINTAINER_ADDR.to_owned()),
+                        denom: NATIVE_DENOM.to_string(),
                         balance: Uint128::from(1_800_000u128)
                     }
                 ]
@@ -3835,6 +4200,17 @@ mod tests {
                 bundle_enabled: config.bundle_enabled,
                 airdropper_address: None,
                 whitelist_address: None,
+                dutch_auction: None,
+                bonding_curve: None,
+                bulk_discounts: None,
+                collection_selection: None,
+                denom_mint_prices: None,
+                cw20_payment: None,
+                oracle_pricing: None,
+                referral_bps: None,
+                refund_period: None,
+                minimum_raise: None,
+                raffle: None,
             };
 
             // removed end time
@@ -3851,6 +4227,10 @@ mod tests {
             let msg = ExecuteMsg::Mint {
                 is_promised_mint: false,
                 minter_address: None,
+                quantity: None,
+                collection_id: None,
+                referrer: None,
+                code: None,
             };
 
             app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
@@ -4009,6 +4389,17 @@ mod tests {
                 bundle_enabled: config.bundle_enabled,
                 airdropper_address: None,
                 whitelist_address: None,
+                dutch_auction: None,
+                bonding_curve: None,
+                bulk_discounts: None,
+                collection_selection: None,
+                denom_mint_prices: None,
+                cw20_payment: None,
+                oracle_pricing: None,
+                referral_bps: None,
+                refund_period: None,
+                minimum_raise: None,
+                raffle: None,
             };
 
             // removed end time
@@ -4025,6 +4416,10 @@ mod tests {
             let msg = ExecuteMsg::Mint {
                 is_promised_mint: false,
                 minter_address: None,
+                quantity: None,
+                collection_id: None,
+                referrer: None,
+                code: None,
             };
 
             app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
@@ -4159,6 +4554,10 @@ mod tests {
             let msg = ExecuteMsg::Mint {
                 is_promised_mint: false,
                 minter_address: None,
+                quantity: None,
+                collection_id: None,
+                referrer: None,
+                code: None,
             };
 
             app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
@@ -4200,6 +4599,10 @@ mod tests {
             let msg = ExecuteMsg::Mint {
                 is_promised_mint: false,
                 minter_address: None,
+                quantity: None,
+                collection_id: None,
+                referrer: None,
+                code: None,
             };
 
             app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
@@ -4250,6 +4653,10 @@ mod tests {
             let msg = ExecuteMsg::Mint {
                 is_promised_mint: false,
                 minter_address: None,
+                quantity: None,
+                collection_id: None,
+                referrer: None,
+                code: None,
             };
 
             app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
@@ -4347,6 +4754,10 @@ mod tests {
             let msg = ExecuteMsg::Mint {
                 is_promised_mint: false,
                 minter_address: None,
+                quantity: None,
+                collection_id: None,
+                referrer: None,
+                code: None,
             };
 
             app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
@@ -4443,7 +4854,7 @@ mod tests {
             println!("### token_data {:?}", token_data);
 
             // not yet block time
-            let msg = ExecuteMsg::MintBundle {};
+            let msg = ExecuteMsg::MintBundle { referrer: None };
 
             app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
             app.update_block(|mut block| block.height += 1);
@@ -4524,7 +4935,7 @@ mod tests {
                 get_collection_current_supply
             );
 
-            let msg = ExecuteMsg::MintBundle {};
+            let msg = ExecuteMsg::MintBundle { referrer: None };
 
             app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
 
@@ -4593,166 +5004,6553 @@ mod tests {
         }
     }
 
-    mod misc_tests {
+    mod randomness {
         use super::*;
-        use crate::state::RoyaltyInfo;
-        use cosmwasm_std::Decimal;
+        use crate::contract::randomize_and_draw_index;
+        use cosmwasm_std::testing::mock_env;
+
+        /// chi-square critical value for 9 degrees of freedom at p = 0.001
+        const CHI_SQUARE_CRITICAL_DF9: f64 = 27.877;
+
+        fn chi_square(counts: &[u32], draws: u32) -> f64 {
+            let expected = draws as f64 / counts.len() as f64;
+
+            counts
+                .iter()
+                .map(|&count| {
+                    let diff = count as f64 - expected;
+                    diff * diff / expected
+                })
+                .sum()
+        }
 
         #[test]
-        fn test_decimal() {
-            let share1 = 10000;
-            let share2 = 5000;
-            let share3 = 2500;
-            let share4 = 1000;
-            let share5 = 750;
-            let share6 = 25;
+        fn draw_index_is_uniform_small_supply() {
+            let limit: u32 = 10;
+            let draws: u32 = 20_000;
+            let mut env = mock_env();
+            let mut counts = vec![0u32; limit as usize];
+
+            // every simulated mint lands in a different block
+            for height in 0..draws {
+                env.block.height = 12_345 + height as u64;
+
+                let index =
+                    randomize_and_draw_index(&env, Addr::unchecked(USER), 101, limit, "").unwrap();
+                counts[index as usize] += 1;
+            }
 
-            println!(
-                "{:?}",
-                (Uint128::from(MINT_PRICE) * Decimal::percent(share1) / Uint128::from(100u128))
-            );
-            println!(
-                "{:?}",
-                (Uint128::from(MINT_PRICE) * Decimal::percent(share2) / Uint128::from(100u128))
-            );
-            println!(
-                "{:?}",
-                (Uint128::from(MINT_PRICE) * Decimal::percent(share3) / Uint128::from(100u128))
-            );
-            println!(
-                "{:?}",
-                (Uint128::from(MINT_PRICE) * Decimal::percent(share4) / Uint128::from(100u128))
-            );
-            println!(
-                "{:?}",
-                (Uint128::from(MINT_PRICE) * Decimal::percent(share5) / Uint128::from(100u128))
-            );
-            println!(
-                "{:?}",
-                (Uint128::from(MINT_PRICE) * Decimal::percent(share6) / Uint128::from(100u128))
-            );
+            let chi_square = chi_square(&counts, draws);
+            println!("counts {:?} chi_square {:?}", counts, chi_square);
+
+            assert!(chi_square < CHI_SQUARE_CRITICAL_DF9);
         }
 
         #[test]
-        fn test_sort() {
-            let mut my_vec: Vec<RoyaltyInfo> = vec![
-                RoyaltyInfo {
-                    addr: Addr::unchecked(USER.to_owned()),
-                    bps: 3300,
-                    is_primary: false,
-                },
-                RoyaltyInfo {
-                    addr: Addr::unchecked(USER2.to_owned()),
-                    bps: 5000,
-                    is_primary: false,
-                },
-                RoyaltyInfo {
-                    addr: Addr::unchecked(USER3.to_owned()),
-                    bps: 500,
-                    is_primary: true,
-                },
-                RoyaltyInfo {
-                    addr: Addr::unchecked(USER10.to_owned()),
-                    bps: 1200,
-                    is_primary: false,
-                },
-            ];
+        fn draw_index_is_uniform_across_remaining_supply() {
+            // indices are bucketed into deciles of the remaining supply as it shrinks,
+            // so draws clustered at the front or back of the vec show up immediately
+            let mut env = mock_env();
+            let mut counts = vec![0u32; 10];
+            let mut draws: u32 = 0;
+
+            for run in 0..4u64 {
+                for remaining in (1..=5_000u32).rev() {
+                    env.block.height = 12_345 + run * 5_000 + remaining as u64;
+
+                    let index =
+                        randomize_and_draw_index(&env, Addr::unchecked(USER25), 102, remaining, "")
+                            .unwrap();
+                    assert!(index < remaining);
+
+                    counts[(index as u64 * 10 / remaining as u64) as usize] += 1;
+                    draws += 1;
+                }
+            }
 
-            my_vec.sort_by(|a, b| a.is_primary.cmp(&b.is_primary));
+            let chi_square = chi_square(&counts, draws);
+            println!("counts {:?} chi_square {:?}", counts, chi_square);
 
-            println!("{:?}", my_vec);
-            //assert_eq!(5, 7);
+            assert!(chi_square < CHI_SQUARE_CRITICAL_DF9);
+        }
+
+        #[test]
+        fn draw_index_empty_supply() {
+            randomize_and_draw_index(&mock_env(), Addr::unchecked(USER), 101, 0, "").unwrap_err();
+        }
+
+        #[test]
+        fn revealed_secret_changes_draws() {
+            let mut env = mock_env();
+            let mut differs: u32 = 0;
+
+            for height in 0..100u64 {
+                env.block.height = 12_345 + height;
+
+                let plain =
+                    randomize_and_draw_index(&env, Addr::unchecked(USER), 101, 1_000, "").unwrap();
+                let mixed =
+                    randomize_and_draw_index(&env, Addr::unchecked(USER), 101, 1_000, "neta")
+                        .unwrap();
+
+                if plain != mixed {
+                    differs += 1;
+                }
+            }
+
+            // each pair only collides 1 in 1000 times
+            assert!(differs > 90);
         }
     }
 
-    mod bulk_tests {
+    mod commit_reveal {
         use super::*;
+        use crate::state::{MintTicket, RandomnessCommit};
+        use crate::ContractError;
+        use sha2::{Digest, Sha256};
+
+        const SECRET: &str = "not so secret";
+
+        fn commitment(secret: &str) -> String {
+            format!("{:x}", Sha256::digest(secret.as_bytes()))
+        }
+
+        fn query_commit(app: &App, contract_addr: Addr) -> Option<RandomnessCommit> {
+            app.wrap()
+                .query_wasm_smart(contract_addr, &QueryMsg::GetRandomnessCommit {})
+                .unwrap()
+        }
 
         #[test]
-        fn test_large_datasets() {
-            let (mut app, cw_template_contract) =
-                proper_instantiate(true, true, true, Some(3), Some(3000));
+        fn commit_and_reveal() {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
 
-            let config: ConfigResponse = app
-                .wrap()
-                .query_wasm_smart(&cw_template_contract.addr(), &QueryMsg::GetConfig {})
-                .unwrap();
+            assert_eq!(query_commit(&app, cw_template_contract.addr()), None);
 
-            let mut msg: BaseInitMsg = BaseInitMsg {
-                maintainer_address: None,
-                start_time: config.start_time,
-                end_time: config.end_time,
-                max_per_address_mint: config.max_per_address_mint,
-                max_per_address_bundle_mint: config.max_per_address_bundle_mint,
-                mint_price: config.mint_price,
-                bundle_mint_price: config.bundle_mint_price,
-                mint_denom: config.mint_denom.to_string(),
-                escrow_funds: false,
-                bundle_enabled: config.bundle_enabled,
-                airdropper_address: None,
-                whitelist_address: None,
+            let commit_msg = ExecuteMsg::CommitRandomness {
+                commitment: commitment(SECRET),
             };
 
-            msg.max_per_address_mint = 40000;
+            // only admin or maintainer
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &commit_msg,
+                &[],
+            )
+            .unwrap_err();
+
+            // must be a sha256 hash
             app.execute_contract(
                 Addr::unchecked(ADMIN),
                 cw_template_contract.addr(),
-                &ExecuteMsg::UpdateConfig(msg),
+                &ExecuteMsg::CommitRandomness {
+                    commitment: SECRET.to_string(),
+                },
                 &[],
             )
-            .unwrap();
+            .unwrap_err();
 
-            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
-            app.update_block(|mut block| block.height += 1);
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &commit_msg,
+                &[],
+            )
+            .unwrap();
+            let committed_at = app.block_info().time;
 
-            let msg = ExecuteMsg::MintBundle {};
+            assert_eq!(
+                query_commit(&app, cw_template_contract.addr()),
+                Some(RandomnessCommit {
+                    commitment: commitment(SECRET),
+                    secret: None,
+                    committed_at,
+                })
+            );
 
-            for _ in 0u32..=5 {
-                let _res = app
-                    .execute_contract(
-                        Addr::unchecked(USER25),
-                        cw_template_contract.addr(),
-                        &msg,
-                        &[coin(5_000_000, NATIVE_DENOM)],
-                    )
-                    .unwrap();
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
 
-                app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
-                app.update_block(|mut block| block.height += 1);
-            }
+            // commitment is locked once the campaign starts
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::CommitRandomness {
+                    commitment: commitment("another secret"),
+                },
+                &[],
+            )
+            .unwrap_err();
 
-            let config: ConfigResponse = app
-                .wrap()
-                .query_wasm_smart(&cw_template_contract.addr(), &QueryMsg::GetConfig {})
-                .unwrap();
-            println!("config {:?}", config);
+            let mint_msg = mint_msg(1, None, None, None);
 
-            let _res = app
-                .execute_contract(
-                    Addr::unchecked(USER25),
-                    cw_template_contract.addr(),
-                    &msg,
-                    &[coin(5_000_000, NATIVE_DENOM)],
-                )
-                .unwrap_err();
+            // paid against the hidden secret, but nothing is drawn or shuffled before the reveal
+            let paid_height = app.block_info().height;
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &mint_msg,
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
 
-            let per_collection_supplies: Vec<(u64, u32)> = app
+            let tickets: Vec<MintTicket> = app
                 .wrap()
                 .query_wasm_smart(
                     &cw_template_contract.addr(),
-                    &QueryMsg::GetCollectionCurrentTokenSupply {
+                    &QueryMsg::GetMintTickets {
+                        minter_address: USER25.to_string(),
                         start_after: None,
-                        limit: Some(50),
+                        limit: None,
                     },
                 )
                 .unwrap();
+            assert_eq!(tickets.len(), 1);
+            assert_eq!(tickets[0].commitment, Some(commitment(SECRET)));
 
-            println!("per_collection_supplies {:?}", per_collection_supplies);
+            app.update_block(|mut block| block.height += 1);
 
-            let msg = ExecuteMsg::Mint {
-                is_promised_mint: false,
-                minter_address: None,
+            let settle_msg = ExecuteMsg::SettleMintTicket {
+                minter_address: USER25.to_string(),
+                height: paid_height,
             };
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER),
+                    cw_template_contract.addr(),
+                    &settle_msg,
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::RandomnessNotRevealed {}
+            );
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ShuffleTokenOrder { limit: None },
+                &[],
+            )
+            .unwrap_err();
+
+            // wrong secret and wrong sender
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::RevealRandomness {
+                    secret: "wrong secret".to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+
+            let reveal_msg = ExecuteMsg::RevealRandomness {
+                secret: SECRET.to_string(),
+            };
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &reveal_msg,
+                &[],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(MAINTAINER_ADDR),
+                cw_template_contract.addr(),
+                &reveal_msg,
+                &[],
+            )
+            .unwrap();
+
+            // can only be revealed once
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &reveal_msg,
+                &[],
+            )
+            .unwrap_err();
+
+            assert_eq!(
+                query_commit(&app, cw_template_contract.addr()),
+                Some(RandomnessCommit {
+                    commitment: commitment(SECRET),
+                    secret: Some(SECRET.to_string()),
+                    committed_at,
+                })
+            );
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &settle_msg,
+                &[],
+            )
+            .unwrap();
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ShuffleTokenOrder { limit: None },
+                &[],
+            )
+            .unwrap();
+
+            // with the secret out, mints are drawn as they are paid until the next commitment
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &mint_msg,
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 3);
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    cw_template_contract.addr(),
+                    &commit_msg,
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::RandomnessAlreadyRevealed {}
+            );
+
+            app.execute_contract(
+                Addr::unchecked(MAINTAINER_ADDR),
+                cw_template_contract.addr(),
+                &ExecuteMsg::CommitRandomness {
+                    commitment: commitment("another secret"),
+                },
+                &[],
+            )
+            .unwrap();
+
+            // paid as a ticket against the new secret
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &mint_msg,
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 3);
+        }
+
+        #[test]
+        fn bundle_mint_after_reveal() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(false, false, true, Some(2), None);
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::CommitRandomness {
+                    commitment: commitment(SECRET),
+                },
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            let bundle_msg = ExecuteMsg::MintBundle { referrer: None };
+
+            // a bundle is drawn as it is paid, which the hidden secret can't be
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    cw_template_contract.addr(),
+                    &bundle_msg,
+                    &[coin(BUNDLE_MINT_PRICE, NATIVE_DENOM)],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::BundleMintUnavailable {}
+            );
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::RevealRandomness {
+                    secret: SECRET.to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &bundle_msg,
+                &[coin(BUNDLE_MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 8);
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &mint_msg(1, None, None, None),
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 7);
+        }
+
+        #[test]
+        fn reveal_without_commit() {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::RevealRandomness {
+                    secret: SECRET.to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        }
+    }
+
+    mod drand {
+        use super::*;
+        use crate::state::{DrandBeacon, MintTicket};
+        use crate::ContractError;
+        use cosmwasm_std::Binary;
+
+        // League of Entropy mainnet
+        const PUBKEY: &str = "868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31";
+
+        // recorded mainnet round
+        const ROUND: u64 = 72785;
+        const PREVIOUS_SIGNATURE: &str = "a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747";
+        const SIGNATURE: &str = "82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42";
+        const RANDOMNESS: &str = "8b676484b5fb1f37f9ec5c413d7d29883504e5b669f604a1ce68b3388e9ae3d9";
+
+        const PERIOD: u64 = 30;
+        // makes `ROUND` the round a ticket paid at `MINT_START_TIME` waits for
+        const GENESIS_TIME: u64 = MINT_START_TIME - (ROUND - 3) * PERIOD;
+
+        fn from_hex(hex: &str) -> Binary {
+            Binary::from(
+                (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+                    .collect::<Vec<u8>>(),
+            )
+        }
+
+        fn configure_msg(pubkey: &str, period: u64) -> ExecuteMsg {
+            ExecuteMsg::ConfigureDrand {
+                pubkey: from_hex(pubkey),
+                genesis_time: GENESIS_TIME,
+                period,
+            }
+        }
+
+        fn beacon_msg(round: u64, signature: &str) -> ExecuteMsg {
+            ExecuteMsg::SubmitDrandBeacon {
+                round,
+                previous_signature: from_hex(PREVIOUS_SIGNATURE),
+                signature: from_hex(signature),
+            }
+        }
+
+        #[test]
+        fn mint_with_drand_beacon() {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
+
+            // nothing to verify against yet
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER),
+                    cw_template_contract.addr(),
+                    &beacon_msg(ROUND, SIGNATURE),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::DrandNotConfigured {}
+            );
+
+            // only admin or maintainer, only valid G1 points and a period
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &configure_msg(PUBKEY, PERIOD),
+                &[],
+            )
+            .unwrap_err();
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    cw_template_contract.addr(),
+                    &configure_msg(&PUBKEY[..64], PERIOD),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidDrandPubkey {}
+            );
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    cw_template_contract.addr(),
+                    &configure_msg(PUBKEY, 0),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidDrandPeriod {}
+            );
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &configure_msg(PUBKEY, PERIOD),
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            let mint_msg = mint_msg(1, None, None, None);
+
+            // paid as a ticket bound to a round that is not out yet
+            let paid_height = app.block_info().height;
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &mint_msg,
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            let tickets: Vec<MintTicket> = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw_template_contract.addr(),
+                    &QueryMsg::GetMintTickets {
+                        minter_address: USER25.to_string(),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(tickets.len(), 1);
+            assert_eq!(tickets[0].drand_round, Some(ROUND));
+
+            app.update_block(|mut block| block.height += 1);
+
+            let settle_msg = ExecuteMsg::SettleMintTicket {
+                minter_address: USER25.to_string(),
+                height: paid_height,
+            };
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER),
+                    cw_template_contract.addr(),
+                    &settle_msg,
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::DrandBeaconMissing {}
+            );
+
+            // signature for a different round
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER),
+                    cw_template_contract.addr(),
+                    &beacon_msg(ROUND + 1, SIGNATURE),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidDrandBeacon { round: ROUND + 1 }
+            );
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &beacon_msg(ROUND, SIGNATURE),
+                &[],
+            )
+            .unwrap();
+
+            let beacon: Option<DrandBeacon> = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw_template_contract.addr(),
+                    &QueryMsg::GetDrandBeacon { round: ROUND },
+                )
+                .unwrap();
+            assert_eq!(
+                beacon,
+                Some(DrandBeacon {
+                    round: ROUND,
+                    randomness: from_hex(RANDOMNESS),
+                })
+            );
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER),
+                    cw_template_contract.addr(),
+                    &beacon_msg(ROUND, SIGNATURE),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::DrandBeaconExists { round: ROUND }
+            );
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &settle_msg,
+                &[],
+            )
+            .unwrap();
+
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 4);
+        }
+
+        #[test]
+        fn not_with_bundles() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(false, false, true, Some(2), None);
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    cw_template_contract.addr(),
+                    &configure_msg(PUBKEY, PERIOD),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::DrandWithBundleMint {}
+            );
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            // bundles are still drawn as they are paid
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &ExecuteMsg::MintBundle { referrer: None },
+                &[coin(BUNDLE_MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 8);
+        }
+    }
+
+    mod randomness_proxy {
+        use super::*;
+        use crate::msg::{MintType, RandomnessCallback};
+        use crate::state::PendingMint;
+        use crate::ContractError;
+        use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult};
+        use cw_multi_test::AppResponse;
+        use cw_storage_plus::Map;
+        use std::fmt::Debug;
+
+        /// stand-in for a Nois style proxy. it records who asked for each job and
+        /// `Deliver` plays the part of the beacon arriving
+        #[cw_serde]
+        pub struct MockProxyInstantiateMsg {}
+
+        #[cw_serde]
+        pub enum MockProxyExecuteMsg {
+            GetNextRandomness { job_id: String },
+            Deliver { job_id: String, randomness: Binary },
+        }
+
+        #[cw_serde]
+        pub enum MockProxyQueryMsg {
+            Jobs {},
+        }
+
+        const JOBS: Map<String, Addr> = Map::new("jobs");
+
+        fn proxy_instantiate(
+            _deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            _msg: MockProxyInstantiateMsg,
+        ) -> StdResult<Response> {
+            Ok(Response::new())
+        }
+
+        fn proxy_execute(
+            deps: DepsMut,
+            _env: Env,
+            info: MessageInfo,
+            msg: MockProxyExecuteMsg,
+        ) -> StdResult<Response> {
+            match msg {
+                MockProxyExecuteMsg::GetNextRandomness { job_id } => {
+                    JOBS.save(deps.storage, job_id, &info.sender)?;
+                    Ok(Response::new())
+                }
+                MockProxyExecuteMsg::Deliver { job_id, randomness } => {
+                    let requester = JOBS.load(deps.storage, job_id.clone())?;
+                    JOBS.remove(deps.storage, job_id.clone());
+
+                    Ok(Response::new().add_message(WasmMsg::Execute {
+                        contract_addr: requester.into_string(),
+                        msg: to_binary(&ExecuteMsg::ReceiveRandomness {
+                            callback: RandomnessCallback { job_id, randomness },
+                        })?,
+                        funds: vec![],
+                    }))
+                }
+            }
+        }
+
+        fn proxy_query(deps: Deps, _env: Env, msg: MockProxyQueryMsg) -> StdResult<Binary> {
+            match msg {
+                MockProxyQueryMsg::Jobs {} => to_binary(
+                    &JOBS
+                        .keys(deps.storage, None, None, Order::Ascending)
+                        .collect::<StdResult<Vec<String>>>()?,
+                ),
+            }
+        }
+
+        fn mock_proxy_contract() -> Box<dyn Contract<Empty>> {
+            let contract = ContractWrapper::new(proxy_execute, proxy_instantiate, proxy_query);
+            Box::new(contract)
+        }
+
+        fn setup() -> (App, CwTemplateContract, Addr) {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
+
+            let proxy_code_id = app.store_code(mock_proxy_contract());
+            let proxy_addr = app
+                .instantiate_contract(
+                    proxy_code_id,
+                    Addr::unchecked(ADMIN),
+                    &MockProxyInstantiateMsg {},
+                    &[],
+                    "proxy",
+                    None,
+                )
+                .unwrap();
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ConfigureRandomnessProxy {
+                    address: Some(proxy_addr.to_string()),
+                },
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            (app, cw_template_contract, proxy_addr)
+        }
+
+        fn deliver(
+            app: &mut App,
+            proxy_addr: Addr,
+            job_id: &str,
+        ) -> Result<AppResponse, impl Debug> {
+            app.execute_contract(
+                Addr::unchecked(USER),
+                proxy_addr,
+                &MockProxyExecuteMsg::Deliver {
+                    job_id: job_id.to_string(),
+                    randomness: Binary::from(job_id.as_bytes()),
+                },
+                &[],
+            )
+        }
+
+        #[test]
+        fn configure_proxy() {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ConfigureRandomnessProxy {
+                    address: Some(USER.to_string()),
+                },
+                &[],
+            )
+            .unwrap_err();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            // locked once the campaign starts
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ConfigureRandomnessProxy { address: None },
+                &[],
+            )
+            .unwrap_err();
+        }
+
+        #[test]
+        fn mint_is_finalized_in_callback() {
+            let (mut app, cw_template_contract, proxy_addr) = setup();
+            let paid_height = app.block_info().height;
+
+            mint(&mut app, cw_template_contract.addr(), USER25, 1, MINT_PRICE).unwrap();
+            mint(&mut app, cw_template_contract.addr(), USER25, 1, MINT_PRICE).unwrap();
+
+            // paid and tracked, but nothing drawn and the payment is held
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 5);
+            assert_eq!(
+                balance(&app, cw_template_contract.addr()),
+                Uint128::new(MINT_PRICE * 2)
+            );
+
+            let jobs: Vec<String> = app
+                .wrap()
+                .query_wasm_smart(&proxy_addr, &MockProxyQueryMsg::Jobs {})
+                .unwrap();
+            assert_eq!(jobs, vec!["mint-1", "mint-2"]);
+
+            let pending_mints: Vec<PendingMint> = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw_template_contract.addr(),
+                    &QueryMsg::GetPendingMints {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                pending_mints,
+                vec![
+                    PendingMint {
+                        job_id: "mint-1".to_string(),
+                        minter_addr: Addr::unchecked(USER25),
+                        expires_at_height: paid_height + 14_400,
+                        mint_type: MintType::Public,
+                        quantity: 1,
+                        paid: Uint128::new(MINT_PRICE),
+                        denom: NATIVE_DENOM.to_string(),
+                        referrer: None,
+                    },
+                    PendingMint {
+                        job_id: "mint-2".to_string(),
+                        minter_addr: Addr::unchecked(USER25),
+                        expires_at_height: paid_height + 14_400,
+                        mint_type: MintType::Public,
+                        quantity: 1,
+                        paid: Uint128::new(MINT_PRICE),
+                        denom: NATIVE_DENOM.to_string(),
+                        referrer: None,
+                    },
+                ]
+            );
+
+            // only the proxy can finalize
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ReceiveRandomness {
+                    callback: RandomnessCallback {
+                        job_id: "mint-1".to_string(),
+                        randomness: Binary::from(b"chosen by the minter".to_vec()),
+                    },
+                },
+                &[],
+            )
+            .unwrap_err();
+
+            deliver(&mut app, proxy_addr.clone(), "mint-1").unwrap();
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 4);
+
+            let cw721_addrs: Vec<AddressValMsg> = app
+                .wrap()
+                .query_wasm_smart(&cw_template_contract.addr(), &QueryMsg::GetCW721Addrs {})
+                .unwrap();
+            assert_eq!(
+                query_all_tokens(&app, cw721_addrs[0].address.clone()).len(),
+                1
+            );
+
+            let pending_mints: Vec<PendingMint> = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw_template_contract.addr(),
+                    &QueryMsg::GetPendingMints {
+                        start_after: Some("mint-1".to_string()),
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(pending_mints.len(), 1);
+            assert_eq!(pending_mints[0].job_id, "mint-2");
+
+            deliver(&mut app, proxy_addr, "mint-2").unwrap();
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 3);
+
+            // both payments are split once drawn
+            assert!(balance(&app, cw_template_contract.addr()).is_zero());
+        }
+
+        #[test]
+        fn refund_expired_pending_mint() {
+            let (mut app, cw_template_contract, proxy_addr) = setup();
+            let starting_balance = balance(&app, USER25);
+
+            for _ in 0..MAX_PER_ADDRESS_MINT {
+                mint(&mut app, cw_template_contract.addr(), USER25, 1, MINT_PRICE).unwrap();
+            }
+
+            let refund_msg = ExecuteMsg::RefundPendingMint {
+                job_id: "mint-1".to_string(),
+            };
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER10),
+                    cw_template_contract.addr(),
+                    &refund_msg,
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::PendingMintNotExpired {}
+            );
+
+            let pending_mints: Vec<PendingMint> = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw_template_contract.addr(),
+                    &QueryMsg::GetPendingMints {
+                        start_after: None,
+                        limit: Some(1),
+                    },
+                )
+                .unwrap();
+            let expires_at_height = pending_mints[0].expires_at_height;
+            app.update_block(|mut block| block.height = expires_at_height + 1);
+
+            // anyone can refund once the proxy has missed the window
+            app.execute_contract(
+                Addr::unchecked(USER10),
+                cw_template_contract.addr(),
+                &refund_msg,
+                &[],
+            )
+            .unwrap();
+
+            assert_eq!(
+                balance(&app, USER25),
+                starting_balance - Uint128::new(MINT_PRICE * 3)
+            );
+
+            // a late callback finds nothing to draw
+            deliver(&mut app, proxy_addr.clone(), "mint-1").unwrap_err();
+            app.execute_contract(
+                Addr::unchecked(USER10),
+                cw_template_contract.addr(),
+                &refund_msg,
+                &[],
+            )
+            .unwrap_err();
+
+            // the reserved token and the public mint are given back
+            mint(&mut app, cw_template_contract.addr(), USER25, 1, MINT_PRICE).unwrap();
+            deliver(&mut app, proxy_addr, "mint-5").unwrap();
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 4);
+        }
+
+        #[test]
+        fn pending_mints_reserve_supply() {
+            let (mut app, cw_template_contract, proxy_addr) = setup();
+
+            for _ in 0..MAX_PER_ADDRESS_MINT {
+                mint(&mut app, cw_template_contract.addr(), USER25, 1, MINT_PRICE).unwrap();
+            }
+            mint(&mut app, cw_template_contract.addr(), USER10, 1, MINT_PRICE).unwrap();
+
+            // all 5 tokens are spoken for even though none are assigned
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 5);
+            mint(&mut app, cw_template_contract.addr(), USER10, 1, MINT_PRICE).unwrap_err();
+
+            for job_number in 1..=5 {
+                deliver(
+                    &mut app,
+                    proxy_addr.clone(),
+                    &format!("mint-{}", job_number),
+                )
+                .unwrap();
+            }
+
+            // unknown or already finalized jobs are rejected
+            app.execute_contract(
+                proxy_addr.clone(),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ReceiveRandomness {
+                    callback: RandomnessCallback {
+                        job_id: "mint-1".to_string(),
+                        randomness: Binary::from(b"mint-1".to_vec()),
+                    },
+                },
+                &[],
+            )
+            .unwrap_err();
+
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 0);
+
+            let cw721_addrs: Vec<AddressValMsg> = app
+                .wrap()
+                .query_wasm_smart(&cw_template_contract.addr(), &QueryMsg::GetCW721Addrs {})
+                .unwrap();
+            let mut tokens = query_all_tokens(&app, cw721_addrs[0].address.clone());
+            tokens.sort();
+            assert_eq!(tokens, vec!["1", "2", "3", "4", "5"]);
+        }
+    }
+
+    mod delayed_mint {
+        use super::*;
+        use crate::msg::MintType;
+        use crate::state::MintTicket;
+        use crate::ContractError;
+        use sha2::{Digest, Sha256};
+
+        const EXPIRY_BLOCKS: u64 = 10;
+        const SECRET: &str = "not so secret";
+
+        fn setup() -> (App, CwTemplateContract) {
+            let (mut app, cw_template_contract) = setup_without_secret();
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::CommitRandomness {
+                    commitment: format!("{:x}", Sha256::digest(SECRET.as_bytes())),
+                },
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            (app, cw_template_contract)
+        }
+
+        fn setup_without_secret() -> (App, CwTemplateContract) {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ConfigureDelayedMint {
+                    expiry_blocks: Some(EXPIRY_BLOCKS),
+                },
+                &[],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ConfigureDelayedMint {
+                    expiry_blocks: Some(0),
+                },
+                &[],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ConfigureDelayedMint {
+                    expiry_blocks: Some(EXPIRY_BLOCKS),
+                },
+                &[],
+            )
+            .unwrap();
+
+            (app, cw_template_contract)
+        }
+
+        fn query_tickets(app: &App, contract_addr: Addr, minter: &str) -> Vec<MintTicket> {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr,
+                    &QueryMsg::GetMintTickets {
+                        minter_address: minter.to_string(),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap()
+        }
+
+        #[test]
+        fn settle_in_later_block() {
+            let (mut app, cw_template_contract) = setup();
+            let paid_height = app.block_info().height;
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &mint_msg(1, None, None, None),
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            // one ticket per address per block
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &mint_msg(1, None, None, None),
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            assert_eq!(
+                query_tickets(&app, cw_template_contract.addr(), USER25),
+                vec![MintTicket {
+                    minter_addr: Addr::unchecked(USER25),
+                    height: paid_height,
+                    expires_at_height: paid_height + EXPIRY_BLOCKS,
+                    mint_type: MintType::Public,
+                    quantity: 1,
+                    paid: Uint128::from(MINT_PRICE),
+                    denom: Some(NATIVE_DENOM.to_string()),
+                    referrer: None,
+                    commitment: Some(format!("{:x}", Sha256::digest(SECRET.as_bytes()))),
+                    drand_round: None,
+                }]
+            );
+
+            // nothing drawn and the payment is held by the minter
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 5);
+            assert_eq!(
+                balance(&app, cw_template_contract.addr()),
+                Uint128::from(MINT_PRICE)
+            );
+
+            let settle_msg = ExecuteMsg::SettleMintTicket {
+                minter_address: USER25.to_string(),
+                height: paid_height,
+            };
+
+            // not in the block it was paid in
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &settle_msg,
+                &[],
+            )
+            .unwrap_err();
+
+            app.update_block(|mut block| block.height += 1);
+
+            // not expired, so no refund
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &ExecuteMsg::RefundMintTicket {
+                    minter_address: USER25.to_string(),
+                    height: paid_height,
+                },
+                &[],
+            )
+            .unwrap_err();
+
+            // drawn with the secret once it is revealed
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER),
+                    cw_template_contract.addr(),
+                    &settle_msg,
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::RandomnessNotRevealed {}
+            );
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::RevealRandomness {
+                    secret: SECRET.to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+
+            // any cranker can settle
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &settle_msg,
+                &[],
+            )
+            .unwrap();
+
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 4);
+            assert_eq!(
+                query_tickets(&app, cw_template_contract.addr(), USER25),
+                vec![]
+            );
+            assert_eq!(balance(&app, cw_template_contract.addr()), Uint128::zero());
+
+            // already settled
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &settle_msg,
+                &[],
+            )
+            .unwrap_err();
+        }
+
+        #[test]
+        fn needs_hidden_randomness() {
+            let (mut app, cw_template_contract) = setup_without_secret();
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            // the block a ticket is settled in is up to whoever settles it
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    cw_template_contract.addr(),
+                    &mint_msg(1, None, None, None),
+                    &[coin(MINT_PRICE, NATIVE_DENOM)],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::HiddenRandomnessUnavailable {}
+            );
+        }
+
+        #[test]
+        fn refund_expired_ticket() {
+            let (mut app, cw_template_contract) = setup();
+            let paid_height = app.block_info().height;
+
+            let balance_before = balance(&app, USER10);
+
+            app.execute_contract(
+                Addr::unchecked(USER10),
+                cw_template_contract.addr(),
+                &mint_msg(1, None, None, None),
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| block.height += EXPIRY_BLOCKS + 1);
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &ExecuteMsg::SettleMintTicket {
+                    minter_address: USER10.to_string(),
+                    height: paid_height,
+                },
+                &[],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &ExecuteMsg::RefundMintTicket {
+                    minter_address: USER10.to_string(),
+                    height: paid_height,
+                },
+                &[],
+            )
+            .unwrap();
+
+            assert_eq!(balance(&app, USER10), balance_before);
+            assert_eq!(
+                query_tickets(&app, cw_template_contract.addr(), USER10),
+                vec![]
+            );
+
+            // the public mint is handed back
+            let address_mints: AddressValMsg = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw_template_contract.addr(),
+                    &QueryMsg::CheckAddressMints {
+                        minter_address: USER10.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(address_mints.value, 0);
+
+            // and so is the reserved supply
+            for _ in 0..MAX_PER_ADDRESS_MINT {
+                app.execute_contract(
+                    Addr::unchecked(USER25),
+                    cw_template_contract.addr(),
+                    &mint_msg(1, None, None, None),
+                    &[coin(MINT_PRICE, NATIVE_DENOM)],
+                )
+                .unwrap();
+                app.update_block(|mut block| block.height += 1);
+            }
+
+            app.execute_contract(
+                Addr::unchecked(USER10),
+                cw_template_contract.addr(),
+                &mint_msg(1, None, None, None),
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            assert_eq!(
+                query_tickets(&app, cw_template_contract.addr(), USER25).len(),
+                4
+            );
+        }
+    }
+
+    mod lazy_shuffle {
+        use super::*;
+        use crate::contract::{
+            get_shuffled_token_position, randomize_and_draw_index, remove_token_from_draw,
+            shuffle_token_ids,
+        };
+        use crate::state::{
+            COLLECTION_CURRENT_TOKEN_SUPPLY, CURRENT_TOKEN_SUPPLY, CW721_SHUFFLED_TOKEN_IDS,
+            LEGACY_CW721_SHUFFLED_TOKEN_IDS,
+        };
+        use cosmwasm_std::testing::{mock_env, MockStorage};
+        use cosmwasm_std::{Order, Record, Storage};
+        use std::cell::Cell;
+        use std::collections::HashSet;
+
+        // cosmos-sdk's default KVStore gas schedule
+        const READ_COST_FLAT: u64 = 1_000;
+        const READ_COST_PER_BYTE: u64 = 3;
+        const WRITE_COST_FLAT: u64 = 2_000;
+        const WRITE_COST_PER_BYTE: u64 = 30;
+        const DELETE_COST: u64 = 1_000;
+
+        const COLLECTION_ID: u64 = 101;
+
+        /// MockStorage that charges every access like the sdk's gas kv store
+        #[derive(Default)]
+        struct GasMeteredStorage {
+            inner: MockStorage,
+            gas_used: Cell<u64>,
+        }
+
+        impl GasMeteredStorage {
+            fn charge(&self, gas: u64) {
+                self.gas_used.set(self.gas_used.get() + gas);
+            }
+        }
+
+        impl Storage for GasMeteredStorage {
+            fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+                let value = self.inner.get(key);
+                let bytes = key.len() + value.as_ref().map_or(0, |v| v.len());
+                self.charge(READ_COST_FLAT + READ_COST_PER_BYTE * bytes as u64);
+                value
+            }
+
+            fn range<'a>(
+                &'a self,
+                start: Option<&[u8]>,
+                end: Option<&[u8]>,
+                order: Order,
+            ) -> Box<dyn Iterator<Item = Record> + 'a> {
+                self.inner.range(start, end, order)
+            }
+
+            fn set(&mut self, key: &[u8], value: &[u8]) {
+                let bytes = key.len() + value.len();
+                self.charge(WRITE_COST_FLAT + WRITE_COST_PER_BYTE * bytes as u64);
+                self.inner.set(key, value);
+            }
+
+            fn remove(&mut self, key: &[u8]) {
+                self.charge(DELETE_COST);
+                self.inner.remove(key);
+            }
+        }
+
+        fn init_supply(storage: &mut dyn Storage, supply: u32) {
+            COLLECTION_CURRENT_TOKEN_SUPPLY
+                .save(storage, COLLECTION_ID, &supply)
+                .unwrap();
+            CURRENT_TOKEN_SUPPLY.save(storage, &supply).unwrap();
+        }
+
+        fn draw_position(height: u64, remaining: u32) -> u32 {
+            let mut env = mock_env();
+            env.block.height = height;
+
+            randomize_and_draw_index(&env, Addr::unchecked(USER), COLLECTION_ID, remaining, "")
+                .unwrap()
+        }
+
+        /// the pre 0.2.0 draw: load the whole vec, swap remove and write it all back
+        fn legacy_draw(storage: &mut dyn Storage, position: u32) -> u32 {
+            let mut token_ids = LEGACY_CW721_SHUFFLED_TOKEN_IDS
+                .load(storage, COLLECTION_ID)
+                .unwrap();
+            let token_id = token_ids.swap_remove(position as usize);
+
+            LEGACY_CW721_SHUFFLED_TOKEN_IDS
+                .save(storage, COLLECTION_ID, &token_ids)
+                .unwrap();
+            init_supply(storage, token_ids.len() as u32);
+
+            token_id
+        }
+
+        /// average gas of `draws` draws after `warmup` draws have filled the swap table
+        fn sparse_draw_gas(supply: u32, warmup: u32, draws: u32) -> u64 {
+            let mut storage = GasMeteredStorage::default();
+            init_supply(&mut storage, supply);
+
+            for i in 0..(warmup + draws) {
+                if i == warmup {
+                    storage.gas_used.set(0);
+                }
+
+                let position = draw_position(i as u64, supply - i);
+                remove_token_from_draw(&mut storage, COLLECTION_ID, position).unwrap();
+            }
+
+            storage.gas_used.get() / draws as u64
+        }
+
+        fn legacy_draw_gas(supply: u32, warmup: u32, draws: u32) -> u64 {
+            let mut storage = GasMeteredStorage::default();
+            LEGACY_CW721_SHUFFLED_TOKEN_IDS
+                .save(
+                    &mut storage,
+                    COLLECTION_ID,
+                    &(1..=supply).collect::<Vec<u32>>(),
+                )
+                .unwrap();
+
+            for i in 0..(warmup + draws) {
+                if i == warmup {
+                    storage.gas_used.set(0);
+                }
+
+                let position = draw_position(i as u64, supply - i);
+                legacy_draw(&mut storage, position);
+            }
+
+            storage.gas_used.get() / draws as u64
+        }
+
+        #[test]
+        fn draws_every_token_once() {
+            let supply: u32 = 1_000;
+            let mut storage = MockStorage::new();
+            init_supply(&mut storage, supply);
+
+            let mut drawn: HashSet<u32> = HashSet::new();
+
+            // leave one token so the collection is never emptied
+            for i in 0..(supply - 1) {
+                let position = draw_position(i as u64, supply - i);
+                let token_id =
+                    remove_token_from_draw(&mut storage, COLLECTION_ID, position).unwrap();
+
+                assert!((1..=supply).contains(&token_id));
+                assert!(drawn.insert(token_id));
+                assert_eq!(
+                    get_shuffled_token_position(&storage, COLLECTION_ID, token_id).unwrap(),
+                    None
+                );
+            }
+
+            assert_eq!(
+                COLLECTION_CURRENT_TOKEN_SUPPLY
+                    .load(&storage, COLLECTION_ID)
+                    .unwrap(),
+                1
+            );
+            assert_eq!(CURRENT_TOKEN_SUPPLY.load(&storage).unwrap(), 1);
+
+            // the last token is the only one not drawn, and it sits at position 0
+            let last_token_id = (1..=supply).find(|t| !drawn.contains(t)).unwrap();
+            assert_eq!(
+                get_shuffled_token_position(&storage, COLLECTION_ID, last_token_id).unwrap(),
+                Some(0)
+            );
+
+            // the swap table never holds more entries than remaining positions
+            let entries = CW721_SHUFFLED_TOKEN_IDS
+                .prefix(COLLECTION_ID)
+                .keys(&storage, None, None, Order::Ascending)
+                .count();
+            assert!(entries <= 1);
+
+            // out of range positions are rejected
+            remove_token_from_draw(&mut storage, COLLECTION_ID, 1).unwrap_err();
+        }
+
+        #[test]
+        fn untouched_tokens_stay_claimable() {
+            let mut storage = MockStorage::new();
+            init_supply(&mut storage, 10);
+
+            // drawing position 2 moves token 10 into it
+            assert_eq!(
+                remove_token_from_draw(&mut storage, COLLECTION_ID, 2).unwrap(),
+                3
+            );
+
+            assert_eq!(
+                get_shuffled_token_position(&storage, COLLECTION_ID, 10).unwrap(),
+                Some(2)
+            );
+            assert_eq!(
+                get_shuffled_token_position(&storage, COLLECTION_ID, 3).unwrap(),
+                None
+            );
+            assert_eq!(
+                get_shuffled_token_position(&storage, COLLECTION_ID, 5).unwrap(),
+                Some(4)
+            );
+            assert_eq!(
+                get_shuffled_token_position(&storage, COLLECTION_ID, 0).unwrap(),
+                None
+            );
+
+            // pulling a specific token out of the draw works through the reverse lookup
+            let position = get_shuffled_token_position(&storage, COLLECTION_ID, 10)
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                remove_token_from_draw(&mut storage, COLLECTION_ID, position).unwrap(),
+                10
+            );
+            assert_eq!(
+                get_shuffled_token_position(&storage, COLLECTION_ID, 9).unwrap(),
+                Some(2)
+            );
+        }
+
+        #[test]
+        fn gas_comparison() {
+            let small_legacy = legacy_draw_gas(100, 20, 20);
+            let small_sparse = sparse_draw_gas(100, 20, 20);
+            let large_legacy = legacy_draw_gas(10_000, 200, 20);
+            let large_sparse = sparse_draw_gas(10_000, 200, 20);
+
+            println!(
+                "gas per draw. supply 100: legacy {} sparse {}. supply 10000: legacy {} sparse {}",
+                small_legacy, small_sparse, large_legacy, large_sparse
+            );
+
+            // the vec layout pays for every remaining token on each draw
+            assert!(large_legacy > small_legacy * 20);
+            assert!(large_sparse * 20 < large_legacy);
+
+            // the swap table costs the same no matter the supply
+            assert!(large_sparse < small_sparse * 2);
+        }
+
+        #[test]
+        fn paged_shuffle_keeps_every_token() {
+            let supply: u32 = 100;
+            let mut storage = MockStorage::new();
+            init_supply(&mut storage, supply);
+
+            let env = mock_env();
+            let mut position = supply - 1;
+            let mut pages = 0;
+
+            while position > 0 {
+                position = shuffle_token_ids(
+                    &mut storage,
+                    &env,
+                    Addr::unchecked(USER),
+                    COLLECTION_ID,
+                    position,
+                    7,
+                )
+                .unwrap();
+                pages += 1;
+            }
+
+            assert_eq!(pages, 15);
+
+            // every token still sits at exactly one position
+            let positions: HashSet<u32> = (1..=supply)
+                .map(|token_id| {
+                    get_shuffled_token_position(&storage, COLLECTION_ID, token_id)
+                        .unwrap()
+                        .unwrap()
+                })
+                .collect();
+            assert_eq!(positions.len(), supply as usize);
+            assert!(positions.iter().all(|p| *p < supply));
+        }
+    }
+
+    mod incremental_shuffle {
+        use super::*;
+        use crate::state::ShuffleProgress;
+
+        fn query_progress(app: &App, contract_addr: Addr) -> Option<ShuffleProgress> {
+            app.wrap()
+                .query_wasm_smart(contract_addr, &QueryMsg::GetShuffleProgress {})
+                .unwrap()
+        }
+
+        #[test]
+        fn shuffle_across_calls() {
+            // collections of 50, 50 and 6 tokens, 49 + 49 + 5 swaps
+            let (mut app, cw_template_contract) =
+                proper_instantiate(true, true, true, Some(3), Some(50));
+
+            assert_eq!(query_progress(&app, cw_template_contract.addr()), None);
+
+            app.update_block(|mut block| block.height += 1);
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ShuffleTokenOrder { limit: Some(40) },
+                &[],
+            )
+            .unwrap();
+
+            assert_eq!(
+                query_progress(&app, cw_template_contract.addr()),
+                Some(ShuffleProgress {
+                    collection_id: 101,
+                    position: 9,
+                    swaps_done: 40,
+                    total_swaps: 103,
+                })
+            );
+
+            // minting waits for the shuffle to finish
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            let mint_msg = mint_msg(1, None, None, None);
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &mint_msg,
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &ExecuteMsg::MintBundle { referrer: None },
+                &[coin(BUNDLE_MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            // anyone can continue a started shuffle without paying the fee
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ShuffleTokenOrder { limit: Some(40) },
+                &[],
+            )
+            .unwrap();
+
+            assert_eq!(
+                query_progress(&app, cw_template_contract.addr()),
+                Some(ShuffleProgress {
+                    collection_id: 102,
+                    position: 18,
+                    swaps_done: 80,
+                    total_swaps: 103,
+                })
+            );
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ShuffleTokenOrder { limit: None },
+                &[],
+            )
+            .unwrap();
+
+            assert_eq!(query_progress(&app, cw_template_contract.addr()), None);
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &mint_msg,
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 105);
+
+            // non admins can't start a new one once minting is open
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ShuffleTokenOrder { limit: None },
+                &[],
+            )
+            .unwrap_err();
+        }
+    }
+
+    mod provenance {
+        use super::*;
+        use crate::provenance::provenance_hash;
+
+        fn query_collection_info(app: &App, contract_addr: Addr) -> CollectionInfo {
+            let collection_infos: Vec<(u64, CollectionInfo)> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr,
+                    &QueryMsg::GetCw721CollectionInfo {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+
+            collection_infos[0].1.clone()
+        }
+
+        #[test]
+        fn hash_of_ordered_metadata() {
+            let metadata = vec![
+                br#"{"name":"1"}"#.to_vec(),
+                br#"{"name":"2"}"#.to_vec(),
+                br#"{"name":"3"}"#.to_vec(),
+            ];
+
+            assert_eq!(
+                provenance_hash(&metadata),
+                "db2dcd615784f066287498a3f480a307fb44e3e11732aa7def2874dc39432e4e"
+            );
+
+            // any change in order gives a different hash
+            let mut reordered = metadata.clone();
+            reordered.swap(0, 2);
+            assert_ne!(provenance_hash(&reordered), provenance_hash(&metadata));
+        }
+
+        #[test]
+        fn provenance_locked_at_start_and_mint_order_recorded() {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
+
+            assert_eq!(
+                query_collection_info(&app, cw_template_contract.addr()).provenance_hash,
+                None
+            );
+
+            let hash = provenance_hash(&[b"1", b"2", b"3", b"4", b"5"]);
+
+            let update_msg = ExecuteMsg::UpdateProvenanceHash {
+                collection_id: 101,
+                provenance_hash: hash.to_uppercase(),
+            };
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &update_msg,
+                &[],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::UpdateProvenanceHash {
+                    collection_id: 101,
+                    provenance_hash: "not a hash".to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::UpdateProvenanceHash {
+                    collection_id: 999,
+                    provenance_hash: hash.clone(),
+                },
+                &[],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &update_msg,
+                &[],
+            )
+            .unwrap();
+
+            assert_eq!(
+                query_collection_info(&app, cw_template_contract.addr()).provenance_hash,
+                Some(hash)
+            );
+
+            // locked once the public mint starts
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &update_msg,
+                &[],
+            )
+            .unwrap_err();
+
+            for _ in 0..2 {
+                app.execute_contract(
+                    Addr::unchecked(USER25),
+                    cw_template_contract.addr(),
+                    &mint_msg(1, None, None, None),
+                    &[coin(MINT_PRICE, NATIVE_DENOM)],
+                )
+                .unwrap();
+            }
+
+            let mint_order: Vec<(u32, u32)> = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw_template_contract.addr(),
+                    &QueryMsg::GetTokenMintOrder {
+                        collection_id: 101,
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+
+            let cw721_addrs: Vec<AddressValMsg> = app
+                .wrap()
+                .query_wasm_smart(&cw_template_contract.addr(), &QueryMsg::GetCW721Addrs {})
+                .unwrap();
+
+            let mut minted: Vec<String> = mint_order
+                .iter()
+                .map(|(token_id, _)| token_id.to_string())
+                .collect();
+            let mut tokens = query_all_tokens(&app, cw721_addrs[0].address.clone());
+            minted.sort();
+            tokens.sort();
+            assert_eq!(minted, tokens);
+
+            let mut orders: Vec<u32> = mint_order.iter().map(|(_, order)| *order).collect();
+            orders.sort();
+            assert_eq!(orders, vec![1, 2]);
+        }
+    }
+
+    mod draw_history {
+        use super::*;
+        use crate::contract::draw_index_from_seed;
+        use crate::state::{DrawKind, DrawRecord};
+
+        fn query_history(
+            app: &App,
+            contract_addr: Addr,
+            start_after: Option<u64>,
+        ) -> Vec<(u64, DrawRecord)> {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr,
+                    &QueryMsg::GetDrawHistory {
+                        start_after,
+                        limit: None,
+                    },
+                )
+                .unwrap()
+        }
+
+        #[test]
+        fn draws_can_be_rerun_from_history() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(true, true, true, Some(2), None);
+
+            app.update_block(|mut block| block.height += 1);
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::ShuffleTokenOrder { limit: None },
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &mint_msg(1, None, None, None),
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            let history = query_history(&app, cw_template_contract.addr(), None);
+            assert_eq!(history.len(), 3);
+
+            // one shuffle page per collection
+            for (draw_id, collection_id) in [(1u64, 101u64), (2, 102)] {
+                assert_eq!(history[draw_id as usize - 1].0, draw_id);
+                match &history[draw_id as usize - 1].1.kind {
+                    DrawKind::Shuffle {
+                        collection_id: shuffled_collection_id,
+                        from_position,
+                        to_position,
+                        ..
+                    } => {
+                        assert_eq!(*shuffled_collection_id, collection_id);
+                        assert_eq!(*from_position, 4);
+                        assert_eq!(*to_position, 0);
+                    }
+                    kind => panic!("unexpected draw {:?}", kind),
+                }
+            }
+
+            let (draw_id, record) = history[2].clone();
+            assert_eq!(draw_id, 3);
+            assert_eq!(record.height, app.block_info().height);
+
+            match record.kind {
+                DrawKind::Mint {
+                    collection_seed,
+                    available_collection_ids,
+                    collection_weights,
+                    collection_id,
+                    index_seed,
+                    supply,
+                    index,
+                } => {
+                    assert_eq!(available_collection_ids, vec![101, 102]);
+                    assert_eq!(collection_weights, None);
+                    assert_eq!(supply, 5);
+
+                    let collection_draw = draw_index_from_seed(
+                        collection_seed.as_bytes(),
+                        available_collection_ids.len() as u32,
+                    );
+                    assert_eq!(
+                        available_collection_ids[collection_draw as usize],
+                        collection_id
+                    );
+                    assert_eq!(draw_index_from_seed(index_seed.as_bytes(), supply), index);
+                }
+                kind => panic!("unexpected draw {:?}", kind),
+            }
+
+            assert_eq!(
+                query_history(&app, cw_template_contract.addr(), Some(2)),
+                vec![history[2].clone()]
+            );
+        }
+    }
+
+    mod dutch_auction {
+        use super::*;
+        use crate::msg::AuctionPriceResponse;
+        use crate::state::{AuctionPurchase, DutchAuction};
+        use sha2::{Digest, Sha256};
+
+        const START_PRICE: u128 = 10_000_000;
+        const FLOOR_PRICE: u128 = 2_000_000;
+        const STEP: u128 = 2_000_000;
+        const DECAY_INTERVAL: u64 = 600;
+
+        fn query_price(app: &App, contract_addr: Addr) -> AuctionPriceResponse {
+            app.wrap()
+                .query_wasm_smart(contract_addr, &QueryMsg::GetAuctionPrice {})
+                .unwrap()
+        }
+
+        #[test]
+        fn price_decays_and_rebates_settle_at_clearing_price() {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
+            let contract_addr = cw_template_contract.addr();
+
+            let auction = DutchAuction {
+                start_price: Uint128::from(START_PRICE),
+                floor_price: Uint128::from(FLOOR_PRICE),
+                decay_interval: DECAY_INTERVAL,
+                step: Uint128::from(STEP),
+                rebate: true,
+            };
+
+            // floor has to sit below the start price
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                dutch_auction: Some(DutchAuction {
+                    floor_price: Uint128::from(START_PRICE),
+                    ..auction.clone()
+                }),
+                ..msg
+            })
+            .unwrap_err();
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                dutch_auction: Some(auction.clone()),
+                ..msg
+            })
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            assert_eq!(
+                query_price(&app, contract_addr.clone()),
+                AuctionPriceResponse {
+                    price: Uint128::from(START_PRICE),
+                    next_price: Some(Uint128::from(START_PRICE - STEP)),
+                    next_drop_time: Some(Timestamp::from_seconds(MINT_START_TIME + DECAY_INTERVAL)),
+                    clearing_price: None,
+                }
+            );
+
+            // the fixed mint price no longer applies
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, None, None, None),
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            mint(&mut app, contract_addr.clone(), USER25, 1, START_PRICE).unwrap();
+
+            app.update_block(|mut block| {
+                block.time = Timestamp::from_seconds(MINT_START_TIME + DECAY_INTERVAL + 1)
+            });
+            mint(
+                &mut app,
+                contract_addr.clone(),
+                USER25,
+                1,
+                START_PRICE - STEP,
+            )
+            .unwrap();
+
+            app.update_block(|mut block| {
+                block.time = Timestamp::from_seconds(MINT_START_TIME + 2 * DECAY_INTERVAL)
+            });
+            mint(
+                &mut app,
+                contract_addr.clone(),
+                USER10,
+                START_PRICE - 2 * STEP,
+            );
+
+            // everything above the floor is held back
+            assert_eq!(
+                balance(&app, contract_addr.clone()),
+                Uint128::from(START_PRICE + (START_PRICE - STEP) + (START_PRICE - 2 * STEP))
+                    - Uint128::from(3 * FLOOR_PRICE)
+            );
+            let purchase: Option<AuctionPurchase> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetAuctionPurchase {
+                        minter_address: USER25.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                purchase,
+                Some(AuctionPurchase {
+                    count: 2,
+                    paid: Uint128::from(START_PRICE + START_PRICE - STEP),
+                })
+            );
+
+            let settle_msg = ExecuteMsg::SettleAuctionRebate {
+                minter_address: USER25.to_string(),
+            };
+
+            // the clearing price isn't known while the auction is live
+            app.execute_contract(
+                Addr::unchecked(USER),
+                contract_addr.clone(),
+                &settle_msg,
+                &[],
+            )
+            .unwrap_err();
+
+            // ends without selling out, so the last price paid clears
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_END_TIME));
+
+            let clearing_price = START_PRICE - 2 * STEP;
+            assert_eq!(
+                query_price(&app, contract_addr.clone()).clearing_price,
+                Some(Uint128::from(clearing_price))
+            );
+
+            let user25_balance = balance(&app, USER25);
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                contract_addr.clone(),
+                &settle_msg,
+                &[],
+            )
+            .unwrap();
+
+            assert_eq!(
+                balance(&app, USER25),
+                user25_balance
+                    + Uint128::from(START_PRICE + (START_PRICE - STEP) - 2 * clearing_price)
+            );
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                contract_addr.clone(),
+                &settle_msg,
+                &[],
+            )
+            .unwrap_err();
+
+            // paid the clearing price, so only the creators' share is released
+            let user10_balance = balance(&app, USER10);
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                contract_addr.clone(),
+                &ExecuteMsg::SettleAuctionRebate {
+                    minter_address: USER10.to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+
+            assert_eq!(balance(&app, USER10), user10_balance);
+            assert_eq!(balance(&app, contract_addr), Uint128::zero());
+        }
+
+        #[test]
+        fn clearing_price_holds_once_sold_out() {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
+            let contract_addr = cw_template_contract.addr();
+
+            let auction = DutchAuction {
+                start_price: Uint128::from(START_PRICE),
+                floor_price: Uint128::from(FLOOR_PRICE),
+                decay_interval: DECAY_INTERVAL,
+                step: Uint128::from(STEP),
+                rebate: true,
+            };
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                dutch_auction: Some(auction),
+                ..msg
+            })
+            .unwrap();
+
+            // tickets hold supply back until they are settled or refunded
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &ExecuteMsg::ConfigureDelayedMint {
+                    expiry_blocks: Some(10),
+                },
+                &[],
+            )
+            .unwrap();
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &ExecuteMsg::CommitRandomness {
+                    commitment: format!("{:x}", Sha256::digest(b"not so secret")),
+                },
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            for _ in 0..MAX_PER_ADDRESS_MINT {
+                mint(&mut app, contract_addr.clone(), USER25, 1, START_PRICE).unwrap();
+                app.update_block(|mut block| block.height += 1);
+            }
+
+            assert_eq!(
+                query_price(&app, contract_addr.clone()).clearing_price,
+                None
+            );
+
+            let paid_height = app.block_info().height;
+            mint(&mut app, contract_addr.clone(), USER10, 1, START_PRICE).unwrap();
+
+            assert_eq!(
+                query_price(&app, contract_addr.clone()).clearing_price,
+                Some(Uint128::from(START_PRICE))
+            );
+
+            app.update_block(|mut block| block.height += 11);
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                contract_addr.clone(),
+                &ExecuteMsg::RefundMintTicket {
+                    minter_address: USER10.to_string(),
+                    height: paid_height,
+                },
+                &[],
+            )
+            .unwrap();
+
+            // a token went back, but the auction already cleared
+            assert_eq!(
+                query_price(&app, contract_addr).clearing_price,
+                Some(Uint128::from(START_PRICE))
+            );
+        }
+    }
+
+    mod bonding_curve {
+        use super::*;
+        use crate::contract::bonding_curve_price;
+        use crate::msg::MintPriceResponse;
+        use crate::state::{BondingCurve, DutchAuction};
+
+        const BASE_PRICE: u128 = 1_000_000;
+        const SLOPE: u128 = 500_000;
+
+        fn quote(app: &App, contract_addr: Addr, quantity: u32) -> Uint128 {
+            let res: MintPriceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr,
+                    &QueryMsg::GetMintPrice {
+                        quantity,
+                        minter_address: None,
+                        collection_id: None,
+                        denom: None,
+                    },
+                )
+                .unwrap();
+
+            res.price
+        }
+
+        fn setup(curve: BondingCurve) -> (App, Addr) {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(true, true, true, Some(2), None);
+            let contract_addr = cw_template_contract.addr();
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                bonding_curve: Some(curve),
+                ..msg
+            })
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            (app, contract_addr)
+        }
+
+        #[test]
+        fn curve_prices() {
+            let linear = BondingCurve::Linear {
+                base_price: Uint128::from(BASE_PRICE),
+                slope: Uint128::from(SLOPE),
+            };
+            assert_eq!(
+                bonding_curve_price(&linear, 0).unwrap(),
+                Uint128::from(BASE_PRICE)
+            );
+            assert_eq!(
+                bonding_curve_price(&linear, 10).unwrap(),
+                Uint128::from(BASE_PRICE + 10 * SLOPE)
+            );
+
+            // 10% more per token sold
+            let exponential = BondingCurve::Exponential {
+                base_price: Uint128::from(BASE_PRICE),
+                growth_bps: 1_000,
+            };
+            assert_eq!(
+                bonding_curve_price(&exponential, 0).unwrap(),
+                Uint128::from(BASE_PRICE)
+            );
+            assert_eq!(
+                bonding_curve_price(&exponential, 2).unwrap(),
+                Uint128::new(1_210_000)
+            );
+
+            // doubling past the top of the range errors instead of panicking
+            let doubling = BondingCurve::Exponential {
+                base_price: Uint128::MAX,
+                growth_bps: 10_000,
+            };
+            bonding_curve_price(&doubling, 1).unwrap_err();
+        }
+
+        #[test]
+        fn price_rises_with_supply_sold() {
+            let (mut app, contract_addr) = setup(BondingCurve::Linear {
+                base_price: Uint128::from(BASE_PRICE),
+                slope: Uint128::from(SLOPE),
+            });
+
+            assert_eq!(
+                quote(&app, contract_addr.clone(), 3),
+                Uint128::from(3 * BASE_PRICE + 3 * SLOPE)
+            );
+
+            // quotes stop at the remaining supply
+            app.wrap()
+                .query_wasm_smart::<MintPriceResponse>(
+                    contract_addr.clone(),
+                    &QueryMsg::GetMintPrice {
+                        quantity: u32::MAX,
+                        minter_address: None,
+                        collection_id: None,
+                        denom: None,
+                    },
+                )
+                .unwrap_err();
+
+            let mint_msg = mint_msg(1, None, None, None);
+
+            // the fixed mint price no longer applies
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg,
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg,
+                &[coin(BASE_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            assert_eq!(
+                quote(&app, contract_addr.clone(), 1),
+                Uint128::from(BASE_PRICE + SLOPE)
+            );
+
+            // a bundle pays the curve price of one token per collection
+            let bundle_price = quote(&app, contract_addr.clone(), 2);
+            assert_eq!(bundle_price, Uint128::from(2 * BASE_PRICE + 3 * SLOPE));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &ExecuteMsg::MintBundle { referrer: None },
+                &[coin(BUNDLE_MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &ExecuteMsg::MintBundle { referrer: None },
+                &[coin(bundle_price.u128(), NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            assert_eq!(
+                quote(&app, contract_addr, 1),
+                Uint128::from(BASE_PRICE + 3 * SLOPE)
+            );
+        }
+
+        #[test]
+        fn curve_and_auction_conflict() {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
+            let contract_addr = cw_template_contract.addr();
+
+            update_config(&mut app, contract_addr, |msg| BaseInitMsg {
+                bonding_curve: Some(BondingCurve::Linear {
+                    base_price: Uint128::from(BASE_PRICE),
+                    slope: Uint128::from(SLOPE),
+                }),
+                dutch_auction: Some(DutchAuction {
+                    start_price: Uint128::from(2 * BASE_PRICE),
+                    floor_price: Uint128::from(BASE_PRICE),
+                    decay_interval: 600,
+                    step: Uint128::from(SLOPE),
+                    rebate: false,
+                }),
+                ..msg
+            })
+            .unwrap_err();
+        }
+    }
+
+    mod multi_quantity_mint {
+        use super::*;
+        use airdropper::msg::CheckAirdropPromisedMintResponse;
+        use whitelist::msg::CheckWhitelistResponse;
+
+        #[test]
+        fn public_mint_quantity() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(true, true, false, Some(2), None);
+            let contract_addr = cw_template_contract.addr();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(0, None, None, None),
+                &[],
+            )
+            .unwrap_err();
+
+            // pays for 2 out of 3
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(3, None, None, None),
+                &[coin(2 * MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(3, None, None, None),
+                &[coin(3 * MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            let address_mints: AddressValMsg = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::CheckAddressMints {
+                        minter_address: USER25.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(address_mints.value, 3);
+
+            // 3 + 2 goes over `MAX_PER_ADDRESS_MINT`
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(2, None, None, None),
+                &[coin(2 * MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, None, None, None),
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            let cw721_addrs: Vec<AddressValMsg> = app
+                .wrap()
+                .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetCW721Addrs {})
+                .unwrap();
+            let minted: usize = cw721_addrs
+                .into_iter()
+                .map(|cw721| query_all_tokens(&app, cw721.address).len())
+                .sum();
+            assert_eq!(minted, 4);
+
+            assert_eq!(remaining_tokens(&app, contract_addr), 6);
+        }
+
+        #[test]
+        fn quantity_over_remaining_supply() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(true, true, false, None, Some(2));
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &mint_msg(3, None, None, None),
+                &[coin(3 * MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &mint_msg(2, None, None, None),
+                &[coin(2 * MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn whitelist_and_promised_quantity() {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
+            let contract_addr = cw_template_contract.addr();
+
+            let config: ConfigResponse = app
+                .wrap()
+                .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetConfig {})
+                .unwrap();
+            let whitelist_addr = config.whitelist_addr.unwrap();
+            let airdropper_addr = config.airdropper_addr.unwrap();
+
+            app.execute_contract(
+                contract_addr.clone(),
+                whitelist_addr.clone(),
+                &WhitelistExecuteMsg::AddToWhitelist(vec![USER25.to_string()]),
+                &[],
+            )
+            .unwrap();
+
+            app.execute_contract(
+                contract_addr.clone(),
+                airdropper_addr.clone(),
+                &AirdropperExecuteMsg::AddPromisedMints(vec![AD_AddressValMsg {
+                    address: USER2.to_owned(),
+                    value: 2,
+                }]),
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| {
+                block.time = Timestamp::from_seconds(WHITELIST_START_TIME + 1)
+            });
+
+            // the whitelist allows 2 per address
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(3, None, None, None),
+                &[coin(3 * WL_MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(2, None, None, None),
+                &[coin(2 * WL_MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            let check_wl: CheckWhitelistResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    whitelist_addr,
+                    &WhitelistQueryMsg::CheckWhitelist {
+                        minter_address: USER25.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(check_wl.current_mint_count, 2);
+
+            // USER2 was promised 2 mints
+            app.execute_contract(
+                Addr::unchecked(USER2),
+                contract_addr.clone(),
+                &ExecuteMsg::Mint {
+                    is_promised_mint: true,
+                    minter_address: None,
+                    quantity: Some(3),
+                    collection_id: None,
+                    referrer: None,
+                    code: None,
+                },
+                &[],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER2),
+                contract_addr,
+                &ExecuteMsg::Mint {
+                    is_promised_mint: true,
+                    minter_address: None,
+                    quantity: Some(2),
+                    collection_id: None,
+                    referrer: None,
+                    code: None,
+                },
+                &[],
+            )
+            .unwrap();
+
+            let promised_mints: CheckAirdropPromisedMintResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    airdropper_addr,
+                    &AirdropperQueryMsg::CheckAddressPromisedMints {
+                        minter_address: USER2.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(promised_mints.claimed_mint_count, 2);
+        }
+    }
+
+    mod bulk_discounts {
+        use super::*;
+        use crate::msg::MintPriceResponse;
+        use crate::state::{BulkDiscounts, DiscountTier, DutchAuction};
+
+        fn quote(app: &App, contract_addr: Addr, minter: &str, quantity: u32) -> MintPriceResponse {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr,
+                    &QueryMsg::GetMintPrice {
+                        quantity,
+                        minter_address: Some(minter.to_string()),
+                        collection_id: None,
+                        denom: None,
+                    },
+                )
+                .unwrap()
+        }
+
+        fn discounts() -> BulkDiscounts {
+            // whitelist: 2 at 5% off. public: 1-2 at full price, 3 at 10% off, 4 at 20% off
+            BulkDiscounts {
+                whitelist: vec![DiscountTier {
+                    min_quantity: 2,
+                    discount_bps: 500,
+                }],
+                public: vec![
+                    DiscountTier {
+                        min_quantity: 3,
+                        discount_bps: 1_000,
+                    },
+                    DiscountTier {
+                        min_quantity: 4,
+                        discount_bps: 2_000,
+                    },
+                ],
+            }
+        }
+
+        fn setup() -> (App, Addr) {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(true, true, false, Some(2), None);
+            let contract_addr = cw_template_contract.addr();
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                bulk_discounts: Some(discounts()),
+                ..msg
+            })
+            .unwrap();
+
+            (app, contract_addr)
+        }
+
+        #[test]
+        fn public_tiers_follow_running_total() {
+            let (mut app, contract_addr) = setup();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            let res = quote(&app, contract_addr.clone(), USER25, 2);
+            assert_eq!(res.price, Uint128::from(2 * MINT_PRICE));
+            assert_eq!(res.discount_bps, 0);
+
+            let res = quote(&app, contract_addr.clone(), USER25, 3);
+            assert_eq!(res.price, Uint128::from(3 * MINT_PRICE * 9 / 10));
+            assert_eq!(res.discount_bps, 1_000);
+
+            let mint_msg = mint_msg(3, None, None, None);
+
+            // full price is no longer the price
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg,
+                &[coin(3 * MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg,
+                &[coin(res.price.u128(), NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            // the revenue split works on the discounted amount
+            let maintainer_balance = app
+                .wrap()
+                .query_balance(MAINTAINER_ADDR.to_string(), NATIVE_DENOM)
+                .unwrap();
+            assert_eq!(
+                maintainer_balance.amount,
+                res.price.multiply_ratio(3_000u128, 10_000u128)
+            );
+
+            // the 4th token of the address reaches the next tier on its own
+            let res = quote(&app, contract_addr.clone(), USER25, 1);
+            assert_eq!(res.price, Uint128::from(MINT_PRICE * 8 / 10));
+            assert_eq!(res.discount_bps, 2_000);
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, None, None, None),
+                &[coin(res.price.u128(), NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            // another address starts back at full price
+            let res = quote(&app, contract_addr, USER10, 1);
+            assert_eq!(res.price, Uint128::from(MINT_PRICE));
+        }
+
+        #[test]
+        fn whitelist_tiers() {
+            let (mut app, contract_addr) = setup();
+
+            let config: ConfigResponse = app
+                .wrap()
+                .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetConfig {})
+                .unwrap();
+
+            app.execute_contract(
+                contract_addr.clone(),
+                config.whitelist_addr.unwrap(),
+                &WhitelistExecuteMsg::AddToWhitelist(vec![USER25.to_string()]),
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| {
+                block.time = Timestamp::from_seconds(WHITELIST_START_TIME + 1)
+            });
+
+            let res = quote(&app, contract_addr.clone(), USER25, 2);
+            assert_eq!(res.price, Uint128::from(2 * WL_MINT_PRICE * 95 / 100));
+            assert_eq!(res.discount_bps, 500);
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr,
+                &mint_msg(2, None, None, None),
+                &[coin(res.price.u128(), NATIVE_DENOM)],
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn invalid_tiers() {
+            let (mut app, contract_addr) = setup();
+
+            // tiers out of order
+            let mut bulk_discounts = discounts();
+            bulk_discounts.public.reverse();
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                bulk_discounts: Some(bulk_discounts),
+                ..msg
+            })
+            .unwrap_err();
+
+            // public tiers can't be combined with an auction
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                dutch_auction: Some(DutchAuction {
+                    start_price: Uint128::from(2 * MINT_PRICE),
+                    floor_price: Uint128::from(MINT_PRICE),
+                    decay_interval: 600,
+                    step: Uint128::from(WL_MINT_PRICE),
+                    rebate: false,
+                }),
+                ..msg
+            })
+            .unwrap_err();
+        }
+    }
+
+    mod collection_pick {
+        use super::*;
+        use crate::msg::MintPriceResponse;
+
+        const PICKED_MINT_PRICE: u128 = 3_000_000;
+
+        fn collection_tokens(app: &App, contract_addr: Addr, collection_id: u32) -> usize {
+            let cw721_addrs: Vec<AddressValMsg> = app
+                .wrap()
+                .query_wasm_smart(contract_addr, &QueryMsg::GetCW721Addrs {})
+                .unwrap();
+            let cw721 = cw721_addrs
+                .into_iter()
+                .find(|cw721| cw721.value == collection_id)
+                .unwrap();
+
+            query_all_tokens(app, cw721.address).len()
+        }
+
+        #[test]
+        fn pick_priced_collection() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(true, true, false, Some(2), None);
+            let contract_addr = cw_template_contract.addr();
+
+            let update_msg = ExecuteMsg::UpdateCollectionMint {
+                collection_id: 102,
+                mint_price: Some(Uint128::from(PICKED_MINT_PRICE)),
+                mint_enabled: true,
+            };
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                contract_addr.clone(),
+                &update_msg,
+                &[],
+            )
+            .unwrap_err();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            // can still be changed while the mint runs
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &update_msg,
+                &[],
+            )
+            .unwrap();
+
+            let quote: MintPriceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetMintPrice {
+                        quantity: 2,
+                        minter_address: None,
+                        collection_id: Some(102),
+                        denom: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(quote.price, Uint128::from(2 * PICKED_MINT_PRICE));
+
+            // a random mint may land in the priced collection, so it pays its price
+            let quote: MintPriceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetMintPrice {
+                        quantity: 1,
+                        minter_address: None,
+                        collection_id: None,
+                        denom: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(quote.price, Uint128::from(PICKED_MINT_PRICE));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, None, None, None),
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(2, Some(102), None, None),
+                &[coin(2 * MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, Some(999), None, None),
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(2, Some(102), None, None),
+                &[coin(2 * PICKED_MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            assert_eq!(collection_tokens(&app, contract_addr.clone(), 101), 0);
+            assert_eq!(collection_tokens(&app, contract_addr, 102), 2);
+        }
+
+        #[test]
+        fn random_mint_pays_priciest_collection() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(true, true, false, Some(2), None);
+            let contract_addr = cw_template_contract.addr();
+            let cheap_mint_price = MINT_PRICE / 2;
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &ExecuteMsg::UpdateCollectionMint {
+                    collection_id: 101,
+                    mint_price: Some(Uint128::from(cheap_mint_price)),
+                    mint_enabled: true,
+                },
+                &[],
+            )
+            .unwrap();
+
+            // every token costs `MINT_PRICE`, including those drawn out of 101
+            let balance_before = balance(&app, USER25);
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(2, None, None, None),
+                &[coin(2 * MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+            assert_eq!(
+                balance_before - balance(&app, USER25),
+                Uint128::from(2 * MINT_PRICE)
+            );
+            assert_eq!(
+                collection_tokens(&app, contract_addr.clone(), 101)
+                    + collection_tokens(&app, contract_addr.clone(), 102),
+                2
+            );
+
+            // picking the cheaper collection pays its own price
+            app.execute_contract(
+                Addr::unchecked(USER2),
+                contract_addr.clone(),
+                &mint_msg(1, Some(101), None, None),
+                &[coin(cheap_mint_price, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            // without the pricier collection in the draw, random mints pay the cheaper price
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &ExecuteMsg::UpdateCollectionMint {
+                    collection_id: 102,
+                    mint_price: None,
+                    mint_enabled: false,
+                },
+                &[],
+            )
+            .unwrap();
+
+            let quote: MintPriceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetMintPrice {
+                        quantity: 1,
+                        minter_address: None,
+                        collection_id: None,
+                        denom: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(quote.price, Uint128::from(cheap_mint_price));
+
+            app.execute_contract(
+                Addr::unchecked(USER3),
+                contract_addr,
+                &mint_msg(1, None, None, None),
+                &[coin(cheap_mint_price, NATIVE_DENOM)],
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn disabled_collection_is_skipped() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(true, true, false, Some(2), None);
+            let contract_addr = cw_template_contract.addr();
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &ExecuteMsg::UpdateCollectionMint {
+                    collection_id: 101,
+                    mint_price: None,
+                    mint_enabled: false,
+                },
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, Some(101), None, None),
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            // random mints only land in the enabled collection
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(4, None, None, None),
+                &[coin(4 * MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            assert_eq!(collection_tokens(&app, contract_addr.clone(), 101), 0);
+            assert_eq!(collection_tokens(&app, contract_addr, 102), 4);
+        }
+
+        #[test]
+        fn bundle_skips_disabled_collection() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(true, true, true, Some(2), None);
+            let contract_addr = cw_template_contract.addr();
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &ExecuteMsg::UpdateCollectionMint {
+                    collection_id: 101,
+                    mint_price: None,
+                    mint_enabled: false,
+                },
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &ExecuteMsg::MintBundle { referrer: None },
+                &[coin(BUNDLE_MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            assert_eq!(collection_tokens(&app, contract_addr.clone(), 101), 0);
+            assert_eq!(collection_tokens(&app, contract_addr, 102), 1);
+        }
+    }
+
+    mod weighted_selection {
+        use super::*;
+        use crate::contract::{draw_index_from_seed, weighted_index};
+        use crate::state::{CollectionSelection, DrawKind, DrawRecord};
+
+        #[test]
+        fn ticket_boundaries() {
+            let weights = [3, 1, 6];
+
+            assert_eq!(weighted_index(&weights, 0), 0);
+            assert_eq!(weighted_index(&weights, 2), 0);
+            assert_eq!(weighted_index(&weights, 3), 1);
+            assert_eq!(weighted_index(&weights, 4), 2);
+            assert_eq!(weighted_index(&weights, 9), 2);
+        }
+
+        #[test]
+        fn draws_are_proportional_to_weight() {
+            let weights = [1_000, 9_000];
+            let total: u32 = weights.iter().sum();
+            let draws = 10_000;
+
+            let small_collection_draws = (0..draws)
+                .filter(|i| {
+                    let ticket = draw_index_from_seed(format!("seed{}", i).as_bytes(), total);
+                    weighted_index(&weights, ticket) == 0
+                })
+                .count();
+
+            // expected 1,000. 5 standard deviations is 150
+            assert!(
+                (850..=1_150).contains(&small_collection_draws),
+                "{} draws of the small collection",
+                small_collection_draws
+            );
+        }
+
+        #[test]
+        fn mint_draws_by_remaining_supply() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(true, true, false, Some(3), None);
+            let contract_addr = cw_template_contract.addr();
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                collection_selection: Some(CollectionSelection::Weighted),
+                denom_mint_prices: None,
+                cw20_payment: None,
+                oracle_pricing: None,
+                referral_bps: None,
+                ..msg
+            })
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, None, None, None),
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            let history: Vec<(u64, DrawRecord)> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr,
+                    &QueryMsg::GetDrawHistory {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+
+            match history.last().unwrap().1.kind.clone() {
+                DrawKind::Mint {
+                    collection_seed,
+                    available_collection_ids,
+                    collection_weights,
+                    collection_id,
+                    ..
+                } => {
+                    // the third test collection holds 6 tokens
+                    let weights = collection_weights.unwrap();
+                    assert_eq!(weights, vec![5, 5, 6]);
+
+                    let ticket = draw_index_from_seed(collection_seed.as_bytes(), 16);
+                    assert_eq!(
+                        available_collection_ids[weighted_index(&weights, ticket)],
+                        collection_id
+                    );
+                }
+                kind => panic!("unexpected draw {:?}", kind),
+            }
+        }
+    }
+
+    mod multi_denom_mint {
+        use super::*;
+        use crate::msg::MintPriceResponse;
+        use crate::state::BondingCurve;
+        use crate::ContractError;
+
+        const ALT_DENOM: &str = "ibc/ATOM";
+        const ALT_MINT_PRICE: u128 = 500_000;
+
+        fn setup(escrow_funds: bool) -> (App, Addr) {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(false, false, false, None, None);
+            let contract_addr = cw_template_contract.addr();
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                denom_mint_prices: Some(vec![coin(ALT_MINT_PRICE, ALT_DENOM)]),
+                escrow_funds,
+                ..msg
+            })
+            .unwrap();
+
+            app.sudo(SudoMsg::Bank({
+                BankSudo::Mint {
+                    to_address: USER25.to_string(),
+                    amount: coins(10 * ALT_MINT_PRICE, ALT_DENOM),
+                }
+            }))
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            (app, contract_addr)
+        }
+
+        #[test]
+        fn invalid_denom_mint_prices() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(false, false, false, None, None);
+            let contract_addr = cw_template_contract.addr();
+
+            for denom_mint_prices in [
+                vec![coin(ALT_MINT_PRICE, NATIVE_DENOM)],
+                vec![coin(0, ALT_DENOM)],
+                vec![coin(ALT_MINT_PRICE, ALT_DENOM), coin(1, ALT_DENOM)],
+                vec![coin(ALT_MINT_PRICE, "a")],
+            ] {
+                update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                    denom_mint_prices: Some(denom_mint_prices),
+                    ..msg
+                })
+                .unwrap_err();
+            }
+
+            // curve prices are only quoted in `mint_denom`
+            let err = update_config(&mut app, contract_addr, |msg| BaseInitMsg {
+                denom_mint_prices: Some(vec![coin(ALT_MINT_PRICE, ALT_DENOM)]),
+                bonding_curve: Some(BondingCurve::Linear {
+                    base_price: Uint128::from(MINT_PRICE),
+                    slope: Uint128::from(1_000u128),
+                }),
+                ..msg
+            })
+            .unwrap_err();
+            assert_eq!(err, ContractError::ConflictingPricingModes {});
+        }
+
+        #[test]
+        fn mint_in_listed_denom() {
+            let (mut app, contract_addr) = setup(false);
+
+            let quote: MintPriceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetMintPrice {
+                        quantity: 2,
+                        minter_address: None,
+                        collection_id: None,
+                        denom: Some(ALT_DENOM.to_string()),
+                    },
+                )
+                .unwrap();
+            assert_eq!(quote.price, Uint128::from(2 * ALT_MINT_PRICE));
+            assert_eq!(quote.denom, ALT_DENOM);
+
+            app.wrap()
+                .query_wasm_smart::<MintPriceResponse>(
+                    contract_addr.clone(),
+                    &QueryMsg::GetMintPrice {
+                        quantity: 2,
+                        minter_address: None,
+                        collection_id: None,
+                        denom: Some("uother".to_string()),
+                    },
+                )
+                .unwrap_err();
+
+            // the `mint_denom` price doesn't apply to the listed denom
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(2, None, None, None),
+                &[coin(2 * MINT_PRICE, ALT_DENOM)],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(2, None, None, None),
+                &[coin(2 * ALT_MINT_PRICE, ALT_DENOM)],
+            )
+            .unwrap();
+
+            // revenue is split in the denom it was paid in
+            let admin_balance = app.wrap().query_balance(ADMIN, ALT_DENOM).unwrap();
+            assert_eq!(admin_balance.amount, Uint128::from(700_000u128));
+            let maintainer_balance = app
+                .wrap()
+                .query_balance(MAINTAINER_ADDR, ALT_DENOM)
+                .unwrap();
+            assert_eq!(maintainer_balance.amount, Uint128::from(300_000u128));
+
+            // `mint_denom` still pays its own price, and both count towards the mint limit
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(2, None, None, None),
+                &[coin(2 * MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr,
+                &mint_msg(1, None, None, None),
+                &[coin(ALT_MINT_PRICE, ALT_DENOM)],
+            )
+            .unwrap_err();
+        }
+
+        #[test]
+        fn escrow_and_disburse_each_denom() {
+            let (mut app, contract_addr) = setup(true);
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, None, None, None),
+                &[coin(ALT_MINT_PRICE, ALT_DENOM)],
+            )
+            .unwrap();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, None, None, None),
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            let escrow_bals: Vec<AddrBal> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetEscrowBalances {
+                        start_after: None,
+                        start_after_denom: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                escrow_bals,
+                vec![
+                    AddrBal {
+                        addr: Addr::unchecked(ADMIN),
+                        denom: NATIVE_DENOM.to_string(),
+                        balance: Uint128::from(1_400_000u128)
+                    },
+                    AddrBal {
+                        addr: Addr::unchecked(ADMIN),
+                        denom: ALT_DENOM.to_string(),
+                        balance: Uint128::from(350_000u128)
+                    },
+                    AddrBal {
+                        addr: Addr::unchecked(MAINTAINER_ADDR),
+                        denom: NATIVE_DENOM.to_string(),
+                        balance: Uint128::from(600_000u128)
+                    },
+                    AddrBal {
+                        addr: Addr::unchecked(MAINTAINER_ADDR),
+                        denom: ALT_DENOM.to_string(),
+                        balance: Uint128::from(150_000u128)
+                    },
+                ]
+            );
+
+            // resume part-way through an address, or skip past it
+            let escrow_bals: Vec<AddrBal> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetEscrowBalances {
+                        start_after: Some(ADMIN.to_string()),
+                        start_after_denom: Some(NATIVE_DENOM.to_string()),
+                        limit: Some(1),
+                    },
+                )
+                .unwrap();
+            assert_eq!(escrow_bals[0].denom, ALT_DENOM);
+            assert_eq!(escrow_bals[0].addr, Addr::unchecked(ADMIN));
+
+            let escrow_bals: Vec<AddrBal> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetEscrowBalances {
+                        start_after: Some(ADMIN.to_string()),
+                        start_after_denom: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(escrow_bals.len(), 2);
+            assert!(escrow_bals
+                .iter()
+                .all(|bal| bal.addr == Addr::unchecked(MAINTAINER_ADDR)));
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &ExecuteMsg::DisburseFunds {},
+                &[],
+            )
+            .unwrap();
+
+            let maintainer_balance = app
+                .wrap()
+                .query_balance(MAINTAINER_ADDR, ALT_DENOM)
+                .unwrap();
+            assert_eq!(maintainer_balance.amount, Uint128::from(150_000u128));
+            let maintainer_balance = app
+                .wrap()
+                .query_balance(MAINTAINER_ADDR, NATIVE_DENOM)
+                .unwrap();
+            assert_eq!(maintainer_balance.amount, Uint128::from(600_000u128));
+
+            let contract_balances = app.wrap().query_all_balances(contract_addr).unwrap();
+            assert_eq!(contract_balances, vec![]);
+        }
+    }
+
+    mod cw20_payment {
+        use super::*;
+        use crate::msg::{MintPriceResponse, ReceiveMsg};
+        use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+
+        const CW20_MINT_PRICE: u128 = 300_000;
+        const CW20_WL_MINT_PRICE: u128 = 200_000;
+        const CW20_BUNDLE_MINT_PRICE: u128 = 1_000_000;
+
+        fn cw20_contract() -> Box<dyn Contract<Empty>> {
+            let contract = ContractWrapper::new(
+                cw20_base::contract::execute,
+                cw20_base::contract::instantiate,
+                cw20_base::contract::query,
+            );
+            Box::new(contract)
+        }
+
+        fn instantiate_cw20(app: &mut App, symbol: &str) -> Addr {
+            let code_id = app.store_code(cw20_contract());
+
+            app.instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "governance".to_string(),
+                    symbol: symbol.to_string(),
+                    decimals: 6,
+                    initial_balances: vec![Cw20Coin {
+                        address: USER25.to_string(),
+                        amount: Uint128::from(10_000_000u128),
+                    }],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                symbol,
+                None,
+            )
+            .unwrap()
+        }
+
+        fn cw20_balance(app: &App, token_addr: &Addr, address: impl Into<String>) -> Uint128 {
+            let res: BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    token_addr,
+                    &Cw20QueryMsg::Balance {
+                        address: address.into(),
+                    },
+                )
+                .unwrap();
+
+            res.balance
+        }
+
+        fn send_msg(contract_addr: &Addr, amount: u128, msg: &ReceiveMsg) -> Cw20ExecuteMsg {
+            Cw20ExecuteMsg::Send {
+                contract: contract_addr.to_string(),
+                amount: Uint128::from(amount),
+                msg: to_binary(msg).unwrap(),
+            }
+        }
+
+        fn receive_mint_msg(quantity: u32) -> ReceiveMsg {
+            ReceiveMsg::Mint {
+                quantity: Some(quantity),
+                collection_id: None,
+                referrer: None,
+            }
+        }
+
+        /// returns the minter and the cw20 it accepts
+        fn setup(escrow_funds: bool) -> (App, Addr, Addr) {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, true, None, None);
+            let contract_addr = cw_template_contract.addr();
+            let token_addr = instantiate_cw20(&mut app, "GOV");
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                cw20_payment: Some(Cw20PaymentMsg {
+                    token_address: token_addr.to_string(),
+                    mint_price: Uint128::from(CW20_MINT_PRICE),
+                    whitelist_mint_price: Some(Uint128::from(CW20_WL_MINT_PRICE)),
+                    bundle_mint_price: Uint128::from(CW20_BUNDLE_MINT_PRICE),
+                }),
+                escrow_funds,
+                ..msg
+            })
+            .unwrap();
+
+            (app, contract_addr, token_addr)
+        }
+
+        #[test]
+        fn public_mint_paid_in_cw20() {
+            let (mut app, contract_addr, token_addr) = setup(false);
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            let quote: MintPriceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetMintPrice {
+                        quantity: 2,
+                        minter_address: None,
+                        collection_id: None,
+                        denom: Some(format!("cw20:{}", token_addr)),
+                    },
+                )
+                .unwrap();
+            assert_eq!(quote.price, Uint128::from(2 * CW20_MINT_PRICE));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                token_addr.clone(),
+                &send_msg(&contract_addr, CW20_MINT_PRICE, &receive_mint_msg(2)),
+                &[],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                token_addr.clone(),
+                &send_msg(&contract_addr, 2 * CW20_MINT_PRICE, &receive_mint_msg(2)),
+                &[],
+            )
+            .unwrap();
+
+            let address_mints: AddressValMsg = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::CheckAddressMints {
+                        minter_address: USER25.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(address_mints.value, 2);
+
+            // the revenue split is paid out with cw20 transfers
+            assert_eq!(
+                cw20_balance(&app, &token_addr, ADMIN),
+                Uint128::from(420_000u128)
+            );
+            assert_eq!(
+                cw20_balance(&app, &token_addr, MAINTAINER_ADDR),
+                Uint128::from(180_000u128)
+            );
+
+            // any other cw20 is refused
+            let other_token_addr = instantiate_cw20(&mut app, "OTHER");
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                other_token_addr,
+                &send_msg(&contract_addr, CW20_MINT_PRICE, &receive_mint_msg(1)),
+                &[],
+            )
+            .unwrap_err();
+
+            // and so is a native coin posing as the token
+            let fake_denom = format!("cw20:{}", token_addr);
+            app.sudo(SudoMsg::Bank({
+                BankSudo::Mint {
+                    to_address: USER25.to_string(),
+                    amount: coins(CW20_MINT_PRICE, fake_denom.clone()),
+                }
+            }))
+            .unwrap();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr,
+                &mint_msg(1, None, None, None),
+                &[coin(CW20_MINT_PRICE, fake_denom)],
+            )
+            .unwrap_err();
+        }
+
+        #[test]
+        fn whitelist_and_bundle_escrowed_in_cw20() {
+            let (mut app, contract_addr, token_addr) = setup(true);
+
+            let config: ConfigResponse = app
+                .wrap()
+                .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetConfig {})
+                .unwrap();
+
+            app.execute_contract(
+                contract_addr.clone(),
+                config.whitelist_addr.unwrap(),
+                &WhitelistExecuteMsg::AddToWhitelist(vec![USER25.to_string()]),
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| {
+                block.time = Timestamp::from_seconds(WHITELIST_START_TIME + 1)
+            });
+
+            // the cw20 whitelist price replaces the whitelist's own
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                token_addr.clone(),
+                &send_msg(&contract_addr, CW20_MINT_PRICE, &receive_mint_msg(1)),
+                &[],
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                token_addr.clone(),
+                &send_msg(&contract_addr, CW20_WL_MINT_PRICE, &receive_mint_msg(1)),
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                token_addr.clone(),
+                &send_msg(
+                    &contract_addr,
+                    CW20_BUNDLE_MINT_PRICE,
+                    &ReceiveMsg::MintBundle { referrer: None },
+                ),
+                &[],
+            )
+            .unwrap();
+
+            let cw20_denom = format!("cw20:{}", token_addr);
+            let escrow_bals: Vec<AddrBal> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetEscrowBalances {
+                        start_after: None,
+                        start_after_denom: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                escrow_bals,
+                vec![
+                    AddrBal {
+                        addr: Addr::unchecked(ADMIN),
+                        denom: cw20_denom.clone(),
+                        balance: Uint128::from(840_000u128)
+                    },
+                    AddrBal {
+                        addr: Addr::unchecked(MAINTAINER_ADDR),
+                        denom: cw20_denom,
+                        balance: Uint128::from(360_000u128)
+                    },
+                ]
+            );
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &ExecuteMsg::DisburseFunds {},
+                &[],
+            )
+            .unwrap();
+
+            assert_eq!(
+                cw20_balance(&app, &token_addr, ADMIN),
+                Uint128::from(840_000u128)
+            );
+            assert_eq!(
+                cw20_balance(&app, &token_addr, MAINTAINER_ADDR),
+                Uint128::from(360_000u128)
+            );
+            assert_eq!(
+                cw20_balance(&app, &token_addr, contract_addr),
+                Uint128::zero()
+            );
+        }
+    }
+
+    mod oracle_pricing {
+        use super::*;
+        use crate::msg::{MintPriceResponse, OraclePriceResponse, OracleQueryMsg};
+        use crate::state::DutchAuction;
+        use crate::ContractError;
+        use cosmwasm_std::{Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+        use cw_storage_plus::Item;
+
+        /// `mint_price` in micro-USD, i.e. $2
+        const REFERENCE_MINT_PRICE: u128 = 2_000_000;
+        const SLIPPAGE_BPS: u32 = 100;
+
+        /// stand-in for a price oracle quoting every denom at one `rate`
+        #[cw_serde]
+        pub struct MockOracleInstantiateMsg {
+            rate: Decimal,
+        }
+
+        #[cw_serde]
+        pub enum MockOracleExecuteMsg {
+            SetRate { rate: Decimal },
+        }
+
+        const RATE: Item<Decimal> = Item::new("rate");
+
+        fn oracle_instantiate(
+            deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            msg: MockOracleInstantiateMsg,
+        ) -> StdResult<Response> {
+            RATE.save(deps.storage, &msg.rate)?;
+            Ok(Response::new())
+        }
+
+        fn oracle_execute(
+            deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            msg: MockOracleExecuteMsg,
+        ) -> StdResult<Response> {
+            match msg {
+                MockOracleExecuteMsg::SetRate { rate } => {
+                    RATE.save(deps.storage, &rate)?;
+                    Ok(Response::new())
+                }
+            }
+        }
+
+        fn oracle_query(deps: Deps, _env: Env, msg: OracleQueryMsg) -> StdResult<Binary> {
+            match msg {
+                OracleQueryMsg::Price { denom: _ } => to_binary(&OraclePriceResponse {
+                    rate: RATE.load(deps.storage)?,
+                }),
+            }
+        }
+
+        fn mock_oracle_contract() -> Box<dyn Contract<Empty>> {
+            let contract = ContractWrapper::new(oracle_execute, oracle_instantiate, oracle_query);
+            Box::new(contract)
+        }
+
+        /// returns the minter and an oracle quoting `NATIVE_DENOM` at $0.50
+        fn setup() -> (App, Addr, Addr) {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(false, false, false, None, None);
+            let contract_addr = cw_template_contract.addr();
+
+            let oracle_code_id = app.store_code(mock_oracle_contract());
+            let oracle_addr = app
+                .instantiate_contract(
+                    oracle_code_id,
+                    Addr::unchecked(ADMIN),
+                    &MockOracleInstantiateMsg {
+                        rate: Decimal::percent(50),
+                    },
+                    &[],
+                    "oracle",
+                    None,
+                )
+                .unwrap();
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                mint_price: Uint128::from(REFERENCE_MINT_PRICE),
+                oracle_pricing: Some(OraclePricingMsg {
+                    oracle_address: oracle_addr.to_string(),
+                    slippage_bps: SLIPPAGE_BPS,
+                }),
+                ..msg
+            })
+            .unwrap();
+
+            fund(&mut app, USER25, 20_000_000);
+
+            (app, contract_addr, oracle_addr)
+        }
+
+        #[test]
+        fn invalid_oracle_pricing() {
+            let (mut app, contract_addr, oracle_addr) = setup();
+
+            let err = update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                oracle_pricing: Some(OraclePricingMsg {
+                    oracle_address: oracle_addr.to_string(),
+                    slippage_bps: 10_001,
+                }),
+                ..msg
+            })
+            .unwrap_err();
+            assert_eq!(err, ContractError::InvalidOraclePricing {});
+
+            // auction rebates can't follow a moving rate
+            let err = update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                dutch_auction: Some(DutchAuction {
+                    start_price: Uint128::from(2 * REFERENCE_MINT_PRICE),
+                    floor_price: Uint128::from(REFERENCE_MINT_PRICE),
+                    decay_interval: 600,
+                    step: Uint128::from(100_000u128),
+                    rebate: false,
+                }),
+                ..msg
+            })
+            .unwrap_err();
+            assert_eq!(err, ContractError::ConflictingPricingModes {});
+        }
+
+        #[test]
+        fn converted_price_refunds_overpayment() {
+            let (mut app, contract_addr, _) = setup();
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            let quote: MintPriceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetMintPrice {
+                        quantity: 1,
+                        minter_address: None,
+                        collection_id: None,
+                        denom: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(quote.price, Uint128::from(4_000_000u128));
+            assert_eq!(
+                quote.reference_price,
+                Some(Uint128::from(REFERENCE_MINT_PRICE))
+            );
+
+            // more than the slippage over the price is turned away
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, None, None, None),
+                &coins(4_040_001, NATIVE_DENOM),
+            )
+            .unwrap_err();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, None, None, None),
+                &coins(4_040_000, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            // only the converted price is kept
+            assert_eq!(balance(&app, USER25), Uint128::from(16_000_000u128));
+            assert_eq!(balance(&app, MAINTAINER_ADDR), Uint128::from(1_200_000u128));
+            assert_eq!(balance(&app, contract_addr), Uint128::zero());
+        }
+
+        #[test]
+        fn underpayment_is_rejected() {
+            let (mut app, contract_addr, oracle_addr) = setup();
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            // $0.40 a token makes the price 5M, which a payment has to cover in full
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                oracle_addr,
+                &MockOracleExecuteMsg::SetRate {
+                    rate: Decimal::percent(40),
+                },
+                &[],
+            )
+            .unwrap();
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr.clone(),
+                    &mint_msg(1, None, None, None),
+                    &coins(4_999_999, NATIVE_DENOM),
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::IncorrectPaymentAmount {
+                    token: NATIVE_DENOM.to_string(),
+                    amt: Uint128::from(5_000_000u128),
+                }
+            );
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, None, None, None),
+                &coins(5_050_000, NATIVE_DENOM),
+            )
+            .unwrap();
+            assert_eq!(balance(&app, USER25), Uint128::from(15_000_000u128));
+        }
+    }
+
+    mod referrals {
+        use super::*;
+        use crate::state::Referral;
+        use crate::ContractError;
+
+        const REFERRER: &str = "influencer";
+        const REFERRAL_BPS: u32 = 1_000;
+
+        fn setup() -> (App, Addr) {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(false, false, true, None, None);
+            let contract_addr = cw_template_contract.addr();
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                referral_bps: Some(REFERRAL_BPS),
+                ..msg
+            })
+            .unwrap();
+
+            fund(&mut app, USER25, 20_000_000);
+
+            (app, contract_addr)
+        }
+
+        fn query_referrals(app: &App, contract_addr: Addr) -> Vec<(Addr, Referral)> {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr,
+                    &QueryMsg::GetReferrals {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap()
+        }
+
+        #[test]
+        fn invalid_referrals() {
+            let (mut app, contract_addr) = setup();
+
+            let err = update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                referral_bps: Some(10_001),
+                ..msg
+            })
+            .unwrap_err();
+            assert_eq!(err, ContractError::InvalidReferralBps {});
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr.clone(),
+                    &mint_msg(1, None, Some(USER25), None),
+                    &coins(MINT_PRICE, NATIVE_DENOM),
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidReferrer {}
+            );
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr.clone(),
+                    &ExecuteMsg::MintBundle {
+                        referrer: Some(USER25.to_string()),
+                    },
+                    &coins(BUNDLE_MINT_PRICE, NATIVE_DENOM),
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidReferrer {}
+            );
+        }
+
+        #[test]
+        fn referral_rewards_escrowed_before_revenue_split() {
+            let (mut app, contract_addr) = setup();
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(2, None, Some(REFERRER), None),
+                &coins(2 * MINT_PRICE, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            // 10% is held for the referrer and the split is over the rest
+            assert_eq!(balance(&app, MAINTAINER_ADDR), Uint128::from(1_080_000u128));
+
+            let cw721_addrs: Vec<AddressValMsg> = app
+                .wrap()
+                .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetCW721Addrs {})
+                .unwrap();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &ExecuteMsg::MintBundle {
+                    referrer: Some(REFERRER.to_string()),
+                },
+                &coins(BUNDLE_MINT_PRICE, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            // unreferred mints earn nothing
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, None, None, None),
+                &coins(MINT_PRICE, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            assert_eq!(
+                query_referrals(&app, contract_addr.clone()),
+                vec![(
+                    Addr::unchecked(REFERRER),
+                    Referral {
+                        mint_count: 2 + cw721_addrs.len() as u32,
+                        earnings: coins(900_000, NATIVE_DENOM),
+                    }
+                )]
+            );
+
+            let balances: Vec<AddrBal> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetEscrowBalances {
+                        start_after: None,
+                        start_after_denom: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                balances,
+                vec![AddrBal {
+                    addr: Addr::unchecked(REFERRER),
+                    denom: NATIVE_DENOM.to_string(),
+                    balance: Uint128::from(900_000u128),
+                }]
+            );
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr,
+                &ExecuteMsg::DisburseFunds {},
+                &[],
+            )
+            .unwrap();
+            assert_eq!(balance(&app, REFERRER), Uint128::from(900_000u128));
+        }
+    }
+
+    mod mint_codes {
+        use super::*;
+        use crate::msg::MintCodeMsg;
+        use crate::state::{MintCode, MintCodeDiscount};
+        use crate::ContractError;
+        use sha2::{Digest, Sha256};
+
+        const FREE_CODE: &str = "GM-FREE-MINT";
+        const HALF_OFF_CODE: &str = "GM-HALF-OFF";
+
+        fn hash(address: &str, code: &str) -> String {
+            format!("{:x}", Sha256::digest(format!("{}:{}", address, code)))
+        }
+
+        fn query_code(app: &App, contract_addr: Addr, code: &str) -> Option<MintCode> {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr,
+                    &QueryMsg::GetMintCode {
+                        hash: hash(USER25, code),
+                    },
+                )
+                .unwrap()
+        }
+
+        fn setup() -> (App, Addr) {
+            let (mut app, contract_addr) = setup_funded(&[USER25]);
+
+            let msg = ExecuteMsg::RegisterMintCodes {
+                codes: vec![
+                    MintCodeMsg {
+                        hash: hash(USER25, FREE_CODE),
+                        uses: 1,
+                        discount: MintCodeDiscount::FixedPrice(Uint128::zero()),
+                    },
+                    MintCodeMsg {
+                        hash: hash(USER25, HALF_OFF_CODE),
+                        uses: 3,
+                        discount: MintCodeDiscount::Bps(5_000),
+                    },
+                ],
+            };
+
+            // only the admin or maintainer hands out codes
+            app.execute_contract(Addr::unchecked(USER25), contract_addr.clone(), &msg, &[])
+                .unwrap_err();
+            app.execute_contract(Addr::unchecked(ADMIN), contract_addr.clone(), &msg, &[])
+                .unwrap();
+
+            (app, contract_addr)
+        }
+
+        #[test]
+        fn invalid_mint_codes() {
+            let (mut app, contract_addr) = setup();
+
+            for code in [
+                MintCodeMsg {
+                    hash: "not a hash".to_string(),
+                    uses: 1,
+                    discount: MintCodeDiscount::Bps(1_000),
+                },
+                MintCodeMsg {
+                    hash: hash(USER25, "GM-NO-USES"),
+                    uses: 0,
+                    discount: MintCodeDiscount::Bps(1_000),
+                },
+                MintCodeMsg {
+                    hash: hash(USER25, "GM-TOO-MUCH"),
+                    uses: 1,
+                    discount: MintCodeDiscount::Bps(10_001),
+                },
+            ] {
+                let err = app
+                    .execute_contract(
+                        Addr::unchecked(ADMIN),
+                        contract_addr.clone(),
+                        &ExecuteMsg::RegisterMintCodes { codes: vec![code] },
+                        &[],
+                    )
+                    .unwrap_err();
+                assert_eq!(
+                    err.downcast::<ContractError>().unwrap(),
+                    ContractError::InvalidMintCodes {}
+                );
+            }
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr.clone(),
+                    &mint_msg(1, None, None, Some("GM-GUESS")),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidMintCode {}
+            );
+
+            // a removed code can't be redeemed
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &ExecuteMsg::RemoveMintCodes {
+                    hashes: vec![hash(USER25, FREE_CODE)],
+                },
+                &[],
+            )
+            .unwrap();
+            assert_eq!(query_code(&app, contract_addr.clone(), FREE_CODE), None);
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr,
+                    &mint_msg(1, None, None, Some(FREE_CODE)),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidMintCode {}
+            );
+        }
+
+        #[test]
+        fn redeem_mint_codes() {
+            let (mut app, contract_addr) = setup();
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, None, None, Some(FREE_CODE)),
+                &[],
+            )
+            .unwrap();
+
+            // one use, and then it's gone
+            assert_eq!(query_code(&app, contract_addr.clone(), FREE_CODE), None);
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr.clone(),
+                    &mint_msg(1, None, None, Some(FREE_CODE)),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidMintCode {}
+            );
+
+            // the full price is no longer what the mint costs
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr.clone(),
+                    &mint_msg(2, None, None, Some(HALF_OFF_CODE)),
+                    &coins(2 * MINT_PRICE, NATIVE_DENOM),
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::IncorrectPaymentAmount {
+                    token: NATIVE_DENOM.to_string(),
+                    amt: Uint128::from(MINT_PRICE),
+                }
+            );
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(2, None, None, Some(HALF_OFF_CODE)),
+                &coins(MINT_PRICE, NATIVE_DENOM),
+            )
+            .unwrap();
+            assert_eq!(
+                query_code(&app, contract_addr.clone(), HALF_OFF_CODE),
+                Some(MintCode {
+                    uses_remaining: 1,
+                    discount: MintCodeDiscount::Bps(5_000),
+                })
+            );
+
+            let address_mints: AddressValMsg = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::CheckAddressMints {
+                        minter_address: USER25.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(address_mints.value, 3);
+            assert_eq!(
+                balance(&app, USER25),
+                Uint128::from(10_000_000 - MINT_PRICE)
+            );
+        }
+
+        #[test]
+        fn code_is_bound_to_its_minter() {
+            let (mut app, contract_addr) = setup();
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            // read out of USER25's pending transaction and sent first
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER2),
+                    contract_addr.clone(),
+                    &mint_msg(1, None, None, Some(FREE_CODE)),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidMintCode {}
+            );
+
+            // minting for USER25 doesn't make it theirs either
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER2),
+                    contract_addr.clone(),
+                    &ExecuteMsg::Mint {
+                        is_promised_mint: false,
+                        minter_address: Some(USER25.to_string()),
+                        quantity: None,
+                        collection_id: None,
+                        referrer: None,
+                        code: Some(FREE_CODE.to_string()),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidMintCode {}
+            );
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, None, None, Some(FREE_CODE)),
+                &[],
+            )
+            .unwrap();
+            assert_eq!(query_code(&app, contract_addr, FREE_CODE), None);
+        }
+    }
+
+    mod refunds {
+        use super::*;
+        use crate::state::{MintReceipt, Referral};
+        use crate::ContractError;
+        use cw721_base::ExecuteMsg as Cw721ExecuteMsg;
+
+        const REFUND_PERIOD: u64 = 86_400;
+
+        fn setup() -> (App, Addr, String) {
+            let (mut app, contract_addr) = setup_funded(&[USER25]);
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                escrow_funds: true,
+                refund_period: Some(REFUND_PERIOD),
+                ..msg
+            })
+            .unwrap();
+
+            let cw721_addrs: Vec<AddressValMsg> = app
+                .wrap()
+                .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetCW721Addrs {})
+                .unwrap();
+
+            (app, contract_addr, cw721_addrs[0].address.clone())
+        }
+
+        fn send_nft_msg(contract_addr: Addr, token_id: String) -> Cw721ExecuteMsg<Empty, Empty> {
+            Cw721ExecuteMsg::SendNft {
+                contract: contract_addr.to_string(),
+                token_id,
+                msg: to_binary(&Empty {}).unwrap(),
+            }
+        }
+
+        #[test]
+        fn refund_within_window() {
+            let (mut app, contract_addr, cw721_addr) = setup();
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            mint(&mut app, contract_addr.clone(), USER25, 1, MINT_PRICE).unwrap();
+            let token_id = query_all_tokens(&app, cw721_addr.clone()).pop().unwrap();
+            assert_eq!(remaining_tokens(&app, contract_addr.clone()), 4);
+
+            let receipt: Option<MintReceipt> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetMintReceipt {
+                        collection_id: 101,
+                        token_id: token_id.parse().unwrap(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                receipt,
+                Some(MintReceipt {
+                    paid: coin(MINT_PRICE, NATIVE_DENOM),
+                    referrer: None,
+                    referral_reward: Uint128::zero(),
+                    refundable_until: Timestamp::from_seconds(MINT_START_TIME + REFUND_PERIOD),
+                })
+            );
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                Addr::unchecked(cw721_addr.clone()),
+                &send_nft_msg(contract_addr.clone(), token_id.clone()),
+                &[],
+            )
+            .unwrap();
+
+            // paid back in full, and the token is burned and back in the draw
+            assert_eq!(balance(&app, USER25), Uint128::from(10_000_000u128));
+            assert!(query_all_tokens(&app, cw721_addr.clone()).is_empty());
+            assert_eq!(remaining_tokens(&app, contract_addr.clone()), 5);
+
+            let receipt: Option<MintReceipt> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetMintReceipt {
+                        collection_id: 101,
+                        token_id: token_id.parse().unwrap(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(receipt, None);
+
+            let escrow_bals: Vec<AddrBal> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetEscrowBalances {
+                        start_after: None,
+                        start_after_denom: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert!(escrow_bals
+                .iter()
+                .all(|addr_bal| addr_bal.balance.is_zero()));
+
+            // the token can be drawn again
+            for _ in 1..MAX_PER_ADDRESS_MINT {
+                mint(&mut app, contract_addr.clone(), USER25, 1, MINT_PRICE).unwrap();
+            }
+            assert_eq!(remaining_tokens(&app, contract_addr), 2);
+        }
+
+        #[test]
+        fn refund_reverses_referral_credited_at_mint() {
+            let (mut app, contract_addr, cw721_addr) = setup();
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                escrow_funds: true,
+                referral_bps: Some(1_000),
+                ..msg
+            })
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &ExecuteMsg::Mint {
+                    is_promised_mint: false,
+                    minter_address: None,
+                    quantity: None,
+                    collection_id: None,
+                    referrer: Some(USER2.to_string()),
+                    code: None,
+                },
+                &coins(MINT_PRICE, NATIVE_DENOM),
+            )
+            .unwrap();
+            let token_id = query_all_tokens(&app, cw721_addr.clone()).pop().unwrap();
+
+            let receipt: Option<MintReceipt> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetMintReceipt {
+                        collection_id: 101,
+                        token_id: token_id.parse().unwrap(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                receipt.unwrap().referral_reward,
+                Uint128::from(MINT_PRICE / 10)
+            );
+
+            // the rate changes between the mint and the refund
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                escrow_funds: true,
+                referral_bps: Some(5_000),
+                ..msg
+            })
+            .unwrap();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                Addr::unchecked(cw721_addr),
+                &send_nft_msg(contract_addr.clone(), token_id),
+                &[],
+            )
+            .unwrap();
+            assert_eq!(balance(&app, USER25), Uint128::from(10_000_000u128));
+
+            // exactly the reward credited at 10% comes back out
+            let referrals: Vec<(Addr, Referral)> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetReferrals {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                referrals,
+                vec![(
+                    Addr::unchecked(USER2),
+                    Referral {
+                        mint_count: 0,
+                        earnings: vec![coin(0, NATIVE_DENOM)],
+                    }
+                )]
+            );
+
+            let escrow_bals: Vec<AddrBal> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr,
+                    &QueryMsg::GetEscrowBalances {
+                        start_after: None,
+                        start_after_denom: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert!(escrow_bals
+                .iter()
+                .all(|addr_bal| addr_bal.balance.is_zero()));
+        }
+
+        #[test]
+        fn refund_window_closes() {
+            let (mut app, contract_addr, cw721_addr) = setup();
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            mint(&mut app, contract_addr.clone(), USER25, 1, MINT_PRICE).unwrap();
+            let token_id = query_all_tokens(&app, cw721_addr.clone()).pop().unwrap();
+
+            let locked_until: Option<Timestamp> = app
+                .wrap()
+                .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetEscrowLockedUntil {})
+                .unwrap();
+            assert_eq!(
+                locked_until,
+                Some(Timestamp::from_seconds(MINT_START_TIME + REFUND_PERIOD))
+            );
+
+            // the escrow has to cover refunds until the window closes
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract_addr.clone(),
+                    &ExecuteMsg::DisburseFunds {},
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::EscrowLocked {
+                    until: Timestamp::from_seconds(MINT_START_TIME + REFUND_PERIOD)
+                }
+            );
+
+            app.update_block(|mut block| {
+                block.time = Timestamp::from_seconds(MINT_START_TIME + REFUND_PERIOD + 1)
+            });
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    Addr::unchecked(cw721_addr),
+                    &send_nft_msg(contract_addr.clone(), token_id),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                ContractError::RefundWindowClosed {}.to_string()
+            );
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr,
+                &ExecuteMsg::DisburseFunds {},
+                &[],
+            )
+            .unwrap();
+            assert_eq!(
+                balance(&app, MAINTAINER_ADDR),
+                Uint128::from(MINT_PRICE * 3 / 10)
+            );
+        }
+
+        #[test]
+        fn invalid_refund_policy() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(false, false, false, None, None);
+            let contract_addr = cw_template_contract.addr();
+
+            // refunds are paid out of the escrow
+            let err = update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                refund_period: Some(REFUND_PERIOD),
+                ..msg
+            })
+            .unwrap_err();
+            assert_eq!(err, ContractError::InvalidRefundPolicy {});
+        }
+    }
+
+    mod minimum_raise {
+        use super::*;
+        use crate::msg::RaiseStatusResponse;
+        use crate::state::{MinimumRaise, RaiseProgress};
+        use crate::ContractError;
+
+        const MINIMUM_TOKENS: u32 = 3;
+
+        fn setup() -> (App, Addr) {
+            let (mut app, contract_addr) = setup_funded(&[USER10, USER25]);
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                minimum_raise: Some(MinimumRaise::Tokens(MINIMUM_TOKENS)),
+                ..msg
+            })
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            (app, contract_addr)
+        }
+
+        #[test]
+        fn locked_after_whitelist_sale() {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
+            let contract_addr = cw_template_contract.addr();
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                minimum_raise: Some(MinimumRaise::Tokens(MINIMUM_TOKENS)),
+                ..msg
+            })
+            .unwrap();
+
+            let config: ConfigResponse = app
+                .wrap()
+                .query_wasm_smart(&contract_addr, &QueryMsg::GetConfig {})
+                .unwrap();
+            app.execute_contract(
+                contract_addr.clone(),
+                config.whitelist_addr.unwrap(),
+                &WhitelistExecuteMsg::AddToWhitelist(vec![USER25.to_string()]),
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| {
+                block.time = Timestamp::from_seconds(WHITELIST_START_TIME)
+            });
+
+            // still open to changes until something is sold
+            app.execute_contract(Addr::unchecked(ADMIN), contract_addr.clone(), &msg, &[])
+                .unwrap();
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &mint_msg(1, None, None, None),
+                &[coin(WL_MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            // the whitelist paid in on these terms before `start_time`
+            let err = update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                minimum_raise: None,
+                ..msg
+            })
+            .unwrap_err();
+            assert_eq!(err, ContractError::MinimumRaiseLocked {});
+        }
+
+        #[test]
+        fn raise_missed() {
+            let (mut app, contract_addr) = setup();
+
+            mint(&mut app, contract_addr.clone(), USER25, 2, 2 * MINT_PRICE).unwrap();
+
+            // held in escrow even though `escrow_funds` is off
+            assert_eq!(balance(&app, MAINTAINER_ADDR), Uint128::zero());
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract_addr.clone(),
+                    &ExecuteMsg::DisburseFunds {},
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::MinimumRaiseNotMet {}
+            );
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr.clone(),
+                    &ExecuteMsg::ClaimRaiseRefund {},
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::MinimumRaiseNotMissed {}
+            );
+
+            // the raise can't be lowered once minting has started
+            let err = update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                minimum_raise: Some(MinimumRaise::Tokens(2)),
+                ..msg
+            })
+            .unwrap_err();
+            assert_eq!(err, ContractError::MinimumRaiseLocked {});
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_END_TIME));
+
+            let status: RaiseStatusResponse = app
+                .wrap()
+                .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetRaiseStatus {})
+                .unwrap();
+            assert_eq!(
+                status,
+                RaiseStatusResponse {
+                    minimum_raise: Some(MinimumRaise::Tokens(MINIMUM_TOKENS)),
+                    progress: RaiseProgress {
+                        tokens_sold: 2,
+                        revenue: Uint128::from(MINT_PRICE * 2),
+                    },
+                    met: false,
+                    missed: true,
+                }
+            );
+
+            let contributions: Vec<Coin> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetRaiseContributions {
+                        address: USER25.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(contributions, coins(MINT_PRICE * 2, NATIVE_DENOM));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &ExecuteMsg::ClaimRaiseRefund {},
+                &[],
+            )
+            .unwrap();
+            assert_eq!(balance(&app, USER25), Uint128::from(10_000_000u128));
+
+            // paid back once, and only to those who paid
+            for user in [USER25, USER10] {
+                let err = app
+                    .execute_contract(
+                        Addr::unchecked(user),
+                        contract_addr.clone(),
+                        &ExecuteMsg::ClaimRaiseRefund {},
+                        &[],
+                    )
+                    .unwrap_err();
+                assert_eq!(
+                    err.downcast::<ContractError>().unwrap(),
+                    ContractError::NoRaiseContribution {}
+                );
+            }
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr,
+                &ExecuteMsg::DisburseFunds {},
+                &[],
+            )
+            .unwrap_err();
+        }
+
+        #[test]
+        fn raise_met() {
+            let (mut app, contract_addr) = setup();
+
+            mint(&mut app, contract_addr.clone(), USER25, 2, 2 * MINT_PRICE).unwrap();
+            mint(&mut app, contract_addr.clone(), USER10, 1, MINT_PRICE).unwrap();
+
+            // the mint that meets the raise is disbursed right away
+            assert_eq!(
+                balance(&app, MAINTAINER_ADDR),
+                Uint128::from(MINT_PRICE * 3 / 10)
+            );
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &ExecuteMsg::DisburseFunds {},
+                &[],
+            )
+            .unwrap();
+            assert_eq!(
+                balance(&app, MAINTAINER_ADDR),
+                Uint128::from(MINT_PRICE * 3 * 3 / 10)
+            );
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_END_TIME));
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr,
+                    &ExecuteMsg::ClaimRaiseRefund {},
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::MinimumRaiseNotMissed {}
+            );
+        }
+
+        #[test]
+        fn invalid_minimum_raise() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(false, false, false, None, None);
+            let contract_addr = cw_template_contract.addr();
+
+            for minimum_raise in [
+                MinimumRaise::Tokens(0),
+                MinimumRaise::Tokens(6),
+                MinimumRaise::Revenue(Uint128::zero()),
+            ] {
+                let err = update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                    minimum_raise: Some(minimum_raise),
+                    ..msg
+                })
+                .unwrap_err();
+                assert_eq!(err, ContractError::InvalidMinimumRaise {});
+            }
+
+            // the raise is judged at `end_time`
+            let err = update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                end_time: None,
+                minimum_raise: Some(MinimumRaise::Tokens(MINIMUM_TOKENS)),
+                ..msg
+            })
+            .unwrap_err();
+            assert_eq!(err, ContractError::InvalidMinimumRaise {});
+        }
+    }
+
+    mod raffle {
+        use super::*;
+        use crate::msg::RaffleResponse;
+        use crate::state::{Raffle, RaffleEntry};
+        use crate::ContractError;
+        use sha2::{Digest, Sha256};
+
+        const MAX_TICKETS: u32 = 3;
+        const ENTRANTS: [&str; 3] = [USER, USER2, USER3];
+        const SECRET: &str = "raffle secret";
+
+        fn setup() -> (App, Addr) {
+            let (mut app, contract_addr) = setup_funded(&ENTRANTS);
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                raffle: Some(Raffle {
+                    deadline: Timestamp::from_seconds(MINT_START_TIME),
+                    max_tickets_per_address: MAX_TICKETS,
+                }),
+                ..msg
+            })
+            .unwrap();
+
+            (app, contract_addr)
+        }
+
+        fn enter(app: &mut App, contract_addr: Addr, user: &str, tickets: u32) {
+            app.execute_contract(
+                Addr::unchecked(user),
+                contract_addr,
+                &ExecuteMsg::EnterRaffle { tickets },
+                &coins(MINT_PRICE * tickets as u128, NATIVE_DENOM),
+            )
+            .unwrap();
+        }
+
+        fn settle(app: &mut App, contract_addr: Addr, limit: Option<u32>) -> RaffleResponse {
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &ExecuteMsg::SettleRaffle { limit },
+                &[],
+            )
+            .unwrap();
+
+            query_raffle(app, contract_addr)
+        }
+
+        fn query_raffle(app: &App, contract_addr: Addr) -> RaffleResponse {
+            app.wrap()
+                .query_wasm_smart(contract_addr, &QueryMsg::GetRaffle {})
+                .unwrap()
+        }
+
+        #[test]
+        fn oversubscribed_raffle() {
+            let (mut app, contract_addr) = setup();
+
+            for user in ENTRANTS {
+                enter(&mut app, contract_addr.clone(), user, MAX_TICKETS);
+            }
+
+            // the whole supply is held for the winners
+            assert_eq!(
+                query_raffle(&app, contract_addr.clone()).progress.reserved,
+                5
+            );
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER),
+                    contract_addr.clone(),
+                    &ExecuteMsg::EnterRaffle { tickets: 1 },
+                    &coins(MINT_PRICE, NATIVE_DENOM),
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::RaffleTicketLimit { max: MAX_TICKETS }
+            );
+
+            // nobody wins before the deadline
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr.clone(),
+                    &ExecuteMsg::SettleRaffle { limit: None },
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::RaffleNotClosed {}
+            );
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr.clone(),
+                    &ExecuteMsg::EnterRaffle { tickets: 1 },
+                    &coins(MINT_PRICE, NATIVE_DENOM),
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::RaffleClosed {}
+            );
+
+            // the public mint waits for the raffle
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr.clone(),
+                    &mint_msg(1, None, None, None),
+                    &coins(MINT_PRICE, NATIVE_DENOM),
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::RaffleNotSettled {}
+            );
+
+            // block data is known to whoever settles, so the draw waits for a secret
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr.clone(),
+                    &ExecuteMsg::SettleRaffle { limit: None },
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::HiddenRandomnessUnavailable {}
+            );
+
+            commit_secret(&mut app, contract_addr.clone());
+
+            // the first call only fixes the winners and what they are drawn with
+            let raffle = settle(&mut app, contract_addr.clone(), Some(2));
+            assert_eq!(raffle.progress.ticket_count, 9);
+            assert_eq!(raffle.progress.winner_count, Some(5));
+            assert_eq!(raffle.progress.winners_drawn, 0);
+            assert!(raffle.progress.commitment.is_some());
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr.clone(),
+                    &ExecuteMsg::SettleRaffle { limit: None },
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::RandomnessNotRevealed {}
+            );
+
+            reveal_secret(&mut app, contract_addr.clone());
+
+            let raffle = settle(&mut app, contract_addr.clone(), Some(2));
+            assert_eq!(raffle.progress.winners_drawn, 2);
+            assert_eq!(raffle.progress.reserved, 3);
+            assert!(!raffle.settled);
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER),
+                    contract_addr.clone(),
+                    &ExecuteMsg::ClaimRaffleRefund {},
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::RaffleNotSettled {}
+            );
+
+            // every token in the supply goes to a winner
+            let raffle = settle(&mut app, contract_addr.clone(), None);
+            assert_eq!(raffle.progress.winners_drawn, 5);
+            assert_eq!(raffle.progress.reserved, 0);
+            assert!(raffle.settled);
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr.clone(),
+                    &ExecuteMsg::SettleRaffle { limit: None },
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::RaffleSettled {}
+            );
+
+            let cw721_addrs: Vec<AddressValMsg> = app
+                .wrap()
+                .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetCW721Addrs {})
+                .unwrap();
+            assert_eq!(
+                query_all_tokens(&app, cw721_addrs[0].address.clone()).len(),
+                5
+            );
+
+            let winners: Vec<(Addr, u32)> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetRaffleWinners {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(winners.iter().map(|(_, won)| won).sum::<u32>(), 5);
+
+            // only the winning tickets are paid out
+            assert_eq!(
+                balance(&app, MAINTAINER_ADDR),
+                Uint128::from(MINT_PRICE * 5 * 3 / 10)
+            );
+
+            // losing tickets are paid back once
+            for user in ENTRANTS {
+                let entry: Option<RaffleEntry> = app
+                    .wrap()
+                    .query_wasm_smart(
+                        contract_addr.clone(),
+                        &QueryMsg::GetRaffleEntry {
+                            address: user.to_string(),
+                        },
+                    )
+                    .unwrap();
+                let entry = entry.unwrap();
+                assert_eq!(entry.tickets, MAX_TICKETS);
+
+                if entry.won < MAX_TICKETS {
+                    app.execute_contract(
+                        Addr::unchecked(user),
+                        contract_addr.clone(),
+                        &ExecuteMsg::ClaimRaffleRefund {},
+                        &[],
+                    )
+                    .unwrap();
+                }
+
+                assert_eq!(
+                    balance(&app, user),
+                    Uint128::from(10_000_000 - MINT_PRICE * entry.won as u128)
+                );
+
+                let err = app
+                    .execute_contract(
+                        Addr::unchecked(user),
+                        contract_addr.clone(),
+                        &ExecuteMsg::ClaimRaffleRefund {},
+                        &[],
+                    )
+                    .unwrap_err();
+                assert_eq!(
+                    err.downcast::<ContractError>().unwrap(),
+                    ContractError::NoRaffleRefund {}
+                );
+            }
+        }
+
+        fn commit_secret(app: &mut App, contract_addr: Addr) {
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr,
+                &ExecuteMsg::CommitRandomness {
+                    commitment: format!("{:x}", Sha256::digest(SECRET.as_bytes())),
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        fn reveal_secret(app: &mut App, contract_addr: Addr) {
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr,
+                &ExecuteMsg::RevealRandomness {
+                    secret: SECRET.to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn winners_only_depend_on_the_secret() {
+            let mut drawn = vec![];
+
+            for limit in [None, Some(1)] {
+                let (mut app, contract_addr) = setup();
+
+                for user in ENTRANTS {
+                    enter(&mut app, contract_addr.clone(), user, MAX_TICKETS);
+                }
+
+                app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+                commit_secret(&mut app, contract_addr.clone());
+                settle(&mut app, contract_addr.clone(), None);
+                reveal_secret(&mut app, contract_addr.clone());
+
+                // a different block and crank size for every winner
+                while !settle(&mut app, contract_addr.clone(), limit).settled {
+                    app.update_block(|mut block| {
+                        block.height += 7;
+                        block.time = block.time.plus_seconds(13);
+                    });
+                }
+
+                let winners: Vec<(Addr, u32)> = app
+                    .wrap()
+                    .query_wasm_smart(
+                        contract_addr.clone(),
+                        &QueryMsg::GetRaffleWinners {
+                            start_after: None,
+                            limit: None,
+                        },
+                    )
+                    .unwrap();
+                drawn.push(winners);
+            }
+
+            assert_eq!(drawn[0], drawn[1]);
+        }
+
+        #[test]
+        fn secret_committed_before_deadline_is_not_drawn_with() {
+            let (mut app, contract_addr) = setup();
+
+            // known to the admin while tickets could still be entered
+            commit_secret(&mut app, contract_addr.clone());
+            enter(&mut app, contract_addr.clone(), USER, 1);
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    contract_addr.clone(),
+                    &ExecuteMsg::SettleRaffle { limit: None },
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::HiddenRandomnessUnavailable {}
+            );
+        }
+
+        #[test]
+        fn invalid_raffle() {
+            let (mut app, contract_addr) = setup();
+
+            for max_tickets_per_address in [0, MAX_PER_ADDRESS_MINT + 1] {
+                let err = update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                    raffle: Some(Raffle {
+                        deadline: Timestamp::from_seconds(MINT_START_TIME),
+                        max_tickets_per_address,
+                    }),
+                    ..msg
+                })
+                .unwrap_err();
+                assert_eq!(err, ContractError::InvalidRaffle {});
+            }
+
+            enter(&mut app, contract_addr.clone(), USER, 1);
+
+            // entrants paid in on its terms
+            let err = update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                raffle: None,
+                ..msg
+            })
+            .unwrap_err();
+            assert_eq!(err, ContractError::RaffleLocked {});
+        }
+    }
+
+    mod token_auctions {
+        use super::*;
+        use crate::state::{MinimumRaise, TokenAuction, TokenAuctionBid};
+        use crate::ContractError;
+        use cw721::OwnerOfResponse;
+        use cw_multi_test::AppResponse;
+        use sha2::{Digest, Sha256};
+
+        const COLLECTION_ID: u64 = 101;
+        const AUCTION_END_TIME: u64 = MINT_START_TIME + 1_000;
+        const EXTENSION: u64 = 300;
+
+        fn create_msg(token_id: u32) -> ExecuteMsg {
+            ExecuteMsg::CreateTokenAuction {
+                collection_id: COLLECTION_ID,
+                token_id,
+                reserve_price: Uint128::from(1_000_000u128),
+                min_increment: Uint128::from(500_000u128),
+                start_time: Timestamp::from_seconds(MINT_START_TIME),
+                end_time: Timestamp::from_seconds(AUCTION_END_TIME),
+                extension: EXTENSION,
+            }
+        }
+
+        fn bid(
+            app: &mut App,
+            contract_addr: Addr,
+            bidder: &str,
+            token_id: u32,
+            amount: u128,
+        ) -> Result<AppResponse, ContractError> {
+            app.execute_contract(
+                Addr::unchecked(bidder),
+                contract_addr,
+                &ExecuteMsg::BidTokenAuction {
+                    collection_id: COLLECTION_ID,
+                    token_id,
+                },
+                &coins(amount, NATIVE_DENOM),
+            )
+            .map_err(|err| err.downcast::<ContractError>().unwrap())
+        }
+
+        fn settle(
+            app: &mut App,
+            contract_addr: Addr,
+            token_id: u32,
+        ) -> Result<AppResponse, ContractError> {
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr,
+                &ExecuteMsg::SettleTokenAuction {
+                    collection_id: COLLECTION_ID,
+                    token_id,
+                },
+                &[],
+            )
+            .map_err(|err| err.downcast::<ContractError>().unwrap())
+        }
+
+        fn query_auction(app: &App, contract_addr: Addr, token_id: u32) -> Option<TokenAuction> {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr,
+                    &QueryMsg::GetTokenAuction {
+                        collection_id: COLLECTION_ID,
+                        token_id,
+                    },
+                )
+                .unwrap()
+        }
+
+        #[test]
+        fn english_auction() {
+            let (mut app, contract_addr) = setup_funded(&[USER, USER2]);
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                contract_addr.clone(),
+                &create_msg(3),
+                &[],
+            )
+            .unwrap_err();
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &create_msg(3),
+                &[],
+            )
+            .unwrap();
+
+            // held out of the draw
+            assert_eq!(remaining_tokens(&app, contract_addr.clone()), 4);
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract_addr.clone(),
+                    &create_msg(3),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::TokenAuctionExists {}
+            );
+
+            assert_eq!(
+                bid(&mut app, contract_addr.clone(), USER, 3, 1_000_000).unwrap_err(),
+                ContractError::TokenAuctionNotOpen {}
+            );
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            assert_eq!(
+                bid(&mut app, contract_addr.clone(), USER, 3, 900_000).unwrap_err(),
+                ContractError::BidTooLow {
+                    min: Uint128::from(1_000_000u128)
+                }
+            );
+            bid(&mut app, contract_addr.clone(), USER, 3, 1_000_000).unwrap();
+
+            assert_eq!(
+                bid(&mut app, contract_addr.clone(), USER2, 3, 1_200_000).unwrap_err(),
+                ContractError::BidTooLow {
+                    min: Uint128::from(1_500_000u128)
+                }
+            );
+            bid(&mut app, contract_addr.clone(), USER2, 3, 1_500_000).unwrap();
+
+            // the outbid bidder is paid back
+            assert_eq!(balance(&app, USER), Uint128::from(10_000_000u128));
+
+            // a late bid pushes the end back
+            app.update_block(|mut block| {
+                block.time = Timestamp::from_seconds(AUCTION_END_TIME - 100)
+            });
+            bid(&mut app, contract_addr.clone(), USER, 3, 2_000_000).unwrap();
+            assert_eq!(balance(&app, USER2), Uint128::from(10_000_000u128));
+
+            let auction = query_auction(&app, contract_addr.clone(), 3).unwrap();
+            assert_eq!(
+                auction.end_time,
+                Timestamp::from_seconds(AUCTION_END_TIME - 100 + EXTENSION)
+            );
+            assert_eq!(
+                auction.highest_bid,
+                Some(TokenAuctionBid {
+                    bidder: Addr::unchecked(USER),
+                    amount: Uint128::from(2_000_000u128),
+                })
+            );
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(AUCTION_END_TIME));
+            assert_eq!(
+                settle(&mut app, contract_addr.clone(), 3).unwrap_err(),
+                ContractError::TokenAuctionNotEnded {}
+            );
+
+            app.update_block(|mut block| {
+                block.time = Timestamp::from_seconds(AUCTION_END_TIME - 100 + EXTENSION)
+            });
+            settle(&mut app, contract_addr.clone(), 3).unwrap();
+
+            let cw721_addrs: Vec<AddressValMsg> = app
+                .wrap()
+                .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetCW721Addrs {})
+                .unwrap();
+            let owner: OwnerOfResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    cw721_addrs[0].address.clone(),
+                    &Cw721QueryMsg::<Empty>::OwnerOf {
+                        token_id: "3".to_string(),
+                        include_expired: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(owner.owner, USER.to_string());
+
+            // proceeds go through the revenue split
+            assert_eq!(
+                balance(&app, MAINTAINER_ADDR),
+                Uint128::from(2_000_000u128 * 3 / 10)
+            );
+            assert_eq!(remaining_tokens(&app, contract_addr.clone()), 4);
+
+            assert_eq!(
+                settle(&mut app, contract_addr, 3).unwrap_err(),
+                ContractError::TokenAuctionSettled {}
+            );
+        }
+
+        #[test]
+        fn unsold_token_returns_to_draw() {
+            let (mut app, contract_addr) = setup_funded(&[USER, USER2]);
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract_addr.clone(),
+                    &create_msg(6),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::TokenNotAvailable {}
+            );
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &create_msg(2),
+                &[],
+            )
+            .unwrap();
+            assert_eq!(remaining_tokens(&app, contract_addr.clone()), 4);
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(AUCTION_END_TIME));
+            settle(&mut app, contract_addr.clone(), 2).unwrap();
+
+            assert_eq!(remaining_tokens(&app, contract_addr.clone()), 5);
+            let auction = query_auction(&app, contract_addr.clone(), 2).unwrap();
+            assert!(auction.settled);
+            assert_eq!(auction.highest_bid, None);
+
+            // and can be auctioned again
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &ExecuteMsg::CreateTokenAuction {
+                    collection_id: COLLECTION_ID,
+                    token_id: 2,
+                    reserve_price: Uint128::from(1_000_000u128),
+                    min_increment: Uint128::from(500_000u128),
+                    start_time: Timestamp::from_seconds(AUCTION_END_TIME),
+                    end_time: Timestamp::from_seconds(AUCTION_END_TIME + 1_000),
+                    extension: EXTENSION,
+                },
+                &[],
+            )
+            .unwrap();
+            assert_eq!(remaining_tokens(&app, contract_addr), 4);
+        }
+
+        #[test]
+        fn supply_held_for_tickets_cant_be_auctioned() {
+            let (mut app, contract_addr) = setup_funded(&[USER, USER2]);
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &ExecuteMsg::CommitRandomness {
+                    commitment: format!("{:x}", Sha256::digest(b"secret")),
+                },
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            // paid for and waiting on the secret
+            app.execute_contract(
+                Addr::unchecked(USER),
+                contract_addr.clone(),
+                &mint_msg(4, None, None, None),
+                &coins(MINT_PRICE * 4, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &create_msg(1),
+                &[],
+            )
+            .unwrap();
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract_addr.clone(),
+                    &create_msg(2),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::MintCompleted {}
+            );
+            assert_eq!(remaining_tokens(&app, contract_addr), 4);
+        }
+
+        #[test]
+        fn unsold_token_reopens_bundles() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(false, false, true, Some(2), Some(1));
+            let contract_addr = cw_template_contract.addr();
+
+            fund(&mut app, USER, BUNDLE_MINT_PRICE);
+
+            // takes the only token of the first collection
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &create_msg(1),
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(AUCTION_END_TIME));
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER),
+                    contract_addr.clone(),
+                    &ExecuteMsg::MintBundle { referrer: None },
+                    &coins(BUNDLE_MINT_PRICE, NATIVE_DENOM),
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::BundleMintCompleted {}
+            );
+
+            settle(&mut app, contract_addr.clone(), 1).unwrap();
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                contract_addr.clone(),
+                &ExecuteMsg::MintBundle { referrer: None },
+                &coins(BUNDLE_MINT_PRICE, NATIVE_DENOM),
+            )
+            .unwrap();
+            assert_eq!(remaining_tokens(&app, contract_addr), 0);
+        }
+
+        #[test]
+        fn not_with_minimum_raise() {
+            let (mut app, contract_addr) = setup_funded(&[USER]);
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                minimum_raise: Some(MinimumRaise::Tokens(3)),
+                ..msg
+            })
+            .unwrap();
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract_addr.clone(),
+                    &create_msg(3),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::TokenAuctionWithMinimumRaise {}
+            );
+
+            update_config(&mut app, contract_addr.clone(), |msg| BaseInitMsg {
+                minimum_raise: None,
+                ..msg
+            })
+            .unwrap();
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &create_msg(3),
+                &[],
+            )
+            .unwrap();
+
+            // its bids would not count towards the raise
+            let err = update_config(&mut app, contract_addr, |msg| BaseInitMsg {
+                minimum_raise: Some(MinimumRaise::Tokens(3)),
+                ..msg
+            })
+            .unwrap_err();
+            assert_eq!(err, ContractError::InvalidMinimumRaise {});
+        }
+
+        fn promise_token(app: &mut App, contract_addr: Addr, address: &str, token_id: u32) {
+            let config: ConfigResponse = app
+                .wrap()
+                .query_wasm_smart(&contract_addr, &QueryMsg::GetConfig {})
+                .unwrap();
+
+            app.execute_contract(
+                contract_addr,
+                config.airdropper_addr.unwrap(),
+                &AirdropperExecuteMsg::AddPromisedTokenIDs(vec![AD_AddressTokenMsg {
+                    address: address.to_owned(),
+                    token: AD_TokenMsg {
+                        collection_id: COLLECTION_ID,
+                        token_id,
+                    },
+                }]),
+                &[],
+            )
+            .unwrap();
+        }
+
+        fn claim(
+            app: &mut App,
+            contract_addr: Addr,
+            claimer: &str,
+        ) -> Result<AppResponse, ContractError> {
+            app.execute_contract(
+                Addr::unchecked(claimer),
+                contract_addr,
+                &ExecuteMsg::AirdropClaim {
+                    minter_address: None,
+                },
+                &[],
+            )
+            .map_err(|err| err.downcast::<ContractError>().unwrap())
+        }
+
+        #[test]
+        fn auctions_and_airdrop_promises_dont_share_tokens() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(true, false, false, None, None);
+            let contract_addr = cw_template_contract.addr();
+
+            fund(&mut app, USER, 10_000_000);
+
+            promise_token(&mut app, contract_addr.clone(), USER, 2);
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    contract_addr.clone(),
+                    &create_msg(2),
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::TokenPromised {}
+            );
+
+            // promised after its auction was created
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr.clone(),
+                &create_msg(3),
+                &[],
+            )
+            .unwrap();
+            promise_token(&mut app, contract_addr.clone(), USER2, 3);
+            assert_eq!(remaining_tokens(&app, contract_addr.clone()), 4);
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            assert_eq!(
+                claim(&mut app, contract_addr.clone(), USER2).unwrap_err(),
+                ContractError::TokenAuctioned {}
+            );
+
+            claim(&mut app, contract_addr.clone(), USER).unwrap();
+            assert_eq!(remaining_tokens(&app, contract_addr.clone()), 3);
+
+            bid(&mut app, contract_addr.clone(), USER, 3, 1_000_000).unwrap();
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(AUCTION_END_TIME));
+            settle(&mut app, contract_addr.clone(), 3).unwrap();
+
+            // sold to the winner, so the promise can't be claimed either
+            assert_eq!(
+                claim(&mut app, contract_addr.clone(), USER2).unwrap_err(),
+                ContractError::TokenAuctioned {}
+            );
+
+            let cw721_addrs: Vec<AddressValMsg> = app
+                .wrap()
+                .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetCW721Addrs {})
+                .unwrap();
+            assert_eq!(
+                query_all_tokens(&app, cw721_addrs[0].address.clone()),
+                vec!["2".to_string(), "3".to_string()]
+            );
+            assert_eq!(remaining_tokens(&app, contract_addr), 3);
+        }
+    }
+
+    mod migrate {
+        use super::*;
+        use crate::msg::MigrateMsg;
+        use crate::state::{
+            CollectionInfo, TokenOrderMigration, COLLECTION_CURRENT_TOKEN_SUPPLY, CONFIG,
+            CURRENT_TOKEN_SUPPLY, CW721_COLLECTION_INFO, CW721_SHUFFLED_TOKEN_IDS,
+            LEGACY_BANK_BALANCES, LEGACY_CW721_SHUFFLED_TOKEN_IDS,
+        };
+        use crate::ContractError;
+        use cosmwasm_std::{from_slice, to_vec, DepsMut, Env, Order, Response, StdResult, Storage};
+        use cw2::{set_contract_version, ContractVersion};
+
+        fn query_contract_version(app: &App, contract_addr: Addr) -> ContractVersion {
+            from_slice(
+                &app.wrap()
+                    .query_wasm_raw(contract_addr, b"contract_info".to_vec())
+                    .unwrap()
+                    .unwrap(),
+            )
+            .unwrap()
+        }
+
+        #[test]
+        fn migrate_mid_campaign() {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
+
+            let msg = mint_msg(1, None, None, None);
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &msg,
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            let config_before: ConfigResponse = app
+                .wrap()
+                .query_wasm_smart(&cw_template_contract.addr(), &QueryMsg::GetConfig {})
+                .unwrap();
+
+            let new_code_id = app.store_code(contract_template());
+
+            // only the contract admin can migrate
+            app.migrate_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &MigrateMsg {},
+                new_code_id,
+            )
+            .unwrap_err();
+
+            app.migrate_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &MigrateMsg {},
+                new_code_id,
+            )
+            .unwrap();
+
+            let contract_version = query_contract_version(&app, cw_template_contract.addr());
+            assert_eq!(contract_version.contract, "crates.io:nft-minter");
+            assert_eq!(contract_version.version, env!("CARGO_PKG_VERSION"));
+
+            // campaign state carries over
+            let config_after: ConfigResponse = app
+                .wrap()
+                .query_wasm_smart(&cw_template_contract.addr(), &QueryMsg::GetConfig {})
+                .unwrap();
+            assert_eq!(config_before, config_after);
+
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 4);
+
+            // and the campaign keeps minting after the migration
+            app.update_block(|mut block| block.height += 1);
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                cw_template_contract.addr(),
+                &msg,
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+
+            assert_eq!(remaining_tokens(&app, cw_template_contract.addr()), 3);
+
+            let address_mints: AddressValMsg = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw_template_contract.addr(),
+                    &QueryMsg::CheckAddressMints {
+                        minter_address: USER25.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(address_mints.value, 2);
+        }
+
+        #[test]
+        fn migrate_to_other_contract_fails() {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
+
+            let whitelist_code_id = app.store_code(whitelist_contract());
+
+            // whitelist's migrate rejects the minter's stored contract name
+            app.migrate_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &whitelist::msg::MigrateMsg {},
+                whitelist_code_id,
+            )
+            .unwrap_err();
+
+            let contract_version = query_contract_version(&app, cw_template_contract.addr());
+            assert_eq!(contract_version.contract, "crates.io:nft-minter");
+        }
+
+        #[test]
+        fn migrate_submodules() {
+            let (mut app, cw_template_contract) = proper_instantiate(true, true, false, None, None);
+
+            let config: ConfigResponse = app
+                .wrap()
+                .query_wasm_smart(&cw_template_contract.addr(), &QueryMsg::GetConfig {})
+                .unwrap();
+
+            let airdropper_addr = config.airdropper_addr.unwrap();
+            let whitelist_addr = config.whitelist_addr.unwrap();
+
+            // submodules were instantiated with the minter as their admin
+            let airdropper_code_id = app.store_code(airdropper_contract());
+            app.migrate_contract(
+                cw_template_contract.addr(),
+                airdropper_addr.clone(),
+                &airdropper::msg::MigrateMsg {},
+                airdropper_code_id,
+            )
+            .unwrap();
+
+            let whitelist_code_id = app.store_code(whitelist_contract());
+            app.migrate_contract(
+                cw_template_contract.addr(),
+                whitelist_addr.clone(),
+                &whitelist::msg::MigrateMsg {},
+                whitelist_code_id,
+            )
+            .unwrap();
+
+            let ad_config: AirdropperConfig = app
+                .wrap()
+                .query_wasm_smart(airdropper_addr, &AirdropperQueryMsg::GetConfig {})
+                .unwrap();
+            assert_eq!(
+                ad_config.maintainer_addr,
+                Some(Addr::unchecked(MAINTAINER_ADDR))
+            );
+
+            let wl_config: WhitelistConfig = app
+                .wrap()
+                .query_wasm_smart(whitelist_addr, &WhitelistQueryMsg::GetConfig {})
+                .unwrap();
+            assert_eq!(wl_config.mint_price, Uint128::from(WL_MINT_PRICE));
+        }
+
+        /// writes the config and collection infos back the way 0.1.0 stored them, before
+        /// any of the optional fields were added, and marks the contract as 0.1.0
+        fn save_0_1_state(storage: &mut dyn Storage) -> StdResult<()> {
+            let config = CONFIG.load(storage)?;
+            let legacy_config = format!(
+                r#"{{"admin":"{}","maintainer_addr":"{}","start_time":"{}","end_time":"{}","total_token_supply":{},"max_per_address_mint":{},"max_per_address_bundle_mint":{},"mint_price":"{}","bundle_mint_price":"{}","mint_denom":"{}","token_code_id":{},"extension":{},"escrow_funds":{},"bundle_enabled":{},"bundle_completed":{},"bonded_denom":"{}"}}"#,
+                config.admin,
+                config.maintainer_addr.unwrap(),
+                config.start_time.nanos(),
+                config.end_time.unwrap().nanos(),
+                config.total_token_supply,
+                config.max_per_address_mint,
+                config.max_per_address_bundle_mint,
+                config.mint_price,
+                config.bundle_mint_price,
+                config.mint_denom,
+                config.token_code_id,
+                String::from_utf8(to_vec(&config.extension)?).unwrap(),
+                config.escrow_funds,
+                config.bundle_enabled,
+                config.bundle_completed,
+                config.bonded_denom,
+            );
+            storage.set(b"config", legacy_config.as_bytes());
+
+            let collection_infos: Vec<(u64, CollectionInfo)> = CW721_COLLECTION_INFO
+                .range(storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<(u64, CollectionInfo)>>>()?;
+
+            for (collection_id, info) in collection_infos {
+                let legacy_info = format!(
+                    r#"{{"id":{},"token_supply":{},"name":"{}","symbol":"{}","base_token_uri":"{}","secondary_metadata_uri":{}}}"#,
+                    info.id,
+                    info.token_supply,
+                    info.name,
+                    info.symbol,
+                    info.base_token_uri,
+                    String::from_utf8(to_vec(&info.secondary_metadata_uri)?).unwrap(),
+                );
+                storage.set(
+                    &CW721_COLLECTION_INFO.key(collection_id),
+                    legacy_info.as_bytes(),
+                );
+            }
+
+            set_contract_version(storage, "crates.io:nft-minter", "0.1.0")
+        }
+
+        fn query_token_order_migration(
+            app: &App,
+            contract_addr: Addr,
+        ) -> Option<TokenOrderMigration> {
+            app.wrap()
+                .query_wasm_smart(contract_addr, &QueryMsg::GetTokenOrderMigration {})
+                .unwrap()
+        }
+
+        /// rewrites the draw into the 0.1.x layout, as if tokens 1 and 3 of the first
+        /// collection had already been minted out of the shuffled vec
+        fn legacy_migrate(
+            deps: DepsMut,
+            _env: Env,
+            _msg: MigrateMsg,
+        ) -> Result<Response, ContractError> {
+            LEGACY_CW721_SHUFFLED_TOKEN_IDS.save(deps.storage, 101, &vec![5, 2, 4])?;
+            COLLECTION_CURRENT_TOKEN_SUPPLY.save(deps.storage, 101, &3)?;
+            CURRENT_TOKEN_SUPPLY.save(deps.storage, &3)?;
+            save_0_1_state(deps.storage)?;
+
+            Ok(Response::new())
+        }
+
+        #[test]
+        fn migrate_legacy_shuffled_token_ids() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(false, false, false, None, None);
+            let minter_addr = cw_template_contract.addr();
+
+            let config_before: ConfigResponse = app
+                .wrap()
+                .query_wasm_smart(&minter_addr, &QueryMsg::GetConfig {})
+                .unwrap();
+            let collection_infos_before: Vec<(u64, CollectionInfo)> = app
+                .wrap()
+                .query_wasm_smart(
+                    &minter_addr,
+                    &QueryMsg::GetCw721CollectionInfo {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+
+            let legacy_code_id = app.store_code(Box::new(
+                ContractWrapper::new(
+                    crate::contract::execute,
+                    crate::contract::instantiate,
+                    crate::query::query,
+                )
+                .with_reply(crate::contract::reply)
+                .with_migrate(legacy_migrate),
+            ));
+            app.migrate_contract(
+                Addr::unchecked(ADMIN),
+                minter_addr.clone(),
+                &MigrateMsg {},
+                legacy_code_id,
+            )
+            .unwrap();
+
+            let contract_version = query_contract_version(&app, minter_addr.clone());
+            assert_eq!(contract_version.version, "0.1.0");
+
+            let new_code_id = app.store_code(contract_template());
+            app.migrate_contract(
+                Addr::unchecked(ADMIN),
+                minter_addr.clone(),
+                &MigrateMsg {},
+                new_code_id,
+            )
+            .unwrap();
+
+            let contract_version = query_contract_version(&app, minter_addr.clone());
+            assert_eq!(contract_version.version, env!("CARGO_PKG_VERSION"));
+
+            // the 0.1.0 config and collections read back with every later option off
+            let config_after: ConfigResponse = app
+                .wrap()
+                .query_wasm_smart(&minter_addr, &QueryMsg::GetConfig {})
+                .unwrap();
+            assert_eq!(config_before, config_after);
+
+            let collection_infos_after: Vec<(u64, CollectionInfo)> = app
+                .wrap()
+                .query_wasm_smart(
+                    &minter_addr,
+                    &QueryMsg::GetCw721CollectionInfo {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(collection_infos_before, collection_infos_after);
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            let msg = mint_msg(1, None, None, None);
+
+            // nothing is drawn until the legacy order is in the swap table
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    minter_addr.clone(),
+                    &msg,
+                    &[coin(MINT_PRICE, NATIVE_DENOM)],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::TokenOrderMigrationInProgress {}
+            );
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                minter_addr.clone(),
+                &ExecuteMsg::MigrateTokenOrder { limit: None },
+                &[],
+            )
+            .unwrap();
+            assert_eq!(query_token_order_migration(&app, minter_addr.clone()), None);
+
+            for _ in 0..3 {
+                app.execute_contract(
+                    Addr::unchecked(USER25),
+                    minter_addr.clone(),
+                    &msg,
+                    &[coin(MINT_PRICE, NATIVE_DENOM)],
+                )
+                .unwrap();
+                app.update_block(|mut block| block.height += 1);
+            }
+
+            // supply ran out with exactly the tokens left in the legacy vec
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                minter_addr.clone(),
+                &msg,
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap_err();
+
+            let cw721_addrs: Vec<AddressValMsg> = app
+                .wrap()
+                .query_wasm_smart(&minter_addr, &QueryMsg::GetCW721Addrs {})
+                .unwrap();
+
+            assert_eq!(
+                query_all_tokens(&app, cw721_addrs[0].address.clone()),
+                vec!["2", "4", "5"]
+            );
+        }
+
+        const LARGE_SUPPLY: u32 = 10_000;
+
+        /// 0.1.x order of a large, untouched campaign. both collections hold every token,
+        /// the first in steps of 7 and the second reversed
+        fn legacy_token_ids(collection_id: u64) -> Vec<u32> {
+            match collection_id {
+                101 => (0..LARGE_SUPPLY)
+                    .map(|position| position * 7 % LARGE_SUPPLY + 1)
+                    .collect(),
+                _ => (1..=LARGE_SUPPLY).rev().collect(),
+            }
+        }
+
+        fn legacy_large_migrate(
+            deps: DepsMut,
+            _env: Env,
+            _msg: MigrateMsg,
+        ) -> Result<Response, ContractError> {
+            for collection_id in [101, 102] {
+                LEGACY_CW721_SHUFFLED_TOKEN_IDS.save(
+                    deps.storage,
+                    collection_id,
+                    &legacy_token_ids(collection_id),
+                )?;
+            }
+            save_0_1_state(deps.storage)?;
+
+            Ok(Response::new())
+        }
+
+        #[test]
+        fn migrate_large_legacy_token_order() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(false, false, false, Some(2), Some(LARGE_SUPPLY));
+            let minter_addr = cw_template_contract.addr();
+
+            let legacy_code_id = app.store_code(Box::new(
+                ContractWrapper::new(
+                    crate::contract::execute,
+                    crate::contract::instantiate,
+                    crate::query::query,
+                )
+                .with_reply(crate::contract::reply)
+                .with_migrate(legacy_large_migrate),
+            ));
+            app.migrate_contract(
+                Addr::unchecked(ADMIN),
+                minter_addr.clone(),
+                &MigrateMsg {},
+                legacy_code_id,
+            )
+            .unwrap();
+
+            let new_code_id = app.store_code(contract_template());
+            app.migrate_contract(
+                Addr::unchecked(ADMIN),
+                minter_addr.clone(),
+                &MigrateMsg {},
+                new_code_id,
+            )
+            .unwrap();
+
+            // the migration itself only leaves a cursor behind
+            assert_eq!(
+                query_token_order_migration(&app, minter_addr.clone()),
+                Some(TokenOrderMigration {
+                    collection_id: 101,
+                    position: 0,
+                })
+            );
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(ADMIN),
+                    minter_addr.clone(),
+                    &ExecuteMsg::ShuffleTokenOrder { limit: None },
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::TokenOrderMigrationInProgress {}
+            );
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER),
+                    minter_addr.clone(),
+                    &ExecuteMsg::MigrateTokenOrder { limit: Some(0) },
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidTokenOrderMigrationLimit {}
+            );
+
+            // the limit is capped, so every call stays within a block
+            app.execute_contract(
+                Addr::unchecked(USER),
+                minter_addr.clone(),
+                &ExecuteMsg::MigrateTokenOrder {
+                    limit: Some(2 * LARGE_SUPPLY),
+                },
+                &[],
+            )
+            .unwrap();
+            assert_eq!(
+                query_token_order_migration(&app, minter_addr.clone()),
+                Some(TokenOrderMigration {
+                    collection_id: 102,
+                    position: 0,
+                })
+            );
+
+            let mut calls = 0;
+            while query_token_order_migration(&app, minter_addr.clone()).is_some() {
+                app.execute_contract(
+                    Addr::unchecked(USER),
+                    minter_addr.clone(),
+                    &ExecuteMsg::MigrateTokenOrder { limit: None },
+                    &[],
+                )
+                .unwrap();
+                calls += 1;
+            }
+            assert_eq!(calls, 10);
+
+            let err = app
+                .execute_contract(
+                    Addr::unchecked(USER),
+                    minter_addr.clone(),
+                    &ExecuteMsg::MigrateTokenOrder { limit: None },
+                    &[],
+                )
+                .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::NoTokenOrderMigration {}
+            );
+
+            // every position holds the token the legacy vec had there
+            for collection_id in [101, 102] {
+                for (position, token_id) in legacy_token_ids(collection_id).into_iter().enumerate()
+                {
+                    let stored: Option<u32> = app
+                        .wrap()
+                        .query_wasm_raw(
+                            minter_addr.clone(),
+                            CW721_SHUFFLED_TOKEN_IDS
+                                .key((collection_id, position as u32))
+                                .to_vec(),
+                        )
+                        .unwrap()
+                        .map(|value| from_slice(&value).unwrap());
+
+                    assert_eq!(stored.unwrap_or(position as u32 + 1), token_id);
+                }
+            }
+
+            assert_eq!(
+                remaining_tokens(&app, minter_addr.clone()),
+                2 * LARGE_SUPPLY
+            );
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+
+            app.execute_contract(
+                Addr::unchecked(USER25),
+                minter_addr.clone(),
+                &mint_msg(1, None, None, None),
+                &[coin(MINT_PRICE, NATIVE_DENOM)],
+            )
+            .unwrap();
+            assert_eq!(remaining_tokens(&app, minter_addr), 2 * LARGE_SUPPLY - 1);
+        }
+
+        /// escrow of the 0.1.x layout, which was keyed by recipient only
+        fn legacy_escrow_migrate(
+            deps: DepsMut,
+            _env: Env,
+            _msg: MigrateMsg,
+        ) -> Result<Response, ContractError> {
+            LEGACY_BANK_BALANCES.save(
+                deps.storage,
+                Addr::unchecked(ADMIN),
+                &Uint128::from(1_400_000u128),
+            )?;
+            LEGACY_BANK_BALANCES.save(
+                deps.storage,
+                Addr::unchecked(MAINTAINER_ADDR),
+                &Uint128::from(600_000u128),
+            )?;
+            save_0_1_state(deps.storage)?;
+
+            Ok(Response::new())
+        }
+
+        #[test]
+        fn migrate_legacy_escrow_balances() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(false, false, false, None, None);
+            let minter_addr = cw_template_contract.addr();
+
+            let legacy_code_id = app.store_code(Box::new(
+                ContractWrapper::new(
+                    crate::contract::execute,
+                    crate::contract::instantiate,
+                    crate::query::query,
+                )
+                .with_reply(crate::contract::reply)
+                .with_migrate(legacy_escrow_migrate),
+            ));
+            app.migrate_contract(
+                Addr::unchecked(ADMIN),
+                minter_addr.clone(),
+                &MigrateMsg {},
+                legacy_code_id,
+            )
+            .unwrap();
+
+            let new_code_id = app.store_code(contract_template());
+            app.migrate_contract(
+                Addr::unchecked(ADMIN),
+                minter_addr.clone(),
+                &MigrateMsg {},
+                new_code_id,
+            )
+            .unwrap();
+
+            // the balances are now held in `mint_denom`
+            let escrow_bals: Vec<AddrBal> = app
+                .wrap()
+                .query_wasm_smart(
+                    minter_addr,
+                    &QueryMsg::GetEscrowBalances {
+                        start_after: None,
+                        start_after_denom: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                escrow_bals,
+                vec![
+                    AddrBal {
+                        addr: Addr::unchecked(ADMIN),
+                        denom: NATIVE_DENOM.to_string(),
+                        balance: Uint128::from(1_400_000u128)
+                    },
+                    AddrBal {
+                        addr: Addr::unchecked(MAINTAINER_ADDR),
+                        denom: NATIVE_DENOM.to_string(),
+                        balance: Uint128::from(600_000u128)
+                    },
+                ]
+            );
+        }
+    }
+
+    mod misc_tests {
+        use super::*;
+        use crate::state::RoyaltyInfo;
+        use cosmwasm_std::Decimal;
+
+        #[test]
+        fn test_decimal() {
+            let share1 = 10000;
+            let share2 = 5000;
+            let share3 = 2500;
+            let share4 = 1000;
+            let share5 = 750;
+            let share6 = 25;
+
+            println!(
+                "{:?}",
+                (Uint128::from(MINT_PRICE) * Decimal::percent(share1) / Uint128::from(100u128))
+            );
+            println!(
+                "{:?}",
+                (Uint128::from(MINT_PRICE) * Decimal::percent(share2) / Uint128::from(100u128))
+            );
+            println!(
+                "{:?}",
+                (Uint128::from(MINT_PRICE) * Decimal::percent(share3) / Uint128::from(100u128))
+            );
+            println!(
+                "{:?}",
+                (Uint128::from(MINT_PRICE) * Decimal::percent(share4) / Uint128::from(100u128))
+            );
+            println!(
+                "{:?}",
+                (Uint128::from(MINT_PRICE) * Decimal::percent(share5) / Uint128::from(100u128))
+            );
+            println!(
+                "{:?}",
+                (Uint128::from(MINT_PRICE) * Decimal::percent(share6) / Uint128::from(100u128))
+            );
+        }
+
+        #[test]
+        fn test_sort() {
+            let mut my_vec: Vec<RoyaltyInfo> = vec![
+                RoyaltyInfo {
+                    addr: Addr::unchecked(USER.to_owned()),
+                    bps: 3300,
+                    is_primary: false,
+                },
+                RoyaltyInfo {
+                    addr: Addr::unchecked(USER2.to_owned()),
+                    bps: 5000,
+                    is_primary: false,
+                },
+                RoyaltyInfo {
+                    addr: Addr::unchecked(USER3.to_owned()),
+                    bps: 500,
+                    is_primary: true,
+                },
+                RoyaltyInfo {
+                    addr: Addr::unchecked(USER10.to_owned()),
+                    bps: 1200,
+                    is_primary: false,
+                },
+            ];
+
+            my_vec.sort_by(|a, b| a.is_primary.cmp(&b.is_primary));
+
+            println!("{:?}", my_vec);
+            //assert_eq!(5, 7);
+        }
+    }
+
+    mod bulk_tests {
+        use super::*;
+
+        #[test]
+        fn test_large_datasets() {
+            let (mut app, cw_template_contract) =
+                proper_instantiate(true, true, true, Some(3), Some(3000));
+
+            let config: ConfigResponse = app
+                .wrap()
+                .query_wasm_smart(&cw_template_contract.addr(), &QueryMsg::GetConfig {})
+                .unwrap();
+
+            let mut msg: BaseInitMsg = BaseInitMsg {
+                maintainer_address: None,
+                start_time: config.start_time,
+                end_time: config.end_time,
+                max_per_address_mint: config.max_per_address_mint,
+                max_per_address_bundle_mint: config.max_per_address_bundle_mint,
+                mint_price: config.mint_price,
+                bundle_mint_price: config.bundle_mint_price,
+                mint_denom: config.mint_denom.to_string(),
+                escrow_funds: false,
+                bundle_enabled: config.bundle_enabled,
+                airdropper_address: None,
+                whitelist_address: None,
+                dutch_auction: None,
+                bonding_curve: None,
+                bulk_discounts: None,
+                collection_selection: None,
+                denom_mint_prices: None,
+                cw20_payment: None,
+                oracle_pricing: None,
+                referral_bps: None,
+                refund_period: None,
+                minimum_raise: None,
+                raffle: None,
+            };
+
+            msg.max_per_address_mint = 40000;
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &ExecuteMsg::UpdateConfig(msg),
+                &[],
+            )
+            .unwrap();
+
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+            app.update_block(|mut block| block.height += 1);
+
+            let msg = ExecuteMsg::MintBundle { referrer: None };
+
+            for _ in 0u32..=5 {
+                let _res = app
+                    .execute_contract(
+                        Addr::unchecked(USER25),
+                        cw_template_contract.addr(),
+                        &msg,
+                        &[coin(5_000_000, NATIVE_DENOM)],
+                    )
+                    .unwrap();
+
+                app.update_block(|mut block| block.time = Timestamp::from_seconds(MINT_START_TIME));
+                app.update_block(|mut block| block.height += 1);
+            }
+
+            let config: ConfigResponse = app
+                .wrap()
+                .query_wasm_smart(&cw_template_contract.addr(), &QueryMsg::GetConfig {})
+                .unwrap();
+            println!("config {:?}", config);
+
+            let _res = app
+                .execute_contract(
+                    Addr::unchecked(USER25),
+                    cw_template_contract.addr(),
+                    &msg,
+                    &[coin(5_000_000, NATIVE_DENOM)],
+                )
+                .unwrap_err();
+
+            let per_collection_supplies: Vec<(u64, u32)> = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw_template_contract.addr(),
+                    &QueryMsg::GetCollectionCurrentTokenSupply {
+                        start_after: None,
+                        limit: Some(50),
+                    },
+                )
+                .unwrap();
+
+            println!("per_collection_supplies {:?}", per_collection_supplies);
+
+            let msg = mint_msg(1, None, None, None);
 
             for _ in 0u32..=5 {
                 // 5987
@@ -3,6 +3,7 @@ mod error;
 pub mod helpers;
 pub mod integration_tests;
 pub mod msg;
+pub mod provenance;
 pub mod query;
 pub mod state;
 
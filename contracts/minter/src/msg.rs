@@ -1,6 +1,11 @@
-use crate::state::SharedCollectionInfo;
+use crate::state::{
+    BondingCurve, BulkDiscounts, CollectionSelection, Cw20Payment, DutchAuction, MinimumRaise,
+    MintCodeDiscount, OraclePricing, Raffle, RaffleProgress, RaiseProgress, SharedCollectionInfo,
+};
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Binary, CosmosMsg, Empty, Timestamp, Uint128};
+use cosmwasm_std::{Addr, Binary, Coin, CosmosMsg, Decimal, Empty, Timestamp, Uint128};
+use cw20::Cw20ReceiveMsg;
+use cw721::Cw721ReceiveMsg;
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -49,6 +54,77 @@ pub struct BaseInitMsg {
     pub bundle_enabled: bool,
     pub airdropper_address: Option<String>,
     pub whitelist_address: Option<String>,
+    /// prices the public mint with a Dutch auction instead of `mint_price`
+    pub dutch_auction: Option<DutchAuction>,
+    /// prices public mints along a curve of the supply sold instead of `mint_price`.
+    /// bundles then cost the curve price of their tokens. can't be combined with
+    /// `dutch_auction`
+    pub bonding_curve: Option<BondingCurve>,
+    /// quantity discounts for the whitelist and public mints. public tiers can't be
+    /// combined with `dutch_auction`
+    pub bulk_discounts: Option<BulkDiscounts>,
+    /// how random mints pick a collection. defaults to `CollectionSelection::Uniform`
+    pub collection_selection: Option<CollectionSelection>,
+    /// other denoms the public mint can be paid in, each with its own price per token.
+    /// bulk discounts apply to them too. whitelist, promised and bundle mints are
+    /// paid in `mint_denom`. can't be combined with `dutch_auction` or `bonding_curve`
+    pub denom_mint_prices: Option<Vec<Coin>>,
+    /// lets public, whitelist and bundle mints be paid in a cw20 token through
+    /// `Receive`. can't be combined with `dutch_auction` or `bonding_curve`
+    pub cw20_payment: Option<Cw20PaymentMsg>,
+    /// prices public and bundle mints paid in `mint_denom` in a reference unit,
+    /// converted by an oracle. can't be combined with `dutch_auction`
+    pub oracle_pricing: Option<OraclePricingMsg>,
+    /// bps of every paid mint escrowed for the mint's `referrer`, taken before
+    /// `mint_revenue_share`. at most 10000
+    pub referral_bps: Option<u32>,
+    /// seconds after minting that the holder of a paid token can send it back with
+    /// `SendNft` for what it was paid. needs `escrow_funds`, which stay locked until
+    /// the last window closes. can't be combined with `dutch_auction`
+    pub refund_period: Option<u64>,
+    /// threshold to reach by `end_time`, which it needs. payments are escrowed until
+    /// it is met and can be claimed back with `ClaimRaiseRefund` if it is missed.
+    /// fixed once minting starts, and can't be combined with `dutch_auction`,
+    /// `refund_period` or token auctions
+    pub minimum_raise: Option<MinimumRaise>,
+    /// fixed once a ticket is entered. tickets are at most `max_per_address_mint` per
+    /// address, and the deadline is before `end_time`. can't be combined with
+    /// `dutch_auction`, `bonding_curve`, `oracle_pricing` or `minimum_raise`
+    pub raffle: Option<Raffle>,
+}
+
+#[cw_serde]
+pub struct OraclePricingMsg {
+    /// contract answering `OracleQueryMsg`
+    pub oracle_address: String,
+    /// how far over the converted price a payment can be, to cover the rate
+    /// moving after it was quoted. the excess is refunded. at most 10000
+    pub slippage_bps: u32,
+}
+
+#[cw_serde]
+pub struct Cw20PaymentMsg {
+    /// cw20 contract accepted as payment
+    pub token_address: String,
+    /// price of one public mint token. bulk discounts apply to it
+    pub mint_price: Uint128,
+    /// price of one whitelist mint token, instead of the whitelist's `mint_price`
+    pub whitelist_mint_price: Option<Uint128>,
+    pub bundle_mint_price: Uint128,
+}
+
+/// mints paid by a cw20 `Send`. the token's sender is the minter
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// whitelist or public mint, like `ExecuteMsg::Mint`
+    Mint {
+        quantity: Option<u32>,
+        collection_id: Option<u64>,
+        referrer: Option<String>,
+    },
+    MintBundle {
+        referrer: Option<String>,
+    },
 }
 
 #[cw_serde]
@@ -64,6 +140,20 @@ pub struct CollectionInfoMsg {
     /// optional secondary metadata resource that is intended to be dynamic
     /// and extensible to the creator's desires
     pub secondary_metadata_uri: Option<String>,
+    /// hex encoded sha256 over the collection's ordered metadata, see
+    /// `provenance::provenance_hash`. locked once `start_time` passes
+    pub provenance_hash: Option<String>,
+    /// replaces `mint_price` for public mints that pick this collection. a mint that
+    /// doesn't pick one pays the highest price among the mintable collections, as it
+    /// isn't known which one it lands in until it is drawn, and nothing is refunded
+    /// when the draw lands in a cheaper one. Dutch auction and bonding curve prices
+    /// still apply to every collection
+    pub mint_price: Option<Uint128>,
+    /// `false` keeps the collection out of `Mint`, picked or random. defaults to `true`
+    pub mint_enabled: Option<bool>,
+    /// fixed weight of the collection with `CollectionSelection::Weighted`, in
+    /// place of its remaining supply
+    pub weight: Option<u32>,
 }
 
 /// Shared Collection Info that stores revenue/royalty split as well the optional secondary metadata
@@ -160,31 +250,199 @@ pub enum ExecuteMsg {
     /// the optional `minter_address` is if a maintainer wants to `push`
     /// an nft to the address rather than having the recipient come `pull`
     /// the promised mint by executing this function themselves
+    /// `quantity` mints several tokens in one go, paying the total price, and
+    /// defaults to 1. whitelist and promised mints of more than 1 need a
+    /// whitelist/airdropper that takes the `...By` count messages
+    /// `collection_id` mints out of that collection instead of a random one
+    /// `referrer` is credited with the mint and earns `referral_bps` of its price
+    /// `code` is the plaintext of a mint code registered for the sender, redeemed for a
+    /// discount
     Mint {
         is_promised_mint: bool,
         minter_address: Option<String>,
+        quantity: Option<u32>,
+        collection_id: Option<u64>,
+        referrer: Option<String>,
+        code: Option<String>,
     },
-    MintBundle {},
+    /// one token out of every bundle collection, drawn as it is paid. unavailable
+    /// while mints are drawn later: through the randomness proxy, as tickets, or
+    /// against a hidden commitment
+    MintBundle { referrer: Option<String> },
+    /// cw20 `Send` of the `cw20_payment` token. `msg` is a `ReceiveMsg`
+    Receive(Cw20ReceiveMsg),
+    /// cw721 `SendNft` of a token minted here, refunding it within `refund_period`.
+    /// `msg` is ignored
+    ReceiveNft(Cw721ReceiveMsg),
     /// airdrop claim is intended for 1:1s or other creator criteria for
     /// granting ownership of specific `token_id`s. This is controlled in the
     /// contract attached to `AIRDROPPER_ADDR`
     /// the optional `minter_address` allows an address to `pull` (execute
-    /// this themselves) or an admin to `push` the token to them
-    AirdropClaim {
-        minter_address: Option<String>,
-    },
+    /// this themselves) or an admin to `push` the token to them. fails while a
+    /// promised token is held by an auction, or after one sold it
+    AirdropClaim { minter_address: Option<String> },
     /// Calls the attached airdropper contract and removes the `token_id`s
     /// from `SHUFFLED_TOKEN_IDS` and `TOKEN_ID_POSITIONS` so they will not
     /// accidentally get minted.  Once complete, it'll shuffle the token order
     CleanClaimedTokensFromShuffle {},
-    /// shuffles the token order. takes a lot of gas
-    ShuffleTokenOrder {},
+    /// shuffles the token order, `limit` swaps per call (default 1000). a shuffle
+    /// that does not finish stores its progress for the next call to pick up, and
+    /// minting is blocked until it completes. non admins pay a fee to start one,
+    /// and can only start one before `start_time`
+    ShuffleTokenOrder { limit: Option<u32> },
+    /// moves the token order of a campaign migrated from 0.1.x into the swap table,
+    /// `limit` positions per call (default 1000). minting and shuffles are blocked
+    /// until it completes. anyone can push it along
+    MigrateTokenOrder { limit: Option<u32> },
     /// Allows this contract to pass execution messages to its submodules
     SubmoduleHook(ExecutionTarget, CosmosMsg<Empty>),
     /// Allows an admin/maintainer to disburse funds in escrow
     DisburseFunds {},
+    /// Pays back everything the sender paid in while the `minimum_raise` was
+    /// pending, once `end_time` has passed without it being met
+    ClaimRaiseRefund {},
+    /// Enters `tickets` into the raffle, paying `mint_price` for each
+    EnterRaffle { tickets: u32 },
+    /// Anyone can settle the raffle once the deadline passes. the first call sets the
+    /// winner count and binds the next drand round or a secret committed after the
+    /// deadline. later calls draw up to `limit` (default 25) winners with it, minting
+    /// each a token. it is settled when all winners are drawn
+    SettleRaffle { limit: Option<u32> },
+    /// Pays back the sender's losing raffle tickets once the raffle is settled
+    ClaimRaffleRefund {},
+    /// Admin/maintainer auctions a token still in the draw, taking it out of the
+    /// draw. tokens assigned by the airdropper can't be auctioned, and neither can
+    /// any token of a campaign with a `minimum_raise`. bids are in `mint_denom`, see
+    /// `TokenAuction`
+    CreateTokenAuction {
+        collection_id: u64,
+        token_id: u32,
+        reserve_price: Uint128,
+        min_increment: Uint128,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        extension: u64,
+    },
+    /// Bids the funds sent, refunding the bid it beats
+    BidTokenAuction { collection_id: u64, token_id: u32 },
+    /// Anyone can settle an auction that has ended. the token is minted to the
+    /// highest bidder and the bid split by `mint_revenue_share`, or without bids
+    /// the token goes back into the draw
+    SettleTokenAuction { collection_id: u64, token_id: u32 },
+    /// Admin/maintainer commits to the hex encoded sha256 of a secret before
+    /// `start_time`, or to a new one once the last is revealed. While a secret is
+    /// hidden, mints are paid as tickets drawn with it once revealed and bundles
+    /// can't be minted. Once it is revealed, mints and bundles are drawn as they are
+    /// paid with the secret mixed in, until the next commitment. Shuffles wait for
+    /// the reveal and mix it in
+    CommitRandomness { commitment: String },
+    /// Reveals the secret behind `CommitRandomness`
+    RevealRandomness { secret: String },
+    /// Admin/maintainer sets the drand network before `start_time`. Once set,
+    /// mints are paid as tickets drawn with a round published after the payment,
+    /// and shuffles mix in the latest submitted round. Not available to campaigns
+    /// with `bundle_enabled`
+    ConfigureDrand {
+        pubkey: Binary,
+        /// seconds
+        genesis_time: u64,
+        /// seconds
+        period: u64,
+    },
+    /// Anyone can submit a drand round. The signature is verified against the
+    /// configured public key
+    SubmitDrandBeacon {
+        round: u64,
+        previous_signature: Binary,
+        signature: Binary,
+    },
+    /// Admin/maintainer sets (or clears) a randomness proxy before `start_time`.
+    /// While set, `Mint` only requests randomness from the proxy and the token
+    /// is assigned once the proxy calls back with `ReceiveRandomness`
+    ConfigureRandomnessProxy { address: Option<String> },
+    /// Callback from the randomness proxy that finalizes a pending mint
+    ReceiveRandomness { callback: RandomnessCallback },
+    /// Refunds a pending mint the proxy never called back for. Anyone can refund
+    /// it once it has expired. Public mints get their mint back, whitelist and
+    /// promised mint counts stay used
+    RefundPendingMint { job_id: String },
+    /// Admin/maintainer enables (or disables) delayed assignment before
+    /// `start_time`. While enabled, `Mint` only records a paid ticket and the
+    /// token is drawn by `SettleMintTicket` in a later block, at most
+    /// `expiry_blocks` after the ticket was paid for. Tickets are drawn with a
+    /// committed secret or a drand round, so one of them has to be set up too
+    ConfigureDelayedMint { expiry_blocks: Option<u64> },
+    /// Draws the token for a ticket. Anyone can settle a ticket once the block
+    /// it was paid in has passed, the secret it was paid against is revealed and
+    /// its drand round is submitted
+    SettleMintTicket { minter_address: String, height: u64 },
+    /// Refunds an expired ticket to its minter. Public mints get their mint
+    /// back, whitelist and promised mint counts stay used
+    RefundMintTicket { minter_address: String, height: u64 },
+    /// Pays out a public buyer's Dutch auction rebate once the clearing price is
+    /// known, and releases the rest of what they paid to the revenue split. Anyone
+    /// can settle an address
+    SettleAuctionRebate { minter_address: String },
+    /// Sets a collection's own public `mint_price` and whether it can be minted.
+    /// admin or maintainer, also while the mint is running
+    UpdateCollectionMint {
+        collection_id: u64,
+        mint_price: Option<Uint128>,
+        mint_enabled: bool,
+    },
+    /// Admin/maintainer sets a collection's provenance hash before `start_time`
+    UpdateProvenanceHash {
+        collection_id: u64,
+        provenance_hash: String,
+    },
+    /// Admin/maintainer registers mint codes by hash, replacing any with the same
+    /// hash. also while the mint is running
+    RegisterMintCodes { codes: Vec<MintCodeMsg> },
+    /// Admin/maintainer drops mint codes by hash
+    RemoveMintCodes { hashes: Vec<String> },
+}
+
+#[cw_serde]
+pub struct MintCodeMsg {
+    /// hex encoded sha256 of `<address>:<code>`. only `address` can redeem the code,
+    /// so it is useless to anyone who reads it out of a pending transaction
+    pub hash: String,
+    /// tokens the code can be used for
+    pub uses: u32,
+    pub discount: MintCodeDiscount,
+}
+
+/// Execute msg sent to the randomness proxy, Nois style
+#[cw_serde]
+pub enum ProxyExecuteMsg {
+    GetNextRandomness { job_id: String },
+}
+
+/// Query msg sent to the price oracle
+#[cw_serde]
+pub enum OracleQueryMsg {
+    /// returns `OraclePriceResponse`
+    Price { denom: String },
+}
+
+#[cw_serde]
+pub struct OraclePriceResponse {
+    /// reference units one unit of `denom` is worth
+    pub rate: Decimal,
+}
+
+/// Randomness the proxy delivers for a `job_id`
+#[cw_serde]
+pub struct RandomnessCallback {
+    pub job_id: String,
+    pub randomness: Binary,
 }
 
+/// Migration message. The state transformations that run are selected from the
+/// cw2 version stored in the contract, so no fields are required
+#[cw_serde]
+pub struct MigrateMsg {}
+
 #[cw_serde]
 pub enum QueryMsg {
     /// Gets Config + some other fields and returns `ConfigResponse`
@@ -204,6 +462,8 @@ pub enum QueryMsg {
     GetEscrowBalances {
         /// address
         start_after: Option<String>,
+        /// denom of `start_after`, to resume part-way through an address' balances
+        start_after_denom: Option<String>,
         limit: Option<u32>,
     },
     GetCw721CollectionInfo {
@@ -225,6 +485,97 @@ pub enum QueryMsg {
     GetRemainingTokens {},
     /// Gets all the cw721 addresses attached to this contract
     GetCW721Addrs {},
+    /// Gets the randomness commitment, if any. returns Option<RandomnessCommit>
+    GetRandomnessCommit {},
+    /// Gets the drand network, if configured. returns Option<DrandConfig>
+    GetDrandConfig {},
+    /// Gets a verified drand round. returns Option<DrandBeacon>
+    GetDrandBeacon { round: u64 },
+    /// Gets mints waiting on the randomness proxy. returns Vec<PendingMint>
+    GetPendingMints {
+        /// job_id
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Gets the cursor of a shuffle that is part-way through.
+    /// returns Option<ShuffleProgress>
+    GetShuffleProgress {},
+    /// Gets the cursor of a token order migration that is part-way through.
+    /// returns Option<TokenOrderMigration>
+    GetTokenOrderMigration {},
+    /// Gets the order minted tokens of a collection went out in, so the drop can be
+    /// checked against its provenance hash. returns Vec<(token_id, mint_order)>
+    GetTokenMintOrder {
+        collection_id: u64,
+        /// token_id
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    /// Gets every seeded draw in the order they were made, with the inputs needed
+    /// to re-run them. returns Vec<(draw_id, DrawRecord)>
+    GetDrawHistory {
+        /// draw_id
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Quotes the total price of the next `quantity` mints, after the bulk discount
+    /// of `minter_address`. before `start_time` a whitelisted `minter_address` is
+    /// quoted the whitelist price. `collection_id` quotes a mint that picks that
+    /// collection, and without it the priciest mintable collection is quoted. `denom` quotes one of `denom_mint_prices`, or `cw20:<token_address>`
+    /// the cw20 token, instead of `mint_denom`. returns `MintPriceResponse`
+    GetMintPrice {
+        quantity: u32,
+        minter_address: Option<String>,
+        collection_id: Option<u64>,
+        denom: Option<String>,
+    },
+    /// Gets the live Dutch auction price. returns `AuctionPriceResponse`
+    GetAuctionPrice {},
+    /// Gets an address' unsettled auction purchases. returns Option<AuctionPurchase>
+    GetAuctionPurchase { minter_address: String },
+    /// Gets an address' unsettled mint tickets. returns Vec<MintTicket>
+    GetMintTickets {
+        minter_address: String,
+        /// height
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Gets how many mints each referrer brought in and what they earned.
+    /// returns Vec<(referrer, Referral)>
+    GetReferrals {
+        /// referrer
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Gets a mint code by hash. returns Option<MintCode>
+    GetMintCode { hash: String },
+    /// Gets what a token can be refunded for, while it still can be.
+    /// returns Option<MintReceipt>
+    GetMintReceipt { collection_id: u64, token_id: u32 },
+    /// Gets until when refunds lock the escrow. returns Option<Timestamp>
+    GetEscrowLockedUntil {},
+    /// Gets how far the `minimum_raise` is. returns RaiseStatusResponse
+    GetRaiseStatus {},
+    /// Gets what an address can claim back if the `minimum_raise` is missed.
+    /// returns Vec<Coin>
+    GetRaiseContributions { address: String },
+    /// Gets the raffle's tickets and winners so far. returns RaffleResponse
+    GetRaffle {},
+    /// Gets an address' tickets, wins and whether the rest were refunded.
+    /// returns Option<RaffleEntry>
+    GetRaffleEntry { address: String },
+    /// Gets addresses that won and how many tickets. returns Vec<(Addr, u32)>
+    GetRaffleWinners {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Gets an auctioned token. returns Option<TokenAuction>
+    GetTokenAuction { collection_id: u64, token_id: u32 },
+    /// Gets auctioned tokens by (collection_id, token_id). returns Vec<TokenAuction>
+    GetTokenAuctions {
+        start_after: Option<(u64, u32)>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
@@ -260,6 +611,55 @@ pub struct ConfigResponse {
     pub extension: SharedCollectionInfo,
     pub bundle_enabled: bool,
     pub bundle_completed: bool,
+    pub dutch_auction: Option<DutchAuction>,
+    pub bonding_curve: Option<BondingCurve>,
+    pub bulk_discounts: Option<BulkDiscounts>,
+    pub collection_selection: Option<CollectionSelection>,
+    pub denom_mint_prices: Option<Vec<Coin>>,
+    pub cw20_payment: Option<Cw20Payment>,
+    pub oracle_pricing: Option<OraclePricing>,
+    pub referral_bps: Option<u32>,
+    pub refund_period: Option<u64>,
+    pub minimum_raise: Option<MinimumRaise>,
+    pub raffle: Option<Raffle>,
+}
+
+#[cw_serde]
+pub struct RaffleResponse {
+    pub raffle: Option<Raffle>,
+    pub progress: RaffleProgress,
+    /// all winners are drawn and losing tickets can be refunded
+    pub settled: bool,
+}
+
+#[cw_serde]
+pub struct RaiseStatusResponse {
+    pub minimum_raise: Option<MinimumRaise>,
+    pub progress: RaiseProgress,
+    pub met: bool,
+    /// `end_time` passed without it being met, so payments can be claimed back
+    pub missed: bool,
+}
+
+#[cw_serde]
+pub struct MintPriceResponse {
+    pub price: Uint128,
+    pub denom: String,
+    /// bulk discount included in `price`
+    pub discount_bps: u32,
+    /// `price` in the reference unit, before the oracle converted it
+    pub reference_price: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct AuctionPriceResponse {
+    /// current public mint price
+    pub price: Uint128,
+    /// price after the next drop, none once the floor is reached
+    pub next_price: Option<Uint128>,
+    pub next_drop_time: Option<Timestamp>,
+    /// price rebates are settled against, once it is known
+    pub clearing_price: Option<Uint128>,
 }
 
 #[cw_serde]
@@ -298,6 +698,7 @@ pub struct AddressValMsg {
 #[cw_serde]
 pub struct AddrBal {
     pub addr: Addr,
+    pub denom: String,
     pub balance: Uint128,
 }
 
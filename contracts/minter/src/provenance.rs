@@ -0,0 +1,14 @@
+use sha2::{Digest, Sha256};
+
+/// provenance hash of a collection: the sha256 of every token's metadata, hex encoded
+/// and concatenated in token_id order, hashed once more. the drop is fair when this
+/// matches the hash committed before `start_time` and the metadata at each
+/// `base_token_uri/{token_id}` is unchanged
+pub fn provenance_hash<T: AsRef<[u8]>>(ordered_metadata: &[T]) -> String {
+    let concatenated: String = ordered_metadata
+        .iter()
+        .map(|metadata| format!("{:x}", Sha256::digest(metadata.as_ref())))
+        .collect();
+
+    format!("{:x}", Sha256::digest(concatenated.as_bytes()))
+}
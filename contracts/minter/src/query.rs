@@ -1,13 +1,29 @@
 #[cfg(not(feature = "library"))]
-use cosmwasm_std::{entry_point, to_binary, Addr, Binary, Deps, Env, Order, StdResult};
-use cw_storage_plus::Bound;
+use cosmwasm_std::{
+    coin, entry_point, to_binary, Addr, Binary, Coin, Deps, Env, Order, StdError, StdResult,
+    Uint128,
+};
+use cw_storage_plus::{Bound, PrefixBound};
 use cw_utils::maybe_addr;
+use whitelist::msg::{CheckWhitelistResponse, QueryMsg as WhitelistQueryMsg};
 
-use crate::msg::{AddrBal, AddressValMsg, ConfigResponse, QueryMsg, TokenDataResponse};
+use crate::contract::{
+    apply_bulk_discount, auction_clearing_price, cw20_denom, dutch_auction_price,
+    minimum_raise_met, minimum_raise_missed, oracle_mint_price, quote_public_mint_price,
+    raffle_settled,
+};
+use crate::msg::{
+    AddrBal, AddressValMsg, AuctionPriceResponse, ConfigResponse, MintPriceResponse, MintType,
+    QueryMsg, RaffleResponse, RaiseStatusResponse, TokenDataResponse,
+};
 use crate::state::{
-    CollectionInfo, ADDRESS_MINT_TRACKER, AIRDROPPER_ADDR, BANK_BALANCES, BUNDLE_MINT_TRACKER,
+    AuctionPurchase, CollectionInfo, DrawRecord, MintTicket, PendingMint, Referral, TokenAuction,
+    ADDRESS_MINT_TRACKER, AIRDROPPER_ADDR, AUCTION_PURCHASES, BANK_BALANCES, BUNDLE_MINT_TRACKER,
     COLLECTION_CURRENT_TOKEN_SUPPLY, CONFIG, CURRENT_TOKEN_SUPPLY, CW721_ADDRS,
-    CW721_COLLECTION_INFO, WHITELIST_ADDR,
+    CW721_COLLECTION_INFO, DRAND_BEACONS, DRAND_CONFIG, DRAW_HISTORY, ESCROW_LOCKED_UNTIL,
+    MINT_CODES, MINT_RECEIPTS, MINT_TICKETS, PENDING_MINTS, RAFFLE_ENTRIES, RAFFLE_PROGRESS,
+    RAISE_CONTRIBUTIONS, RAISE_PROGRESS, RANDOMNESS_COMMIT, REFERRALS, SHUFFLE_PROGRESS,
+    TOKEN_AUCTIONS, TOKEN_MINT_ORDER, TOKEN_ORDER_MIGRATION, WHITELIST_ADDR,
 };
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -20,9 +36,11 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetAddressMints { start_after, limit } => {
             query_get_address_mints(deps, env, start_after, limit)
         }
-        QueryMsg::GetEscrowBalances { start_after, limit } => {
-            query_get_escrow_balances(deps, env, start_after, limit)
-        }
+        QueryMsg::GetEscrowBalances {
+            start_after,
+            start_after_denom,
+            limit,
+        } => query_get_escrow_balances(deps, env, start_after, start_after_denom, limit),
         QueryMsg::GetCw721CollectionInfo { start_after, limit } => to_binary(
             &query_get_cw721_collection_info(deps, env, start_after, limit)?,
         ),
@@ -34,6 +52,91 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         ),
         QueryMsg::GetRemainingTokens {} => query_get_remaining_tokens(deps, env),
         QueryMsg::GetCW721Addrs {} => query_get_cw721_addrs(deps, env),
+        QueryMsg::GetRandomnessCommit {} => to_binary(&RANDOMNESS_COMMIT.may_load(deps.storage)?),
+        QueryMsg::GetDrandConfig {} => to_binary(&DRAND_CONFIG.may_load(deps.storage)?),
+        QueryMsg::GetDrandBeacon { round } => {
+            to_binary(&DRAND_BEACONS.may_load(deps.storage, round)?)
+        }
+        QueryMsg::GetPendingMints { start_after, limit } => {
+            to_binary(&query_get_pending_mints(deps, env, start_after, limit)?)
+        }
+        QueryMsg::GetShuffleProgress {} => to_binary(&SHUFFLE_PROGRESS.may_load(deps.storage)?),
+        QueryMsg::GetTokenOrderMigration {} => {
+            to_binary(&TOKEN_ORDER_MIGRATION.may_load(deps.storage)?)
+        }
+        QueryMsg::GetTokenMintOrder {
+            collection_id,
+            start_after,
+            limit,
+        } => to_binary(&query_get_token_mint_order(
+            deps,
+            env,
+            collection_id,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::GetDrawHistory { start_after, limit } => {
+            to_binary(&query_get_draw_history(deps, env, start_after, limit)?)
+        }
+        QueryMsg::GetMintPrice {
+            quantity,
+            minter_address,
+            collection_id,
+            denom,
+        } => to_binary(&query_get_mint_price(
+            deps,
+            env,
+            quantity,
+            minter_address,
+            collection_id,
+            denom,
+        )?),
+        QueryMsg::GetAuctionPrice {} => to_binary(&query_get_auction_price(deps, env)?),
+        QueryMsg::GetAuctionPurchase { minter_address } => {
+            to_binary(&query_get_auction_purchase(deps, env, minter_address)?)
+        }
+        QueryMsg::GetMintTickets {
+            minter_address,
+            start_after,
+            limit,
+        } => to_binary(&query_get_mint_tickets(
+            deps,
+            env,
+            minter_address,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::GetReferrals { start_after, limit } => {
+            to_binary(&query_get_referrals(deps, env, start_after, limit)?)
+        }
+        QueryMsg::GetMintCode { hash } => {
+            to_binary(&MINT_CODES.may_load(deps.storage, hash.to_lowercase())?)
+        }
+        QueryMsg::GetMintReceipt {
+            collection_id,
+            token_id,
+        } => to_binary(&MINT_RECEIPTS.may_load(deps.storage, (collection_id, token_id))?),
+        QueryMsg::GetEscrowLockedUntil {} => {
+            to_binary(&ESCROW_LOCKED_UNTIL.may_load(deps.storage)?)
+        }
+        QueryMsg::GetRaiseStatus {} => to_binary(&query_raise_status(deps, env)?),
+        QueryMsg::GetRaiseContributions { address } => {
+            to_binary(&query_raise_contributions(deps, env, address)?)
+        }
+        QueryMsg::GetRaffle {} => to_binary(&query_raffle(deps, env)?),
+        QueryMsg::GetRaffleEntry { address } => {
+            to_binary(&RAFFLE_ENTRIES.may_load(deps.storage, deps.api.addr_validate(&address)?)?)
+        }
+        QueryMsg::GetRaffleWinners { start_after, limit } => {
+            to_binary(&query_raffle_winners(deps, env, start_after, limit)?)
+        }
+        QueryMsg::GetTokenAuction {
+            collection_id,
+            token_id,
+        } => to_binary(&TOKEN_AUCTIONS.may_load(deps.storage, (collection_id, token_id))?),
+        QueryMsg::GetTokenAuctions { start_after, limit } => {
+            to_binary(&query_token_auctions(deps, env, start_after, limit)?)
+        }
     }
 }
 
@@ -59,9 +162,167 @@ fn query_config(deps: Deps, _env: Env) -> StdResult<ConfigResponse> {
         extension: config.extension,
         bundle_enabled: config.bundle_enabled,
         bundle_completed: config.bundle_completed,
+        dutch_auction: config.dutch_auction,
+        bonding_curve: config.bonding_curve,
+        bulk_discounts: config.bulk_discounts,
+        collection_selection: config.collection_selection,
+        denom_mint_prices: config.denom_mint_prices,
+        cw20_payment: config.cw20_payment,
+        oracle_pricing: config.oracle_pricing,
+        referral_bps: config.referral_bps,
+        refund_period: config.refund_period,
+        minimum_raise: config.minimum_raise,
+        raffle: config.raffle,
     })
 }
 
+fn query_get_mint_price(
+    deps: Deps,
+    env: Env,
+    quantity: u32,
+    minter_address: Option<String>,
+    collection_id: Option<u64>,
+    denom: Option<String>,
+) -> StdResult<MintPriceResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let minter_addr = maybe_addr(deps.api, minter_address)?;
+
+    let cw20_payment = config
+        .cw20_payment
+        .clone()
+        .filter(|cw20_payment| denom == Some(cw20_denom(&cw20_payment.token_addr)));
+
+    let denom_price = match (&cw20_payment, denom) {
+        (Some(cw20_payment), _) => Some(coin(
+            cw20_payment.mint_price.u128(),
+            cw20_denom(&cw20_payment.token_addr),
+        )),
+        (None, Some(denom)) if denom != config.mint_denom => Some(
+            config
+                .denom_mint_prices
+                .iter()
+                .flatten()
+                .find(|price| price.denom == denom)
+                .cloned()
+                .ok_or_else(|| StdError::generic_err(format!("{} is not a mint denom", denom)))?,
+        ),
+        _ => None,
+    };
+
+    let mut check_wl: Option<CheckWhitelistResponse> = None;
+
+    // the whitelist only takes `mint_denom` and the cw20 token
+    if let (Some(whitelist_addr), Some(minter_addr), true) = (
+        WHITELIST_ADDR.may_load(deps.storage)?,
+        &minter_addr,
+        denom_price.is_none() || cw20_payment.is_some(),
+    ) {
+        if env.block.time < config.start_time {
+            let wl_config: CheckWhitelistResponse = deps.querier.query_wasm_smart(
+                whitelist_addr,
+                &WhitelistQueryMsg::CheckWhitelist {
+                    minter_address: minter_addr.to_string(),
+                },
+            )?;
+
+            if wl_config.is_on_whitelist {
+                check_wl = Some(wl_config);
+            }
+        }
+    }
+
+    let (price, discount_bps, reference_price) = match check_wl {
+        Some(wl_config) => {
+            let unit_price = match &cw20_payment {
+                Some(cw20_payment) => cw20_payment.whitelist_mint_price.ok_or_else(|| {
+                    StdError::generic_err("whitelist mints can't be paid in the cw20 token")
+                })?,
+                None => wl_config.mint_price,
+            };
+
+            let (price, discount_bps) = apply_bulk_discount(
+                &config,
+                &MintType::Whitelist,
+                wl_config.current_mint_count,
+                quantity,
+                unit_price.checked_mul(Uint128::from(quantity))?,
+            );
+
+            (price, discount_bps, None)
+        }
+        None => {
+            let current_mint_count = match minter_addr {
+                Some(minter_addr) => {
+                    (ADDRESS_MINT_TRACKER.may_load(deps.storage, minter_addr)?).unwrap_or(0)
+                }
+                None => 0,
+            };
+
+            let price = match &denom_price {
+                Some(denom_price) => denom_price.amount.checked_mul(Uint128::from(quantity))?,
+                None => {
+                    quote_public_mint_price(deps.storage, &env, &config, quantity, collection_id)?
+                }
+            };
+
+            let (price, discount_bps) = apply_bulk_discount(
+                &config,
+                &MintType::Public,
+                current_mint_count,
+                quantity,
+                price,
+            );
+
+            // `mint_denom` prices are in the oracle's reference unit
+            match (&config.oracle_pricing, &denom_price) {
+                (Some(oracle_pricing), None) => (
+                    oracle_mint_price(deps, oracle_pricing, &config.mint_denom, price)?,
+                    discount_bps,
+                    Some(price),
+                ),
+                _ => (price, discount_bps, None),
+            }
+        }
+    };
+
+    Ok(MintPriceResponse {
+        price,
+        discount_bps,
+        reference_price,
+        denom: denom_price
+            .map(|denom_price| denom_price.denom)
+            .unwrap_or(config.mint_denom),
+    })
+}
+
+fn query_get_auction_price(deps: Deps, env: Env) -> StdResult<AuctionPriceResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let auction = config
+        .dutch_auction
+        .clone()
+        .ok_or_else(|| StdError::generic_err("No Dutch auction configured"))?;
+
+    let (price, next_drop) = dutch_auction_price(&auction, config.start_time, env.block.time);
+    let clearing_price = auction_clearing_price(deps.storage, &env, &config, &auction)?;
+
+    Ok(AuctionPriceResponse {
+        price,
+        next_price: next_drop.map(|(_, next_price)| next_price),
+        next_drop_time: next_drop.map(|(time, _)| time),
+        clearing_price,
+    })
+}
+
+fn query_get_auction_purchase(
+    deps: Deps,
+    _env: Env,
+    minter_address: String,
+) -> StdResult<Option<AuctionPurchase>> {
+    let minter_addr = deps.api.addr_validate(&minter_address)?;
+
+    AUCTION_PURCHASES.may_load(deps.storage, minter_addr)
+}
+
 fn query_check_address_mints(deps: Deps, minter_address: String) -> StdResult<Binary> {
     let minter_addr: Addr = deps.api.addr_validate(&minter_address)?;
 
@@ -109,25 +370,85 @@ fn query_get_remaining_tokens(deps: Deps, _env: Env) -> StdResult<Binary> {
     })
 }
 
-fn query_get_escrow_balances(
+fn query_get_pending_mints(
     deps: Deps,
     _env: Env,
     start_after: Option<String>,
     limit: Option<u32>,
-) -> StdResult<Binary> {
-    let start_after = maybe_addr(deps.api, start_after)?;
-    let start = start_after.map(Bound::<Addr>::exclusive);
+) -> StdResult<Vec<PendingMint>> {
+    let start = start_after.map(Bound::<String>::exclusive);
+
+    let limit = limit.unwrap_or(100).min(100) as usize;
+
+    PENDING_MINTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, pending_mint) = item?;
+            Ok(pending_mint)
+        })
+        .collect::<StdResult<Vec<PendingMint>>>()
+}
+
+fn query_get_mint_tickets(
+    deps: Deps,
+    _env: Env,
+    minter_address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<MintTicket>> {
+    let minter_addr = deps.api.addr_validate(&minter_address)?;
+    let start = start_after.map(Bound::<u64>::exclusive);
 
     let limit = limit.unwrap_or(100).min(100) as usize;
 
-    let balances = BANK_BALANCES
+    MINT_TICKETS
+        .prefix(minter_addr)
         .range(deps.storage, start, None, Order::Ascending)
         .take(limit)
         .map(|item| {
-            let (addr, balance) = item?;
-            Ok(AddrBal { addr, balance })
+            let (_, ticket) = item?;
+            Ok(ticket)
+        })
+        .collect::<StdResult<Vec<MintTicket>>>()
+}
+
+fn query_get_escrow_balances(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<String>,
+    start_after_denom: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let start_after = maybe_addr(deps.api, start_after)?;
+
+    let limit = limit.unwrap_or(100).min(100) as usize;
+
+    // without a denom, every balance of `start_after` is skipped
+    let balances = match (start_after, start_after_denom) {
+        (Some(addr), Some(denom)) => BANK_BALANCES.range(
+            deps.storage,
+            Some(Bound::exclusive((addr, denom))),
+            None,
+            Order::Ascending,
+        ),
+        (start_after, _) => BANK_BALANCES.prefix_range(
+            deps.storage,
+            start_after.map(PrefixBound::exclusive),
+            None,
+            Order::Ascending,
+        ),
+    }
+    .take(limit)
+    .map(|item| {
+        let ((addr, denom), balance) = item?;
+        Ok(AddrBal {
+            addr,
+            denom,
+            balance,
         })
-        .collect::<StdResult<Vec<AddrBal>>>();
+    })
+    .collect::<StdResult<Vec<AddrBal>>>();
 
     to_binary(&balances.unwrap())
 }
@@ -169,6 +490,130 @@ fn query_get_cw721_collection_info(
     Ok(tokens.unwrap())
 }
 
+fn query_get_token_mint_order(
+    deps: Deps,
+    _env: Env,
+    collection_id: u64,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u32, u32)>> {
+    let start = start_after.map(Bound::exclusive);
+
+    let limit = limit.unwrap_or(100).min(100) as usize;
+
+    TOKEN_MINT_ORDER
+        .prefix(collection_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()
+}
+
+fn query_get_draw_history(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, DrawRecord)>> {
+    let start = start_after.map(Bound::exclusive);
+
+    let limit = limit.unwrap_or(100).min(100) as usize;
+
+    DRAW_HISTORY
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()
+}
+
+fn query_get_referrals(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, Referral)>> {
+    let start_after = maybe_addr(deps.api, start_after)?;
+    let start = start_after.map(Bound::<Addr>::exclusive);
+
+    let limit = limit.unwrap_or(100).min(100) as usize;
+
+    REFERRALS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()
+}
+
+fn query_raise_status(deps: Deps, env: Env) -> StdResult<RaiseStatusResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    Ok(RaiseStatusResponse {
+        progress: (RAISE_PROGRESS.may_load(deps.storage)?).unwrap_or_default(),
+        met: minimum_raise_met(deps.storage, &config)?,
+        missed: minimum_raise_missed(deps.storage, &env, &config)?,
+        minimum_raise: config.minimum_raise,
+    })
+}
+
+fn query_raise_contributions(deps: Deps, _env: Env, address: String) -> StdResult<Vec<Coin>> {
+    let addr = deps.api.addr_validate(&address)?;
+
+    RAISE_CONTRIBUTIONS
+        .prefix(addr)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (denom, amount) = item?;
+            Ok(coin(amount.u128(), denom))
+        })
+        .collect::<StdResult<Vec<_>>>()
+}
+
+fn query_raffle(deps: Deps, _env: Env) -> StdResult<RaffleResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    Ok(RaffleResponse {
+        progress: (RAFFLE_PROGRESS.may_load(deps.storage)?).unwrap_or_default(),
+        settled: config.raffle.is_some() && raffle_settled(deps.storage, &config)?,
+        raffle: config.raffle,
+    })
+}
+
+fn query_raffle_winners(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, u32)>> {
+    let start_after = maybe_addr(deps.api, start_after)?;
+    let start = start_after.map(Bound::<Addr>::exclusive);
+
+    let limit = limit.unwrap_or(100).min(100) as usize;
+
+    RAFFLE_ENTRIES
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| item.as_ref().map_or(true, |(_, entry)| entry.won > 0))
+        .take(limit)
+        .map(|item| {
+            let (addr, entry) = item?;
+            Ok((addr, entry.won))
+        })
+        .collect::<StdResult<Vec<_>>>()
+}
+
+fn query_token_auctions(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<(u64, u32)>,
+    limit: Option<u32>,
+) -> StdResult<Vec<TokenAuction>> {
+    let start = start_after.map(Bound::exclusive);
+
+    let limit = limit.unwrap_or(100).min(100) as usize;
+
+    TOKEN_AUCTIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| Ok(item?.1))
+        .collect::<StdResult<Vec<_>>>()
+}
+
 fn query_get_bundle_mint_tracker(
     deps: Deps,
     _env: Env,
@@ -1,6 +1,7 @@
+use crate::msg::MintType;
 use cosmwasm_schema::cw_serde;
 
-use cosmwasm_std::{Addr, Timestamp, Uint128};
+use cosmwasm_std::{Addr, Binary, Coin, Timestamp, Uint128};
 use cw_storage_plus::{Item, Map};
 
 #[cw_serde]
@@ -21,6 +22,195 @@ pub struct Config {
     pub bundle_enabled: bool,
     pub bundle_completed: bool,
     pub bonded_denom: String,
+    pub dutch_auction: Option<DutchAuction>,
+    pub bonding_curve: Option<BondingCurve>,
+    pub bulk_discounts: Option<BulkDiscounts>,
+    pub collection_selection: Option<CollectionSelection>,
+    /// price of one public mint token in each denom accepted besides `mint_denom`
+    pub denom_mint_prices: Option<Vec<Coin>>,
+    pub cw20_payment: Option<Cw20Payment>,
+    pub oracle_pricing: Option<OraclePricing>,
+    /// share of each paid mint escrowed for its referrer, before the revenue split
+    pub referral_bps: Option<u32>,
+    /// seconds a paid token can be sent back for a refund after it is minted
+    pub refund_period: Option<u64>,
+    /// threshold the drop has to reach by `end_time` to go ahead
+    pub minimum_raise: Option<MinimumRaise>,
+    /// hands out the public mint by raffle instead of first come first served
+    pub raffle: Option<Raffle>,
+}
+
+/// paid tokens, or revenue in `mint_denom`, to raise by `end_time`. until it is met
+/// every payment is escrowed, and if it is missed each payer can claim theirs back
+#[cw_serde]
+pub enum MinimumRaise {
+    Tokens(u32),
+    Revenue(Uint128),
+}
+
+/// paid tokens and `mint_denom` revenue counted towards the `minimum_raise`
+#[cw_serde]
+#[derive(Default)]
+pub struct RaiseProgress {
+    pub tokens_sold: u32,
+    pub revenue: Uint128,
+}
+
+/// addresses enter up to `max_tickets_per_address` tickets at `mint_price` until
+/// `deadline`. `SettleRaffle` then draws a token for each winning ticket and the losing
+/// ones are refundable. the public mint opens for what is left once it is settled.
+/// winners are drawn with the next drand round or a secret committed after `deadline`
+#[cw_serde]
+pub struct Raffle {
+    pub deadline: Timestamp,
+    pub max_tickets_per_address: u32,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct RaffleProgress {
+    pub ticket_count: u32,
+    /// `mint_price` when the first ticket was entered, paid for every ticket
+    pub ticket_price: Option<Coin>,
+    /// supply held back in `PENDING_MINT_COUNT` for the winners still to be drawn
+    pub reserved: u32,
+    /// tickets that win, set by the reserved supply on the first `SettleRaffle`
+    pub winner_count: Option<u32>,
+    pub winners_drawn: u32,
+    /// commitment that was hidden when the first `SettleRaffle` came in, drawn with
+    /// once revealed
+    pub commitment: Option<String>,
+    /// drand round published after the first `SettleRaffle`, drawn with once submitted
+    pub drand_round: Option<u64>,
+}
+
+#[cw_serde]
+pub struct RaffleEntry {
+    pub tickets: u32,
+    pub won: u32,
+    /// the losing tickets were paid back
+    pub refunded: bool,
+}
+
+/// English auction of a 1:1 held out of the draw. bids are escrowed in `denom` and
+/// have to beat the highest one by `min_increment`. a bid in the last `extension`
+/// seconds pushes `end_time` back to `extension` seconds after it
+#[cw_serde]
+pub struct TokenAuction {
+    pub collection_id: u64,
+    pub token_id: u32,
+    pub denom: String,
+    pub reserve_price: Uint128,
+    pub min_increment: Uint128,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub extension: u64,
+    pub highest_bid: Option<TokenAuctionBid>,
+    /// the token was minted to the winner, or went back into the draw without bids
+    pub settled: bool,
+}
+
+#[cw_serde]
+pub struct TokenAuctionBid {
+    pub bidder: Addr,
+    pub amount: Uint128,
+}
+
+/// `mint_price`, `bundle_mint_price`, collection prices and the bonding curve are in a
+/// reference unit such as micro-USD, converted to `mint_denom` at mint time with the
+/// rate of `oracle_addr`. a payment has to cover the converted price and can be up to
+/// `slippage_bps` over it, and anything paid over it is refunded
+#[cw_serde]
+pub struct OraclePricing {
+    pub oracle_addr: Addr,
+    pub slippage_bps: u32,
+}
+
+/// prices in the cw20 `token_addr`, paid by sending the token with `ReceiveMsg`.
+/// escrow keeps the token under the denom `cw20:<token_addr>`
+#[cw_serde]
+pub struct Cw20Payment {
+    pub token_addr: Addr,
+    pub mint_price: Uint128,
+    /// `None` if whitelisted addresses can't pay in the token
+    pub whitelist_mint_price: Option<Uint128>,
+    pub bundle_mint_price: Uint128,
+}
+
+/// how a random mint picks its collection
+#[cw_serde]
+pub enum CollectionSelection {
+    /// every collection with tokens left is equally likely
+    Uniform,
+    /// collections are picked in proportion to their `weight`, or to their remaining
+    /// supply when no weight is set
+    Weighted,
+}
+
+/// discount tiers of the whitelist and public mints. the tier is picked by the number
+/// of tokens an address will have minted in that phase, counting the ones being bought,
+/// and applies to every token of the mint
+#[cw_serde]
+pub struct BulkDiscounts {
+    pub whitelist: Vec<DiscountTier>,
+    pub public: Vec<DiscountTier>,
+}
+
+/// `discount_bps` off from `min_quantity` tokens until the next tier
+#[cw_serde]
+pub struct DiscountTier {
+    pub min_quantity: u32,
+    pub discount_bps: u32,
+}
+
+/// what a mint code takes off the price of each token it is used for
+#[cw_serde]
+pub enum MintCodeDiscount {
+    Bps(u32),
+    /// the token costs this much in `mint_denom`, or its usual price if lower
+    FixedPrice(Uint128),
+}
+
+/// code registered by the sha256 of `<address>:<code>`, good for `uses_remaining` more
+/// tokens minted by `address`
+#[cw_serde]
+pub struct MintCode {
+    pub uses_remaining: u32,
+    pub discount: MintCodeDiscount,
+}
+
+/// public mint price as a function of `sold`, the tokens already minted or reserved
+#[cw_serde]
+pub enum BondingCurve {
+    /// `base_price + slope * sold`
+    Linear { base_price: Uint128, slope: Uint128 },
+    /// `base_price * (1 + growth_bps / 10000) ^ sold`
+    Exponential {
+        base_price: Uint128,
+        growth_bps: u32,
+    },
+}
+
+/// Dutch auction for the public mint. the price starts at `start_price` at
+/// `start_time` and drops by `step` every `decay_interval` seconds until it
+/// reaches `floor_price`
+#[cw_serde]
+pub struct DutchAuction {
+    pub start_price: Uint128,
+    pub floor_price: Uint128,
+    /// seconds
+    pub decay_interval: u64,
+    pub step: Uint128,
+    /// refund public buyers the difference down to the clearing price. everything
+    /// paid above `floor_price` is held until `SettleAuctionRebate`
+    pub rebate: bool,
+}
+
+/// public auction mints of an address that are waiting on their rebate
+#[cw_serde]
+pub struct AuctionPurchase {
+    pub count: u32,
+    pub paid: Uint128,
 }
 
 #[cw_serde]
@@ -31,6 +221,10 @@ pub struct CollectionInfo {
     pub symbol: String,
     pub base_token_uri: String,
     pub secondary_metadata_uri: Option<String>,
+    pub provenance_hash: Option<String>,
+    pub mint_price: Option<Uint128>,
+    pub mint_enabled: Option<bool>,
+    pub weight: Option<u32>,
 }
 
 #[cw_serde]
@@ -46,8 +240,225 @@ pub struct RoyaltyInfo {
     pub is_primary: bool,
 }
 
+/// `commitment` is the hex encoded sha256 of `secret`. `secret` stays `None`
+/// until it is revealed. mints paid in the meantime are tickets drawn with it
+#[cw_serde]
+pub struct RandomnessCommit {
+    pub commitment: String,
+    pub secret: Option<String>,
+    pub committed_at: Timestamp,
+}
+
+/// mint that was paid for and is waiting on randomness from `RANDOMNESS_PROXY`.
+/// the payment stays in the contract until the proxy calls back, or is refunded
+/// after `expires_at_height`
+#[cw_serde]
+pub struct PendingMint {
+    pub job_id: String,
+    pub minter_addr: Addr,
+    pub expires_at_height: u64,
+    pub mint_type: MintType,
+    /// number of tokens drawn when the randomness arrives
+    pub quantity: u32,
+    pub paid: Uint128,
+    pub denom: String,
+    /// credited with the mint once it is drawn
+    pub referrer: Option<Addr>,
+}
+
+/// what a token was paid when it can be refunded. `paid` is already split into
+/// `BANK_BALANCES`, `referrer` included, and is taken back out of them on a refund
+#[cw_serde]
+pub struct MintReceipt {
+    pub paid: Coin,
+    pub referrer: Option<Addr>,
+    /// part of `paid` credited to `referrer` at the `referral_bps` of the mint
+    pub referral_reward: Uint128,
+    pub refundable_until: Timestamp,
+}
+
+/// paid mint waiting to be drawn in a later block. the payment stays in the
+/// contract until the ticket is settled, or refunded after `expires_at_height`
+#[cw_serde]
+pub struct MintTicket {
+    pub minter_addr: Addr,
+    pub height: u64,
+    pub expires_at_height: u64,
+    pub mint_type: MintType,
+    /// number of tokens drawn when the ticket is settled
+    pub quantity: u32,
+    pub paid: Uint128,
+    /// denom `paid` is in. `None` for tickets that paid in `mint_denom`
+    /// before the denom was recorded
+    pub denom: Option<String>,
+    /// credited with the mint when the ticket is settled
+    pub referrer: Option<Addr>,
+    /// commitment that was still hidden when the ticket was paid. the ticket is
+    /// drawn with its secret once revealed
+    pub commitment: Option<String>,
+    /// drand round published after the ticket was paid, drawn with once submitted
+    pub drand_round: Option<u64>,
+}
+
+/// mints an address referred, and its rewards in each denom they were paid in
+#[cw_serde]
+pub struct Referral {
+    pub mint_count: u32,
+    pub earnings: Vec<Coin>,
+}
+
+/// cursor of a `ShuffleTokenOrder` that is part-way through. `collection_id` is
+/// swapped from `position` down to 0, then the next collection starts from its end
+#[cw_serde]
+pub struct ShuffleProgress {
+    pub collection_id: u64,
+    pub position: u32,
+    pub swaps_done: u64,
+    pub total_swaps: u64,
+}
+
+/// cursor of a `MigrateTokenOrder` that is part-way through. positions of
+/// `collection_id` before `position` are in the swap table, and collections already
+/// moved are dropped from `LEGACY_CW721_SHUFFLED_TOKEN_IDS`
+#[cw_serde]
+pub struct TokenOrderMigration {
+    pub collection_id: u64,
+    pub position: u32,
+}
+
+/// one seeded draw. every `seed` is hashed with sha256 and its first 16 bytes seed
+/// `Xoshiro128PlusPlus`, so the result can be re-run offline
+#[cw_serde]
+pub struct DrawRecord {
+    pub height: u64,
+    pub kind: DrawKind,
+}
+
+#[cw_serde]
+pub enum DrawKind {
+    /// a collection out of `available_collection_ids`, then a position out of that
+    /// collection's `supply`
+    Mint {
+        collection_seed: String,
+        available_collection_ids: Vec<u64>,
+        /// weights of `available_collection_ids` with `CollectionSelection::Weighted`.
+        /// the collection draw is then a ticket out of their sum, see `weighted_index`
+        collection_weights: Option<Vec<u32>>,
+        collection_id: u64,
+        index_seed: String,
+        supply: u32,
+        index: u32,
+    },
+    /// a position out of `supply` for one collection of a bundle
+    BundleMint {
+        seed: String,
+        collection_id: u64,
+        supply: u32,
+        index: u32,
+    },
+    /// Fisher-Yates swaps of `collection_id` from `from_position` down to `to_position`
+    Shuffle {
+        seed: String,
+        collection_id: u64,
+        from_position: u32,
+        to_position: u32,
+    },
+    /// a position out of `supply` for a mint that picked `collection_id`
+    CollectionMint {
+        seed: String,
+        collection_id: u64,
+        supply: u32,
+        index: u32,
+    },
+    /// mint drawn with randomness that was unknown when it was paid. `job_id` is the
+    /// proxy job, `ticket-<minter>-<height>-<nonce>` for a ticket, or
+    /// `raffle-mint-<position>` for a raffle winner. the seed is
+    /// `randomness` followed by the bytes of `job_id`, and the collection is drawn out
+    /// of `available_collection_ids` like `Mint`
+    DeferredMint {
+        job_id: String,
+        randomness: Binary,
+        available_collection_ids: Vec<u64>,
+        collection_weights: Option<Vec<u32>>,
+        collection_id: u64,
+        supply: u32,
+        index: u32,
+    },
+    /// winner `position` of a raffle, `index` out of the `tickets` not drawn yet. the
+    /// ticket drawn is number `position + index`. the seed is `randomness` followed by
+    /// the bytes of `draw_id`, `raffle-ticket-<position>`
+    RaffleTicket {
+        draw_id: String,
+        randomness: Binary,
+        position: u32,
+        tickets: u32,
+        index: u32,
+    },
+}
+
+/// drand network beacons are checked against. round `r` is published at
+/// `genesis_time + (r - 1) * period` seconds
+#[cw_serde]
+pub struct DrandConfig {
+    /// BLS public key (compressed G1)
+    pub pubkey: Binary,
+    pub genesis_time: u64,
+    pub period: u64,
+}
+
+/// verified drand round. `randomness` is the sha256 of the round's signature
+#[cw_serde]
+pub struct DrandBeacon {
+    pub round: u64,
+    pub randomness: Binary,
+}
+
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const CW721_COLLECTION_INFO: Map<u64, CollectionInfo> = Map::new("cw721_collection_info");
+pub const RANDOMNESS_COMMIT: Item<RandomnessCommit> = Item::new("randomness_commit");
+/// commitment -> revealed secret, kept for tickets paid while it was hidden
+pub const RANDOMNESS_SECRETS: Map<String, String> = Map::new("randomness_secrets");
+pub const DRAND_CONFIG: Item<DrandConfig> = Item::new("drand_config");
+/// round -> verified beacon
+pub const DRAND_BEACONS: Map<u64, DrandBeacon> = Map::new("drand_beacons");
+pub const RANDOMNESS_PROXY: Item<Addr> = Item::new("randomness_proxy");
+pub const PENDING_MINTS: Map<String, PendingMint> = Map::new("pending_mints");
+/// tokens reserved for `PENDING_MINTS`, which can't be handed out to new mints
+pub const PENDING_MINT_COUNT: Item<u32> = Item::new("pending_mint_count");
+pub const MINT_JOB_COUNTER: Item<u64> = Item::new("mint_job_counter");
+pub const DELAYED_MINT_EXPIRY_BLOCKS: Item<u64> = Item::new("delayed_mint_expiry_blocks");
+/// (minter, height paid) -> ticket
+pub const MINT_TICKETS: Map<(Addr, u64), MintTicket> = Map::new("mint_tickets");
+pub const SHUFFLE_PROGRESS: Item<ShuffleProgress> = Item::new("shuffle_progress");
+/// set by `migrate` while the 0.1.x token order is still being moved into the swap table
+pub const TOKEN_ORDER_MIGRATION: Item<TokenOrderMigration> = Item::new("token_order_migration");
+pub const MINT_ORDER_COUNTER: Item<u32> = Item::new("mint_order_counter");
+/// (collection_id, token_id) -> mint_order, starting at 1 for the first token minted
+pub const TOKEN_MINT_ORDER: Map<(u64, u32), u32> = Map::new("token_mint_order");
+pub const DRAW_COUNTER: Item<u64> = Item::new("draw_counter");
+pub const DRAW_HISTORY: Map<u64, DrawRecord> = Map::new("draw_history");
+/// price of the latest public auction mint
+pub const AUCTION_LAST_PRICE: Item<Uint128> = Item::new("auction_last_price");
+/// last price paid when the public auction first sold out. kept even if tokens
+/// later go back into the draw
+pub const AUCTION_CLEARING_PRICE: Item<Uint128> = Item::new("auction_clearing_price");
+pub const AUCTION_PURCHASES: Map<Addr, AuctionPurchase> = Map::new("auction_purchases");
+pub const REFERRALS: Map<Addr, Referral> = Map::new("referrals");
+/// key is the hex encoded sha256 of the code
+pub const MINT_CODES: Map<String, MintCode> = Map::new("mint_codes");
+/// key is (collection_id, token_id)
+pub const MINT_RECEIPTS: Map<(u64, u32), MintReceipt> = Map::new("mint_receipts");
+/// `DisburseFunds` waits for the last refund window to close
+pub const ESCROW_LOCKED_UNTIL: Item<Timestamp> = Item::new("escrow_locked_until");
+pub const RAISE_PROGRESS: Item<RaiseProgress> = Item::new("raise_progress");
+/// key is (payer, denom). only payments made before the `minimum_raise` is met
+pub const RAISE_CONTRIBUTIONS: Map<(Addr, String), Uint128> = Map::new("raise_contributions");
+pub const RAFFLE_PROGRESS: Item<RaffleProgress> = Item::new("raffle_progress");
+pub const RAFFLE_ENTRIES: Map<Addr, RaffleEntry> = Map::new("raffle_entries");
+/// key is the ticket number. settling swaps each winner in front of the undrawn tickets
+pub const RAFFLE_TICKETS: Map<u32, Addr> = Map::new("raffle_tickets");
+/// key is (collection_id, token_id)
+pub const TOKEN_AUCTIONS: Map<(u64, u32), TokenAuction> = Map::new("token_auctions");
 
 // addresses
 pub const FEE_COLLECTION_ADDR: Item<Addr> = Item::new("fee_collection_addr");
@@ -64,5 +475,51 @@ pub const COLLECTION_CURRENT_TOKEN_SUPPLY: Map<u64, u32> =
 // trackers
 pub const ADDRESS_MINT_TRACKER: Map<Addr, u32> = Map::new("address_mint_tracker");
 pub const BUNDLE_MINT_TRACKER: Map<Addr, u32> = Map::new("bundle_mint_tracker");
-pub const BANK_BALANCES: Map<Addr, Uint128> = Map::new("bank_balances");
-pub const CW721_SHUFFLED_TOKEN_IDS: Map<u64, Vec<u32>> = Map::new("cw721_shuffled_token_ids");
+/// (recipient, denom) -> escrowed funds
+pub const BANK_BALANCES: Map<(Addr, String), Uint128> = Map::new("bank_denom_balances");
+/// Sparse swap table for the lazy Fisher-Yates draw. (collection_id, position) -> token_id
+/// Only positions that no longer hold their original token_id (`position + 1`) are stored
+pub const CW721_SHUFFLED_TOKEN_IDS: Map<(u64, u32), u32> = Map::new("cw721_shuffled_positions");
+/// Reverse lookup of `CW721_SHUFFLED_TOKEN_IDS`. (collection_id, token_id) -> position
+pub const CW721_TOKEN_ID_POSITIONS: Map<(u64, u32), u32> = Map::new("cw721_token_id_positions");
+/// 0.1.x layout that kept every collection's full shuffled vec. Moved into the swap table
+/// by `MigrateTokenOrder`
+pub const LEGACY_CW721_SHUFFLED_TOKEN_IDS: Map<u64, Vec<u32>> =
+    Map::new("cw721_shuffled_token_ids");
+/// escrow layout that only held `mint_denom`. Only read by `migrate`
+pub const LEGACY_BANK_BALANCES: Map<Addr, Uint128> = Map::new("bank_balances");
+/// 0.1.x `Config`, before any of the optional pricing and sale modes. Only read by `migrate`
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+/// 0.1.x `CollectionInfo`. Only read by `migrate`
+pub const LEGACY_CW721_COLLECTION_INFO: Map<u64, LegacyCollectionInfo> =
+    Map::new("cw721_collection_info");
+
+#[cw_serde]
+pub struct LegacyConfig {
+    pub admin: Addr,
+    pub maintainer_addr: Option<Addr>,
+    pub start_time: Timestamp,
+    pub end_time: Option<Timestamp>,
+    pub total_token_supply: u32,
+    pub max_per_address_mint: u32,
+    pub max_per_address_bundle_mint: u32,
+    pub mint_price: Uint128,
+    pub bundle_mint_price: Uint128,
+    pub mint_denom: String,
+    pub token_code_id: u64,
+    pub extension: SharedCollectionInfo,
+    pub escrow_funds: bool,
+    pub bundle_enabled: bool,
+    pub bundle_completed: bool,
+    pub bonded_denom: String,
+}
+
+#[cw_serde]
+pub struct LegacyCollectionInfo {
+    pub id: u64,
+    pub token_supply: u32,
+    pub name: String,
+    pub symbol: String,
+    pub base_token_uri: String,
+    pub secondary_metadata_uri: Option<String>,
+}
@@ -4,7 +4,7 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, schema_for};
 
 use whitelist::msg::{CheckWhitelistResponse, ConfigResponse};
-use whitelist::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use whitelist::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use whitelist::state::Config;
 
 fn main() {
@@ -16,6 +16,7 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(Config), &out_dir);
 
     export_schema_with_title(&schema_for!(ConfigResponse), &out_dir, "GetConfigResponse");
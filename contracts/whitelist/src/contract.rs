@@ -1,10 +1,11 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{entry_point, Deps, DepsMut, Env, MessageInfo, Response};
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw_utils::maybe_addr;
+use semver::Version;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg};
 use crate::state::{Config, ADDRESS_MINT_TRACKER, CONFIG, WHITELIST, WHITELIST_ADDRESS_COUNT};
 
 // version info for migration info
@@ -88,9 +89,58 @@ pub fn execute(
             execute_remove_from_whitelist(deps, env, info, addresses)
         }
         ExecuteMsg::UpdateAddressMintTracker(minter_address) => {
-            execute_update_address_mint_tracker(deps, env, info, &minter_address)
+            execute_update_address_mint_tracker(deps, env, info, &minter_address, 1)
         }
+        ExecuteMsg::UpdateAddressMintTrackerBy(minter_address, count) => {
+            execute_update_address_mint_tracker(deps, env, info, &minter_address, count)
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+
+    // only allow migrations from a previous version of this contract
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidMigrationContract {
+            stored: stored.contract,
+            expected: CONTRACT_NAME.to_string(),
+        });
     }
+
+    let stored_version = parse_contract_version(&stored.version)?;
+    let current_version = parse_contract_version(CONTRACT_VERSION)?;
+
+    if stored_version > current_version {
+        return Err(ContractError::CannotMigrateToOlderVersion {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    migrate_state(deps.branch(), &stored_version)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+/// Brings whitelist storage written by `stored_version` up to the current layout, one
+/// release at a time
+fn migrate_state(_deps: DepsMut, _stored_version: &Version) -> Result<(), ContractError> {
+    // 0.1.x -> 0.2.0 only added `UpdateAddressMintTrackerBy`. the config, whitelist
+    // and mint trackers are read as 0.1.x wrote them
+    Ok(())
+}
+
+fn parse_contract_version(version: &str) -> Result<Version, ContractError> {
+    Version::parse(version).map_err(|_| ContractError::InvalidContractVersion {
+        version: version.to_string(),
+    })
 }
 
 fn execute_update_config(
@@ -271,6 +321,7 @@ pub fn execute_update_address_mint_tracker(
     _env: Env,
     info: MessageInfo,
     minter_address: &str,
+    count: u32,
 ) -> Result<Response, ContractError> {
     check_can_execute(deps.as_ref(), &info)?;
 
@@ -285,11 +336,11 @@ pub fn execute_update_address_mint_tracker(
 
     let config = CONFIG.load(deps.storage)?;
 
-    if current_mint_count == config.max_per_address_mint {
+    if current_mint_count + count > config.max_per_address_mint {
         return Err(ContractError::MaxMintsReached(config.max_per_address_mint));
     }
 
-    let new_mint_count = current_mint_count + 1;
+    let new_mint_count = current_mint_count + count;
 
     ADDRESS_MINT_TRACKER.save(deps.storage, addr, &new_mint_count)?;
 
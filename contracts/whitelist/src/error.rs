@@ -36,6 +36,15 @@ pub enum ContractError {
     #[error("Max mint reached for address: max {0}")]
     MaxMintsReached(u32),
 
+    #[error("Cannot migrate contract {stored} to {expected}")]
+    InvalidMigrationContract { stored: String, expected: String },
+
+    #[error("Cannot migrate from version {stored} to older version {current}")]
+    CannotMigrateToOlderVersion { stored: String, current: String },
+
+    #[error("Invalid contract version: {version}")]
+    InvalidContractVersion { version: String },
+
     #[error("Custom Error val: {val:?}")]
     CustomError { val: String },
     // Add any other custom errors you like here.
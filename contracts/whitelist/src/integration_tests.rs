@@ -15,7 +15,8 @@ mod tests {
             crate::contract::execute,
             crate::contract::instantiate,
             crate::query::query,
-        );
+        )
+        .with_migrate(crate::contract::migrate);
         Box::new(contract)
     }
 
@@ -64,7 +65,7 @@ mod tests {
                 &msg,
                 &[],
                 "test",
-                None,
+                Some(ADMIN.to_string()),
             )
             .unwrap();
 
@@ -1740,6 +1741,27 @@ mod tests {
                 - check_whitelist_response.current_mint_count;
             assert_eq!(remaining_mint_count, 2);
 
+            // a multi-token mint can't go over the remaining mints
+            let msg = ExecuteMsg::UpdateAddressMintTrackerBy(valid_minter_address.clone(), 3);
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap_err();
+
+            let msg = ExecuteMsg::UpdateAddressMintTrackerBy(valid_minter_address.clone(), 2);
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            let check_whitelist_response: CheckWhitelistResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw_template_contract.addr(),
+                    &QueryMsg::CheckWhitelist {
+                        minter_address: valid_minter_address.clone(),
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(check_whitelist_response.current_mint_count, 3);
+
             // update time then ensure whitelist is closed
             app.update_block(|mut block| block.time = Timestamp::from_seconds(1771797428));
 
@@ -1813,4 +1835,145 @@ mod tests {
             )
         }
     }
+
+    mod migrate {
+        use super::*;
+        use crate::contract::{execute, migrate};
+        use crate::msg::MigrateMsg;
+        use crate::query::query;
+        use crate::state::{ADDRESS_MINT_TRACKER, WHITELIST, WHITELIST_ADDRESS_COUNT};
+        use crate::ContractError;
+        use cosmwasm_std::testing::mock_dependencies;
+        use cosmwasm_std::{from_binary, Storage};
+        use cw2::{get_contract_version, set_contract_version};
+
+        #[test]
+        fn migrate_during_whitelist() {
+            let (mut app, cw_template_contract) = proper_instantiate();
+            let init_addresses = proper_init_whitelist(&mut app, cw_template_contract.clone());
+
+            // whitelist is in progress
+            app.update_block(|mut block| block.time = Timestamp::from_seconds(1571797421));
+
+            let new_code_id = app.store_code(contract_template());
+
+            app.migrate_contract(
+                Addr::unchecked(USER),
+                cw_template_contract.addr(),
+                &MigrateMsg {},
+                new_code_id,
+            )
+            .unwrap_err();
+
+            app.migrate_contract(
+                Addr::unchecked(ADMIN),
+                cw_template_contract.addr(),
+                &MigrateMsg {},
+                new_code_id,
+            )
+            .unwrap();
+
+            let config: ConfigResponse = app
+                .wrap()
+                .query_wasm_smart(&cw_template_contract.addr(), &QueryMsg::GetConfig {})
+                .unwrap();
+
+            assert!(config.whitelist_in_progress);
+            assert_eq!(config.whitelist_address_count, 5);
+
+            let check_whitelist_response: CheckWhitelistResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw_template_contract.addr(),
+                    &QueryMsg::CheckWhitelist {
+                        minter_address: init_addresses[0].clone(),
+                    },
+                )
+                .unwrap();
+
+            assert!(check_whitelist_response.is_on_whitelist);
+        }
+
+        #[test]
+        fn migrate_from_0_1_state() {
+            let mut deps = mock_dependencies();
+
+            // storage as 0.1.0 wrote it
+            deps.storage.set(
+                b"config",
+                br#"{"admin":"admin","maintainer_addr":null,"start_time":"1571797419879305533","end_time":"1571797519879305533","max_whitelist_address_count":100,"max_per_address_mint":2,"mint_price":"1000000"}"#,
+            );
+            WHITELIST
+                .save(deps.as_mut().storage, Addr::unchecked(USER), &true)
+                .unwrap();
+            ADDRESS_MINT_TRACKER
+                .save(deps.as_mut().storage, Addr::unchecked(USER), &1)
+                .unwrap();
+            WHITELIST_ADDRESS_COUNT
+                .save(deps.as_mut().storage, &1)
+                .unwrap();
+            set_contract_version(deps.as_mut().storage, "crates.io:neta-whitelist", "0.1.0")
+                .unwrap();
+
+            migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+            let version = get_contract_version(deps.as_ref().storage).unwrap();
+            assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+            // a minter can count a multi-token mint against the 0.1.0 tracker
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(ADMIN, &[]),
+                ExecuteMsg::UpdateAddressMintTrackerBy(USER.to_string(), 1),
+            )
+            .unwrap();
+
+            let res: CheckWhitelistResponse = from_binary(
+                &query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::CheckWhitelist {
+                        minter_address: USER.to_string(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            assert!(res.is_on_whitelist);
+            assert_eq!(res.current_mint_count, 2);
+            assert_eq!(res.max_per_address_mint, 2);
+            assert_eq!(res.mint_price, Uint128::from(1_000_000u128));
+        }
+
+        #[test]
+        fn fail_migrate_older_version() {
+            let mut deps = mock_dependencies();
+            set_contract_version(deps.as_mut().storage, "crates.io:neta-whitelist", "99.0.0")
+                .unwrap();
+
+            let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+
+            assert!(matches!(
+                err,
+                ContractError::CannotMigrateToOlderVersion { .. }
+            ));
+
+            let version = get_contract_version(deps.as_ref().storage).unwrap();
+            assert_eq!(version.version, "99.0.0");
+        }
+
+        #[test]
+        fn fail_migrate_other_contract() {
+            let mut deps = mock_dependencies();
+            set_contract_version(deps.as_mut().storage, "crates.io:airdropper", "0.1.0").unwrap();
+
+            let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+
+            assert!(matches!(
+                err,
+                ContractError::InvalidMigrationContract { .. }
+            ));
+        }
+    }
 }
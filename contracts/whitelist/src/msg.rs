@@ -37,8 +37,16 @@ pub enum ExecuteMsg {
     /// For the address passed in, we'll increment their mint count by 1
     /// in the `ADDRESS_MINT_TRACKER`
     UpdateAddressMintTracker(String),
+    /// Same as `UpdateAddressMintTracker`, but increments the mint count by the
+    /// number of tokens minted. Sent by minters for multi-token mints
+    UpdateAddressMintTrackerBy(String, u32),
 }
 
+/// Migration message. The state transformations that run are selected from the
+/// cw2 version stored in the contract, so no fields are required
+#[cw_serde]
+pub struct MigrateMsg {}
+
 #[cw_serde]
 pub enum QueryMsg {
     /// Gets `state::Config` a